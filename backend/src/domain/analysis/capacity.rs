@@ -0,0 +1,331 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::constraints::ConstraintFinding;
+use super::exposure;
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{Diagram, Node};
+use crate::domain::model::requirements::Requirements;
+
+// シナリオの負荷から各層に必要な台数を見積もり、設計図の台数と比較する
+
+// 1台あたりの処理能力に対して、平常時に許容する使用率
+const TARGET_UTILIZATION: f64 = 0.7;
+// キャッシュがある場合にデータベースまで届くリクエストの割合
+const CACHE_MISS_RATIO: f64 = 0.2;
+// 必要台数の何倍以上を過剰とみなすか
+const OVER_PROVISION_FACTOR: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisionStatus {
+    Under,
+    Ok,
+    Over,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierCapacity {
+    #[serde(rename = "type")]
+    pub type_label: String,
+    pub node_ids: Vec<String>,
+    // この層が受けるピーク時の秒間リクエスト数
+    pub load_rps: u32,
+    pub rps_per_instance: u32,
+    pub required: u32,
+    // 常時稼働している台数と、オートスケール時の最大台数
+    pub provided: u32,
+    pub provided_max: u32,
+    pub status: ProvisionStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityPlan {
+    pub peak_rps: u32,
+    // ピーク時のリクエスト数をDAUから推定したか
+    pub estimated: bool,
+    pub tiers: Vec<TierCapacity>,
+}
+
+pub fn plan(
+    diagram: &Diagram,
+    catalog: &ComponentCatalog,
+    requirements: &Requirements,
+) -> Option<CapacityPlan> {
+    let (peak_rps, estimated) = requirements.effective_peak_rps()?;
+
+    // インターネットからのリクエストが届く層のみを対象にする (入口がなければ全体)
+    let reachable = exposure::analyze(diagram, catalog).internet_reachable;
+    let in_path = |node: &Node| reachable.is_empty() || reachable.contains(&node.id);
+    let has_cache = diagram
+        .nodes
+        .iter()
+        .any(|n| n.type_label == "Distributed Cache");
+
+    let mut by_type: BTreeMap<&str, Vec<&Node>> = BTreeMap::new();
+    for node in diagram.nodes.iter().filter(|n| in_path(n)) {
+        let has_profile = catalog
+            .get(&node.type_label)
+            .is_some_and(|c| c.semantics.capacity.is_some());
+        if has_profile {
+            by_type
+                .entry(node.type_label.as_str())
+                .or_default()
+                .push(node);
+        }
+    }
+
+    let tiers = by_type
+        .into_iter()
+        .filter_map(|(type_label, nodes)| {
+            let def = catalog.get(type_label)?;
+            let rps_per_instance = def.semantics.capacity?.rps_per_instance.max(1);
+            let is_database = def.category == "database";
+
+            let load_rps = if is_database && has_cache {
+                (peak_rps as f64 * CACHE_MISS_RATIO).ceil() as u32
+            } else {
+                peak_rps
+            };
+            let required = ((load_rps as f64 / (rps_per_instance as f64 * TARGET_UTILIZATION))
+                .ceil() as u32)
+                .max(1);
+
+            // データベースはリードレプリカも読み取りの処理能力に数える
+            let replicas = |n: &Node| {
+                if is_database {
+                    n.properties.read_replicas.unwrap_or(0)
+                } else {
+                    0
+                }
+            };
+            let provided: u32 = nodes.iter().map(|n| n.instance_count() + replicas(n)).sum();
+            let provided_max: u32 = nodes
+                .iter()
+                .map(|n| {
+                    let max = n.properties.autoscaling.map(|a| a.max).unwrap_or(0);
+                    n.instance_count().max(max) + replicas(n)
+                })
+                .sum();
+
+            let status = if provided_max < required {
+                ProvisionStatus::Under
+            } else if provided >= required * OVER_PROVISION_FACTOR && provided > required + 1 {
+                ProvisionStatus::Over
+            } else {
+                ProvisionStatus::Ok
+            };
+
+            Some(TierCapacity {
+                type_label: type_label.to_string(),
+                node_ids: nodes.iter().map(|n| n.id.clone()).collect(),
+                load_rps,
+                rps_per_instance,
+                required,
+                provided,
+                provided_max,
+                status,
+            })
+        })
+        .collect();
+
+    Some(CapacityPlan {
+        peak_rps,
+        estimated,
+        tiers,
+    })
+}
+
+// 台数の過不足を、スコアの上限となる制約に変換する
+pub fn findings(plan: &CapacityPlan) -> Vec<ConstraintFinding> {
+    plan.tiers
+        .iter()
+        .filter_map(|tier| {
+            let (dimension, max_score, message) = match tier.status {
+                ProvisionStatus::Under => (
+                    "scalability",
+                    50,
+                    format!(
+                        "{} はピーク時に約{}台必要ですが、最大{}台しかありません。",
+                        tier.type_label, tier.required, tier.provided_max
+                    ),
+                ),
+                ProvisionStatus::Over => (
+                    "costEfficiency",
+                    70,
+                    format!(
+                        "{} は約{}台で足りる負荷に対し、常時{}台が稼働しています。",
+                        tier.type_label, tier.required, tier.provided
+                    ),
+                ),
+                ProvisionStatus::Ok => return None,
+            };
+            Some(ConstraintFinding {
+                id: format!("capacity:{}", tier.type_label),
+                dimension,
+                max_score,
+                message,
+                node_ids: tier.node_ids.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    fn diagram(nodes: Vec<Value>, edges: &[(&str, &str)]) -> Diagram {
+        let edges: Vec<Value> = edges
+            .iter()
+            .map(|(source, target)| json!({ "source": source, "target": target }))
+            .collect();
+        serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+    }
+
+    fn node(id: &str, node_type: &str, properties: Value) -> Value {
+        json!({ "id": id, "type": node_type, "position": { "x": 0, "y": 0 }, "properties": properties })
+    }
+
+    fn peak(rps: u32) -> Requirements {
+        Requirements {
+            peak_rps: Some(rps),
+            ..Default::default()
+        }
+    }
+
+    // Web Browser → Load Balancer → App Server → RDBMS (SQL)
+    fn web(app: Value, db: Value, with_cache: bool) -> Diagram {
+        let mut nodes = vec![
+            node("browser", "Web Browser", json!({})),
+            node("lb", "Load Balancer", json!({})),
+            node("app", "App Server", app),
+            node("db", "RDBMS (SQL)", db),
+        ];
+        let mut edges = vec![("browser", "lb"), ("lb", "app"), ("app", "db")];
+        if with_cache {
+            nodes.push(node("cache", "Distributed Cache", json!({})));
+            edges.push(("app", "cache"));
+        }
+        diagram(nodes, &edges)
+    }
+
+    fn tier<'a>(plan: &'a CapacityPlan, type_label: &str) -> &'a TierCapacity {
+        plan.tiers
+            .iter()
+            .find(|t| t.type_label == type_label)
+            .unwrap()
+    }
+
+    #[test]
+    fn app_servers_are_compared_with_the_required_count() {
+        // 700 rps / (200 rps × 0.7) = 5台
+        let cases = [
+            (json!({ "replicas": 2 }), 2, 2, ProvisionStatus::Under),
+            (
+                json!({ "replicas": 2, "autoscaling": { "min": 2, "max": 6 } }),
+                2,
+                6,
+                ProvisionStatus::Ok,
+            ),
+            (json!({ "replicas": 5 }), 5, 5, ProvisionStatus::Ok),
+            (json!({ "replicas": 15 }), 15, 15, ProvisionStatus::Over),
+        ];
+        for (properties, provided, provided_max, status) in cases {
+            let diagram = web(properties.clone(), json!({}), false);
+            let plan = plan(&diagram, &test_catalog(), &peak(700)).unwrap();
+            let app = tier(&plan, "App Server");
+            assert_eq!(app.required, 5, "{}", properties);
+            assert_eq!(
+                (app.provided, app.provided_max, app.status),
+                (provided, provided_max, status),
+                "{}",
+                properties
+            );
+        }
+    }
+
+    #[test]
+    fn databases_count_read_replicas_and_benefit_from_a_cache() {
+        let catalog = test_catalog();
+        // 2800 rps / (1000 rps × 0.7) = 4台
+        let plain = plan(&web(json!({}), json!({}), false), &catalog, &peak(2800)).unwrap();
+        let db = tier(&plain, "RDBMS (SQL)");
+        assert_eq!(
+            (db.load_rps, db.required, db.status),
+            (2800, 4, ProvisionStatus::Under)
+        );
+
+        let replicated = plan(
+            &web(json!({}), json!({ "readReplicas": 3 }), false),
+            &catalog,
+            &peak(2800),
+        )
+        .unwrap();
+        let db = tier(&replicated, "RDBMS (SQL)");
+        assert_eq!((db.provided, db.status), (4, ProvisionStatus::Ok));
+
+        // キャッシュがあればデータベースに届くのは2割
+        let cached = plan(&web(json!({}), json!({}), true), &catalog, &peak(2800)).unwrap();
+        let db = tier(&cached, "RDBMS (SQL)");
+        assert_eq!(
+            (db.load_rps, db.required, db.status),
+            (560, 1, ProvisionStatus::Ok)
+        );
+    }
+
+    #[test]
+    fn only_tiers_on_the_request_path_are_planned() {
+        let diagram = diagram(
+            vec![
+                node("browser", "Web Browser", json!({})),
+                node("lb", "Load Balancer", json!({})),
+                node("web", "Web Server", json!({ "replicas": 2 })),
+                node("batch", "App Server", json!({})),
+            ],
+            &[("browser", "lb"), ("lb", "web")],
+        );
+        let plan = plan(&diagram, &test_catalog(), &peak(100)).unwrap();
+        let tiers: Vec<&str> = plan.tiers.iter().map(|t| t.type_label.as_str()).collect();
+        assert_eq!(tiers, vec!["Web Server"]);
+        assert!(!plan.estimated);
+    }
+
+    #[test]
+    fn plans_need_a_load_estimate() {
+        let diagram = web(json!({}), json!({}), false);
+        assert!(plan(&diagram, &test_catalog(), &Requirements::default()).is_none());
+
+        let by_dau = Requirements {
+            dau: Some(1_000_000),
+            ..Default::default()
+        };
+        assert!(plan(&diagram, &test_catalog(), &by_dau).unwrap().estimated);
+    }
+
+    #[test]
+    fn findings_cap_scalability_and_cost() {
+        let under = web(json!({ "replicas": 1 }), json!({}), false);
+        let over = web(json!({ "replicas": 20 }), json!({}), false);
+        let catalog = test_catalog();
+
+        let found = |diagram: &Diagram| -> Vec<(String, &'static str, u32)> {
+            findings(&plan(diagram, &catalog, &peak(700)).unwrap())
+                .into_iter()
+                .map(|f| (f.id, f.dimension, f.max_score))
+                .collect()
+        };
+        assert_eq!(
+            found(&under),
+            vec![("capacity:App Server".to_string(), "scalability", 50)]
+        );
+        assert_eq!(
+            found(&over),
+            vec![("capacity:App Server".to_string(), "costEfficiency", 70)]
+        );
+    }
+}
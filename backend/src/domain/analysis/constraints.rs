@@ -0,0 +1,577 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::exposure;
+use super::failure_domains::sync_dependents;
+use crate::domain::model::catalog::{ComponentCatalog, ComponentDef};
+use crate::domain::model::containment::ContainmentTree;
+use crate::domain::model::diagram::{Diagram, Node};
+use crate::domain::model::evaluation::{DetailedScores, EvaluationResult};
+use crate::domain::model::requirements::{Level, RequirementLevels};
+
+// 機械的に判定できる要件違反を「スコアの上限」としてLLMの評価に課す
+// system_prompt.txt の Check Requirements (MANDATORY) に対応する
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintFinding {
+    pub id: String,
+    // 上限を課す評価観点 (DetailedScores のキー)
+    pub dimension: &'static str,
+    pub max_score: u32,
+    pub message: String,
+    // 関係するノードのID
+    pub node_ids: Vec<String>,
+}
+
+// 低予算の構成で過剰とみなす連携基盤
+const HEAVY_INTEGRATION: [&str; 4] = ["Message Queue", "Pub/Sub", "Event Bus", "Search Engine"];
+
+pub fn check(
+    diagram: &Diagram,
+    catalog: &ComponentCatalog,
+    levels: &RequirementLevels,
+) -> Vec<ConstraintFinding> {
+    let mut findings = Vec::new();
+    let ids_where = |pred: &dyn Fn(&ComponentDef) -> bool| -> Vec<String> {
+        diagram
+            .nodes
+            .iter()
+            .filter(|n| catalog.get(&n.type_label).is_some_and(pred))
+            .map(|n| n.id.clone())
+            .collect()
+    };
+    let ids_of = |types: &[&str]| -> Vec<String> {
+        diagram
+            .nodes
+            .iter()
+            .filter(|n| types.contains(&n.type_label.as_str()))
+            .map(|n| n.id.clone())
+            .collect()
+    };
+
+    // 1. 大規模トラフィック: Load Balancer と CDN は必須
+    if levels.scale >= Level::High {
+        if ids_of(&["Load Balancer", "API Gateway"]).is_empty() {
+            findings.push(ConstraintFinding {
+                id: "missing_load_balancer".to_string(),
+                dimension: "scalability",
+                max_score: 40,
+                message: "大規模なトラフィックが想定されますが、Load Balancer がありません。"
+                    .to_string(),
+                node_ids: Vec::new(),
+            });
+        }
+        if ids_of(&["CDN (CloudFront)"]).is_empty() {
+            findings.push(ConstraintFinding {
+                id: "missing_cdn".to_string(),
+                dimension: "scalability",
+                max_score: 60,
+                message: "大規模なトラフィックが想定されますが、CDN がありません。".to_string(),
+                node_ids: Vec::new(),
+            });
+        }
+    }
+
+    // 2. 高可用性: 単一障害点 (SPOF) と Multi-AZ
+    if levels.availability >= Level::High {
+        let mut by_type: BTreeMap<&str, Vec<&Node>> = BTreeMap::new();
+        for node in &diagram.nodes {
+            let needs_redundancy = catalog
+                .get(&node.type_label)
+                .is_some_and(|c| c.needs_redundancy());
+            if needs_redundancy {
+                by_type
+                    .entry(node.type_label.as_str())
+                    .or_default()
+                    .push(node);
+            }
+        }
+        // 同じ種類のノードが1つだけで、そのノード自体も冗長化されていないもの
+        // どことも接続されていないノードは、止まっても影響を受ける経路がないため除く
+        let spofs = by_type
+            .iter()
+            .filter(|(_, nodes)| nodes.len() == 1 && !nodes[0].is_redundant())
+            .filter(|(_, nodes)| is_connected(diagram, &nodes[0].id))
+            .map(|(type_label, nodes)| (type_label, vec![nodes[0].id.clone()]));
+        for (type_label, ids) in spofs {
+            // 同期的に依存するノードがなければ、非同期接続で障害が切り離されている
+            let dependents = sync_dependents(diagram, &ids[0]);
+            let (max_score, message) = if dependents.is_empty() && has_incoming(diagram, &ids[0]) {
+                (
+                    70,
+                    format!(
+                        "{} が1台のみですが、非同期接続により呼び出し元からは切り離されています。",
+                        type_label
+                    ),
+                )
+            } else {
+                (
+                    if levels.availability == Level::Critical {
+                        40
+                    } else {
+                        60
+                    },
+                    format!(
+                        "{} が1台のみで、単一障害点になっています (同期的に依存するノード: {}個)。",
+                        type_label,
+                        dependents.len()
+                    ),
+                )
+            };
+            findings.push(ConstraintFinding {
+                id: format!("spof:{}", ids[0]),
+                dimension: "availability",
+                max_score,
+                message,
+                node_ids: ids.iter().cloned().chain(dependents).collect(),
+            });
+        }
+
+        // 水平スケールするマネージドサービス (DynamoDB, S3等) は複数AZが前提のため除く
+        // 配置AZは座標ではなく、グループの包含関係 (parentNode) から判断する
+        let tree = ContainmentTree::new(diagram);
+        let databases: Vec<String> = diagram
+            .nodes
+            .iter()
+            .filter(|n| {
+                catalog
+                    .get(&n.type_label)
+                    .is_some_and(|c| c.category == "database" && c.needs_redundancy())
+            })
+            .filter(|n| n.properties.multi_az != Some(true))
+            .filter(|n| tree.zones_for_type(&n.type_label).len() < 2)
+            .map(|n| n.id.clone())
+            .collect();
+        if levels.availability == Level::Critical && !databases.is_empty() {
+            findings.push(ConstraintFinding {
+                id: "single_az_database".to_string(),
+                dimension: "availability",
+                max_score: 40,
+                message: "Critical な可用性が求められますが、データベースが複数のAvailability Zoneに配置されていません。".to_string(),
+                node_ids: databases,
+            });
+        }
+    }
+
+    // 3. 低予算: 過剰な構成
+    if levels.budget == Level::Low {
+        let compute = ids_where(&|c| c.category == "compute");
+        let integration = ids_of(&HEAVY_INTEGRATION);
+        if compute.len() > 3 || integration.len() > 1 {
+            findings.push(ConstraintFinding {
+                id: "over_engineering".to_string(),
+                dimension: "costEfficiency",
+                max_score: 50,
+                message: "予算が限られていますが、コンピュートや連携基盤が過剰です。".to_string(),
+                node_ids: compute.into_iter().chain(integration).collect(),
+            });
+        }
+    }
+
+    // 4. セキュリティ: インターネットからの露出 (要件によらず常に確認する)
+    for exposure in exposure::analyze(diagram, catalog).findings {
+        let max_score = match exposure.severity {
+            Level::Critical => 30,
+            Level::High => 50,
+            Level::Medium | Level::Low => continue,
+        };
+        findings.push(ConstraintFinding {
+            id: format!("exposure:{}:{}", exposure.code, exposure.node_ids.join(",")),
+            dimension: "security",
+            max_score,
+            message: exposure.message,
+            node_ids: exposure.node_ids,
+        });
+    }
+
+    findings
+}
+
+fn has_incoming(diagram: &Diagram, node_id: &str) -> bool {
+    diagram.edges.iter().any(|e| e.target == node_id)
+}
+
+fn is_connected(diagram: &Diagram, node_id: &str) -> bool {
+    diagram
+        .edges
+        .iter()
+        .any(|e| e.source == node_id || e.target == node_id)
+}
+
+// 各観点のスコアがどの制約によって抑えられたか
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundedScore {
+    pub original: u32,
+    pub bounded: u32,
+    pub constraints: Vec<String>,
+}
+
+// LLMのスコアを制約の上限で切り詰め、切り詰めた観点の一覧を返す
+pub fn apply(
+    result: &mut EvaluationResult,
+    findings: &[ConstraintFinding],
+) -> BTreeMap<&'static str, BoundedScore> {
+    let mut bounded = BTreeMap::new();
+    for dimension in DetailedScores::DIMENSIONS {
+        let original = result.details.get(dimension);
+        let limiting: Vec<&ConstraintFinding> = findings
+            .iter()
+            .filter(|f| f.dimension == dimension && f.max_score < original)
+            .collect();
+        if let Some(cap) = limiting.iter().map(|f| f.max_score).min() {
+            bounded.insert(
+                dimension,
+                BoundedScore {
+                    original,
+                    bounded: cap,
+                    constraints: limiting.iter().map(|f| f.id.clone()).collect(),
+                },
+            );
+        }
+    }
+
+    if bounded.is_empty() {
+        return bounded;
+    }
+
+    // 合計点は、切り詰めた分の平均だけ差し引く
+    let reduction: u32 = bounded.values().map(|b| b.original - b.bounded).sum();
+    result.details = DetailedScores::from_fn(|name| match bounded.get(name) {
+        Some(b) => b.bounded,
+        None => result.details.get(name),
+    });
+    result.total_score = result
+        .total_score
+        .saturating_sub(reduction.div_ceil(DetailedScores::DIMENSIONS.len() as u32));
+    bounded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    // (説明, 設計, 要件, 期待する (ID, 上限))
+    type CheckCase = (
+        &'static str,
+        Diagram,
+        RequirementLevels,
+        Vec<(&'static str, u32)>,
+    );
+    // (合計, availability, security)
+    type Scores = (u32, u32, u32);
+    // (制約, 元のスコア, 適用後のスコア, 切り詰めた観点)
+    type ApplyCase = (Vec<ConstraintFinding>, Scores, Scores, Vec<&'static str>);
+
+    fn node(id: &str, node_type: &str) -> Value {
+        json!({ "id": id, "type": node_type, "position": { "x": 0, "y": 0 } })
+    }
+
+    fn with_properties(mut node: Value, properties: Value) -> Value {
+        node["properties"] = properties;
+        node
+    }
+
+    fn replicated(id: &str, node_type: &str) -> Value {
+        with_properties(node(id, node_type), json!({ "replicas": 2 }))
+    }
+
+    fn edge(source: &str, target: &str) -> Value {
+        json!({ "source": source, "target": target })
+    }
+
+    fn diagram(nodes: Vec<Value>, edges: Vec<Value>) -> Diagram {
+        serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+    }
+
+    fn levels(scale: Level, availability: Level, budget: Level) -> RequirementLevels {
+        RequirementLevels {
+            scale,
+            availability,
+            budget,
+        }
+    }
+
+    #[test]
+    fn check_reports_each_violation() {
+        use Level::*;
+        // Web Browser → Load Balancer → App Server (1台)
+        let web = || {
+            diagram(
+                vec![
+                    node("browser", "Web Browser"),
+                    node("lb", "Load Balancer"),
+                    node("app", "App Server"),
+                ],
+                vec![edge("browser", "lb"), edge("lb", "app")],
+            )
+        };
+        let cases: Vec<CheckCase> = vec![
+            (
+                "requirements are met",
+                web(),
+                levels(Medium, Medium, Medium),
+                vec![],
+            ),
+            (
+                "large scale without entry points",
+                diagram(vec![replicated("app", "App Server")], vec![]),
+                levels(High, Medium, Medium),
+                vec![("missing_load_balancer", 40), ("missing_cdn", 60)],
+            ),
+            (
+                "single app server",
+                web(),
+                levels(Medium, High, Medium),
+                vec![("spof:app", 60)],
+            ),
+            (
+                "single app server under critical availability",
+                web(),
+                levels(Medium, Critical, Medium),
+                vec![("spof:app", 40)],
+            ),
+            (
+                "single worker behind an async connection",
+                diagram(
+                    vec![
+                        replicated("app", "App Server"),
+                        node("worker", "Worker (Async)"),
+                    ],
+                    vec![json!({ "source": "app", "target": "worker", "style": "async" })],
+                ),
+                levels(Medium, High, Medium),
+                vec![("spof:worker", 70)],
+            ),
+            (
+                "monitoring does not need redundancy",
+                diagram(
+                    vec![
+                        node("alerts", "Alert Manager"),
+                        node("health", "Health Checker"),
+                        node("lb", "Load Balancer"),
+                        replicated("app", "App Server"),
+                    ],
+                    vec![
+                        edge("lb", "app"),
+                        edge("health", "app"),
+                        edge("health", "alerts"),
+                    ],
+                ),
+                levels(Medium, High, Medium),
+                vec![],
+            ),
+            (
+                "lone alert manager",
+                diagram(vec![node("alerts", "Alert Manager")], vec![]),
+                levels(Medium, High, Medium),
+                vec![],
+            ),
+            (
+                "unconnected cache",
+                diagram(vec![node("cache", "Distributed Cache")], vec![]),
+                levels(Medium, Critical, Medium),
+                vec![],
+            ),
+            (
+                "cache called synchronously",
+                diagram(
+                    vec![
+                        replicated("app", "App Server"),
+                        node("cache", "Distributed Cache"),
+                    ],
+                    vec![edge("app", "cache")],
+                ),
+                levels(Medium, High, Medium),
+                vec![("spof:cache", 60)],
+            ),
+            (
+                "replicated app server",
+                diagram(
+                    vec![node("lb", "Load Balancer"), replicated("app", "App Server")],
+                    vec![edge("lb", "app")],
+                ),
+                levels(Medium, Critical, Medium),
+                vec![],
+            ),
+            (
+                "single-AZ database",
+                diagram(
+                    vec![replicated("app", "App Server"), node("db", "RDBMS (SQL)")],
+                    vec![edge("app", "db")],
+                ),
+                levels(Medium, Critical, Medium),
+                vec![("spof:db", 40), ("single_az_database", 40)],
+            ),
+            (
+                "Multi-AZ database",
+                diagram(
+                    vec![
+                        replicated("app", "App Server"),
+                        with_properties(node("db", "RDBMS (SQL)"), json!({ "multiAz": true })),
+                    ],
+                    vec![edge("app", "db")],
+                ),
+                levels(Medium, Critical, Medium),
+                vec![],
+            ),
+            (
+                "too much compute for a low budget",
+                diagram(
+                    (0..4)
+                        .map(|i| node(&format!("app{}", i), "App Server"))
+                        .collect(),
+                    vec![],
+                ),
+                levels(Low, Low, Low),
+                vec![("over_engineering", 50)],
+            ),
+            (
+                "too many brokers for a low budget",
+                diagram(
+                    vec![node("mq", "Message Queue"), node("bus", "Event Bus")],
+                    vec![],
+                ),
+                levels(Low, Low, Low),
+                vec![("over_engineering", 50)],
+            ),
+            (
+                "database reached directly from the browser",
+                diagram(
+                    vec![node("browser", "Web Browser"), node("db", "RDBMS (SQL)")],
+                    vec![edge("browser", "db")],
+                ),
+                levels(Low, Low, Medium),
+                vec![("exposure:data_store_direct_access:db", 30)],
+            ),
+        ];
+
+        let catalog = test_catalog();
+        for (name, diagram, levels, expected) in cases {
+            let findings = check(&diagram, &catalog, &levels);
+            let found: Vec<(&str, u32)> = findings
+                .iter()
+                .map(|f| (f.id.as_str(), f.max_score))
+                .collect();
+            assert_eq!(found, expected, "{}", name);
+        }
+    }
+
+    fn finding(id: &str, dimension: &'static str, max_score: u32) -> ConstraintFinding {
+        ConstraintFinding {
+            id: id.to_string(),
+            dimension,
+            max_score,
+            message: String::new(),
+            node_ids: Vec::new(),
+        }
+    }
+
+    fn evaluation(total_score: u32, availability: u32, security: u32) -> EvaluationResult {
+        EvaluationResult {
+            total_score,
+            details: DetailedScores {
+                availability,
+                scalability: 70,
+                security,
+                maintainability: 70,
+                cost_efficiency: 70,
+                feasibility: 70,
+            },
+            feedback: String::new(),
+            improvement: String::new(),
+        }
+    }
+
+    #[test]
+    fn apply_clamps_scores_and_the_total() {
+        let cases: Vec<ApplyCase> = vec![
+            (vec![], (75, 80, 80), (75, 80, 80), vec![]),
+            // 上限以下のスコアはそのまま
+            (
+                vec![finding("spof:app", "availability", 60)],
+                (75, 60, 80),
+                (75, 60, 80),
+                vec![],
+            ),
+            // 差40 → 合計から ceil(40/6) = 7 を引く
+            (
+                vec![finding("spof:app", "availability", 40)],
+                (75, 80, 80),
+                (68, 40, 80),
+                vec!["availability"],
+            ),
+            // 同じ観点の制約は最も低い上限を使う
+            (
+                vec![
+                    finding("spof:app", "availability", 60),
+                    finding("single_az_database", "availability", 40),
+                ],
+                (75, 80, 80),
+                (68, 40, 80),
+                vec!["availability"],
+            ),
+            // 差1でも切り上げて1点引く
+            (
+                vec![finding("exposure:x", "security", 79)],
+                (75, 80, 80),
+                (74, 80, 79),
+                vec!["security"],
+            ),
+            // 複数の観点は差の合計で計算する: (80-40) + (80-30) = 90 → 15
+            (
+                vec![
+                    finding("spof:app", "availability", 40),
+                    finding("exposure:x", "security", 30),
+                ],
+                (75, 80, 80),
+                (60, 40, 30),
+                vec!["availability", "security"],
+            ),
+            // 合計点は0未満にならない
+            (
+                vec![finding("exposure:x", "security", 30)],
+                (5, 80, 80),
+                (0, 80, 30),
+                vec!["security"],
+            ),
+        ];
+
+        for (findings, (total, availability, security), expected, dimensions) in cases {
+            let mut result = evaluation(total, availability, security);
+            let bounded = apply(&mut result, &findings);
+            assert_eq!(
+                (
+                    result.total_score,
+                    result.details.availability,
+                    result.details.security
+                ),
+                expected,
+                "{:?}",
+                findings.iter().map(|f| &f.id).collect::<Vec<_>>()
+            );
+            assert_eq!(bounded.keys().copied().collect::<Vec<_>>(), dimensions);
+            assert_eq!(result.details.scalability, 70);
+        }
+    }
+
+    #[test]
+    fn apply_lists_every_limiting_constraint() {
+        let mut result = evaluation(75, 80, 80);
+        let findings = [
+            finding("spof:app", "availability", 60),
+            finding("single_az_database", "availability", 40),
+            finding("spof:cache", "availability", 90),
+        ];
+        let bounded = apply(&mut result, &findings);
+        let availability = &bounded["availability"];
+        assert_eq!((availability.original, availability.bounded), (80, 40));
+        assert_eq!(
+            availability.constraints,
+            vec!["spof:app", "single_az_database"]
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{
+    CommunicationStyle, Diagram, Edge, MESSAGE_BROKERS, Node, Protocol,
+};
+
+// コンポーネント定義の connectsTo に基づく接続の検証
+// LLMに評価を依頼する前に、明らかに不自然な接続を検出する
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeWarning {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub source: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+// 間に挟むコンポーネントを探す際の最大数
+const MAX_SUGGESTED_HOPS: usize = 2;
+
+pub fn validate_edges(diagram: &Diagram, catalog: &ComponentCatalog) -> Vec<EdgeWarning> {
+    let mut warnings = Vec::new();
+    let mut seen = HashSet::new();
+    let present: Vec<&str> = diagram
+        .nodes
+        .iter()
+        .map(|n| n.type_label.as_str())
+        .collect();
+
+    for edge in &diagram.edges {
+        let warn = |code, severity, message: String, suggestion: Option<String>| EdgeWarning {
+            code,
+            severity,
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            message,
+            suggestion,
+        };
+
+        let (Some(source), Some(target)) = (diagram.node(&edge.source), diagram.node(&edge.target))
+        else {
+            warnings.push(warn(
+                "dangling_edge",
+                Severity::Error,
+                "接続先または接続元のノードが存在しません。".to_string(),
+                Some("接続線を削除してください。".to_string()),
+            ));
+            continue;
+        };
+
+        if source.id == target.id {
+            warnings.push(warn(
+                "self_loop",
+                Severity::Warning,
+                format!("{} が自分自身に接続されています。", source.display_name()),
+                Some("接続線を削除してください。".to_string()),
+            ));
+            continue;
+        }
+
+        if !seen.insert((edge.source.as_str(), edge.target.as_str())) {
+            warnings.push(warn(
+                "duplicate_edge",
+                Severity::Warning,
+                format!(
+                    "{} から {} への接続が重複しています。",
+                    source.display_name(),
+                    target.display_name()
+                ),
+                None,
+            ));
+            continue;
+        }
+
+        let (Some(source_def), Some(target_def)) = (
+            catalog.get(&source.type_label),
+            catalog.get(&target.type_label),
+        ) else {
+            // 未知の型は接続ルールを判定できないため、不明ノードとして別途報告する
+            continue;
+        };
+
+        if source_def.semantics.group || target_def.semantics.group {
+            warnings.push(warn(
+                "group_edge",
+                Severity::Warning,
+                "VPCやSubnetなどのグループは接続ではなく、ノードを内包させて表現します。"
+                    .to_string(),
+                Some("グループ内の具体的なコンポーネント同士を接続してください。".to_string()),
+            ));
+            continue;
+        }
+
+        if catalog.can_connect(&source.type_label, &target.type_label) {
+            if let Some(warning) = check_attributes(edge, source, target) {
+                warnings.push(warning);
+            }
+            continue;
+        }
+
+        if catalog.can_connect(&target.type_label, &source.type_label) {
+            warnings.push(warn(
+                "reversed_edge",
+                Severity::Warning,
+                format!(
+                    "{} → {} の向きが逆になっています。",
+                    source.display_name(),
+                    target.display_name()
+                ),
+                Some(format!(
+                    "{} から {} へ接続してください。",
+                    target.display_name(),
+                    source.display_name()
+                )),
+            ));
+            continue;
+        }
+
+        let suggestion = catalog
+            .suggest_path(
+                &source.type_label,
+                &target.type_label,
+                MAX_SUGGESTED_HOPS,
+                &present,
+            )
+            .map(|via| {
+                let mut route = vec![source.type_label.clone()];
+                route.extend(via);
+                route.push(target.type_label.clone());
+                format!("{} のように経由させてください。", route.join(" → "))
+            });
+        warnings.push(warn(
+            "incompatible_edge",
+            Severity::Error,
+            format!(
+                "{} から {} へ直接接続することは想定されていません。",
+                source.display_name(),
+                target.display_name()
+            ),
+            suggestion,
+        ));
+    }
+
+    warnings
+}
+
+// プロトコル・通信方式が接続の両端と整合しているか
+fn check_attributes(edge: &Edge, source: &Node, target: &Node) -> Option<EdgeWarning> {
+    let is_broker = |n: &Node| MESSAGE_BROKERS.contains(&n.type_label.as_str());
+    let mismatch = |message: String, suggestion: String| EdgeWarning {
+        code: "protocol_mismatch",
+        severity: Severity::Warning,
+        source: edge.source.clone(),
+        target: edge.target.clone(),
+        message,
+        suggestion: Some(suggestion),
+    };
+
+    match edge.protocol {
+        Some(Protocol::Sql) if target.type_label != "RDBMS (SQL)" => {
+            return Some(mismatch(
+                format!("SQL で {} に接続しています。", target.display_name()),
+                "SQL はリレーショナルデータベースへの接続にのみ使用してください。".to_string(),
+            ));
+        }
+        Some(Protocol::Dns) if target.type_label != "DNS (Route53)" => {
+            return Some(mismatch(
+                format!("DNS で {} に接続しています。", target.display_name()),
+                "DNS は DNS (Route53) への問い合わせにのみ使用してください。".to_string(),
+            ));
+        }
+        Some(Protocol::Amqp) if !is_broker(source) && !is_broker(target) => {
+            return Some(mismatch(
+                "AMQP の接続がメッセージブローカーを経由していません。".to_string(),
+                "Message Queue や Pub/Sub を間に配置してください。".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    // ブローカーを介さない非同期通信は、実際には障害が伝播しうる
+    let event_source = ["Object Storage", "NoSQL (KV)"].contains(&source.type_label.as_str());
+    if edge.style == Some(CommunicationStyle::Async)
+        && !is_broker(source)
+        && !is_broker(target)
+        && !event_source
+    {
+        return Some(EdgeWarning {
+            code: "async_without_broker",
+            severity: Severity::Warning,
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            message: format!(
+                "{} → {} は非同期とされていますが、メッセージブローカーを経由していません。",
+                source.display_name(),
+                target.display_name()
+            ),
+            suggestion: Some(format!(
+                "{} → Message Queue → {} のように経由させると、障害の影響を切り離せます。",
+                source.type_label, target.type_label
+            )),
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    fn diagram(edges: Vec<Value>) -> Diagram {
+        let nodes: Vec<Value> = [
+            ("browser", "Web Browser"),
+            ("lb", "Load Balancer"),
+            ("app", "App Server"),
+            ("worker", "Worker (Async)"),
+            ("db", "RDBMS (SQL)"),
+            ("kv", "NoSQL (KV)"),
+            ("mq", "Message Queue"),
+            ("vpc", "VPC (Network)"),
+            ("mainframe", "Mainframe"),
+        ]
+        .iter()
+        .map(|(id, node_type)| json!({ "id": id, "type": node_type, "position": { "x": 0, "y": 0 } }))
+        .collect();
+        serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap()
+    }
+
+    fn codes(edges: Vec<Value>) -> Vec<&'static str> {
+        validate_edges(&diagram(edges), &test_catalog())
+            .iter()
+            .map(|w| w.code)
+            .collect()
+    }
+
+    #[test]
+    fn each_edge_is_checked_against_the_catalog() {
+        let cases: [(Value, Option<&str>); 15] = [
+            (
+                json!({ "source": "browser", "target": "lb", "protocol": "HTTPS" }),
+                None,
+            ),
+            (
+                json!({ "source": "app", "target": "db", "protocol": "SQL" }),
+                None,
+            ),
+            (
+                json!({ "source": "app", "target": "mq", "protocol": "AMQP" }),
+                None,
+            ),
+            (
+                json!({ "source": "mq", "target": "worker", "style": "async" }),
+                None,
+            ),
+            // イベントの発生元からの非同期通知はブローカーがなくてもよい
+            (
+                json!({ "source": "kv", "target": "app", "style": "async" }),
+                None,
+            ),
+            // 定義のない型は接続ルールを判定しない
+            (json!({ "source": "mainframe", "target": "app" }), None),
+            (
+                json!({ "source": "app", "target": "ghost" }),
+                Some("dangling_edge"),
+            ),
+            (
+                json!({ "source": "app", "target": "app" }),
+                Some("self_loop"),
+            ),
+            (
+                json!({ "source": "vpc", "target": "app" }),
+                Some("group_edge"),
+            ),
+            (
+                json!({ "source": "db", "target": "app" }),
+                Some("reversed_edge"),
+            ),
+            (
+                json!({ "source": "browser", "target": "db" }),
+                Some("incompatible_edge"),
+            ),
+            (
+                json!({ "source": "app", "target": "kv", "protocol": "SQL" }),
+                Some("protocol_mismatch"),
+            ),
+            (
+                json!({ "source": "app", "target": "db", "protocol": "DNS" }),
+                Some("protocol_mismatch"),
+            ),
+            (
+                json!({ "source": "app", "target": "worker", "protocol": "AMQP" }),
+                Some("protocol_mismatch"),
+            ),
+            (
+                json!({ "source": "app", "target": "worker", "style": "async" }),
+                Some("async_without_broker"),
+            ),
+        ];
+        for (edge, expected) in cases {
+            let found = codes(vec![edge.clone()]);
+            assert_eq!(found, expected.into_iter().collect::<Vec<_>>(), "{}", edge);
+        }
+    }
+
+    #[test]
+    fn duplicates_are_reported_once_per_extra_edge() {
+        let edge = json!({ "source": "browser", "target": "lb" });
+        assert_eq!(
+            codes(vec![edge.clone(), edge.clone(), edge]),
+            vec!["duplicate_edge", "duplicate_edge"]
+        );
+    }
+
+    #[test]
+    fn incompatible_edges_suggest_a_route_through_present_components() {
+        let warnings = validate_edges(
+            &diagram(vec![json!({ "source": "browser", "target": "db" })]),
+            &test_catalog(),
+        );
+        assert!(matches!(warnings[0].severity, Severity::Error));
+        assert_eq!(
+            warnings[0].suggestion.as_deref(),
+            Some(
+                "Web Browser → Load Balancer → App Server → RDBMS (SQL) のように経由させてください。"
+            )
+        );
+    }
+
+    #[test]
+    fn reversed_edges_suggest_the_opposite_direction() {
+        let warnings = validate_edges(
+            &diagram(vec![json!({ "source": "db", "target": "app" })]),
+            &test_catalog(),
+        );
+        assert_eq!(
+            warnings[0].suggestion.as_deref(),
+            Some("App Server から RDBMS (SQL) へ接続してください。")
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use crate::domain::model::catalog::{ComponentCatalog, Hosting};
+use crate::domain::model::containment::{ContainmentTree, GroupKind};
+use crate::domain::model::diagram::Diagram;
+use crate::domain::model::requirements::Level;
+
+// インターネットからの到達性とセキュリティ上の露出の分析
+// グループの包含関係と接続から、security 観点の根拠となる事実を求める
+
+// 通過するリクエストを検査・制限するコンポーネント
+pub const TRAFFIC_FILTERS: [&str; 2] = ["WAF (Firewall)", "API Gateway"];
+// 公開を前提とした使い方が一般的なデータストア (署名付きURL等)
+const PUBLIC_BY_DESIGN: [&str; 1] = ["Object Storage"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExposureFinding {
+    pub code: &'static str,
+    pub severity: Level,
+    pub node_ids: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExposureReport {
+    // インターネットから到達できるノード
+    pub internet_reachable: Vec<String>,
+    // WAF / API Gateway を経由せずに到達できるノード
+    pub unfiltered: Vec<String>,
+    // インターネットから直接到達できるノードを含むサブネット
+    pub public_subnets: Vec<String>,
+    pub findings: Vec<ExposureFinding>,
+}
+
+pub fn analyze(diagram: &Diagram, catalog: &ComponentCatalog) -> ExposureReport {
+    let def_of = |id: &str| diagram.node(id).and_then(|n| catalog.get(&n.type_label));
+    let is_client = |id: &str| def_of(id).is_some_and(|c| c.semantics.hosting == Hosting::Client);
+
+    let tree = ContainmentTree::new(diagram);
+
+    // 1. インターネット側の起点: 利用者の端末と、呼び出し元のない入口コンポーネント
+    let clients: Vec<&str> = diagram
+        .nodes
+        .iter()
+        .map(|n| n.id.as_str())
+        .filter(|id| is_client(id))
+        .collect();
+    let front_doors: Vec<&str> = diagram
+        .nodes
+        .iter()
+        .filter(|n| def_of(&n.id).is_some_and(|c| c.semantics.entry_point))
+        .filter(|n| !diagram.edges.iter().any(|e| e.target == n.id))
+        // VPC 内の入口は、パブリックとされたサブネットにあるものだけを公開されているとみなす
+        .filter(|n| {
+            tree.enclosing(&n.id, GroupKind::Vpc).is_none()
+                || tree
+                    .enclosing(&n.id, GroupKind::Subnet)
+                    .is_some_and(|s| s.label.as_deref().is_some_and(is_labeled_public))
+        })
+        .map(|n| n.id.as_str())
+        .collect();
+    // 利用者の端末から直接接続される (公開されている) ノード
+    let mut first_hop: BTreeSet<&str> = diagram
+        .edges
+        .iter()
+        .filter(|e| clients.contains(&e.source.as_str()))
+        .map(|e| e.target.as_str())
+        .filter(|id| diagram.node(id).is_some() && !is_client(id))
+        .collect();
+    first_hop.extend(front_doors.iter().copied());
+
+    let roots: Vec<&str> = first_hop.iter().copied().collect();
+    let reachable = traverse(diagram, &roots, &|_| true);
+    let unfiltered = traverse(diagram, &roots, &|id| {
+        diagram
+            .node(id)
+            .is_none_or(|n| !TRAFFIC_FILTERS.contains(&n.type_label.as_str()))
+    });
+    // アプリケーション層も経由せずに到達できるノード
+    // (アプリケーション層の先にあるデータストアは、通常の構成として扱う)
+    let bypassing_app = traverse(diagram, &roots, &|id| {
+        diagram.node(id).is_none_or(|n| {
+            !TRAFFIC_FILTERS.contains(&n.type_label.as_str())
+                && catalog
+                    .get(&n.type_label)
+                    .is_none_or(|c| c.category != "compute")
+        })
+    });
+
+    let mut findings = Vec::new();
+
+    // 2. WAF / API Gateway やアプリケーション層を経由せずに到達できるデータストア
+    for node in &diagram.nodes {
+        if !unfiltered.contains(node.id.as_str()) {
+            continue;
+        }
+        let Some(def) = catalog.get(&node.type_label) else {
+            continue;
+        };
+        if def.semantics.stateful {
+            if !bypassing_app.contains(node.id.as_str()) {
+                continue;
+            }
+            let direct = first_hop.contains(node.id.as_str());
+            let (code, severity) = if PUBLIC_BY_DESIGN.contains(&node.type_label.as_str()) {
+                ("public_object_storage", Level::Medium)
+            } else if direct {
+                ("data_store_direct_access", Level::Critical)
+            } else {
+                ("data_store_exposed", Level::Medium)
+            };
+            let message = match code {
+                "public_object_storage" => format!(
+                    "{} にインターネットから到達できます。公開範囲 (バケットポリシーや署名付きURL) を限定してください。",
+                    node.display_name()
+                ),
+                "data_store_direct_access" => format!(
+                    "{} がインターネットから直接アクセスされています。アプリケーション層を経由させてください。",
+                    node.display_name()
+                ),
+                _ => format!(
+                    "{} に WAF / API Gateway もアプリケーション層も経由しない経路でインターネットから到達できます。",
+                    node.display_name()
+                ),
+            };
+            findings.push(ExposureFinding {
+                code,
+                severity,
+                node_ids: vec![node.id.clone()],
+                message,
+            });
+        } else if def.category == "compute" {
+            findings.push(ExposureFinding {
+                code: "compute_unfiltered",
+                severity: Level::Low,
+                node_ids: vec![node.id.clone()],
+                message: format!(
+                    "{} へのリクエストが WAF / API Gateway で検査されていません。",
+                    node.display_name()
+                ),
+            });
+        }
+    }
+
+    // 3. 実質的にパブリックなサブネット (包含関係から判断する)
+    let mut public_subnets = BTreeSet::new();
+    for id in &first_hop {
+        if let Some(subnet) = tree.enclosing(id, GroupKind::Subnet) {
+            public_subnets.insert(subnet.id.as_str());
+        }
+    }
+    for subnet_id in &public_subnets {
+        let Some(subnet) = diagram.node(subnet_id) else {
+            continue;
+        };
+        let stores: Vec<String> = diagram
+            .nodes
+            .iter()
+            .filter(|n| tree.ancestors(&n.id).iter().any(|a| a.id == *subnet_id))
+            .filter(|n| def_of(&n.id).is_some_and(|c| c.semantics.stateful))
+            .filter(|n| !PUBLIC_BY_DESIGN.contains(&n.type_label.as_str()))
+            .map(|n| n.id.clone())
+            .collect();
+        if !stores.is_empty() {
+            findings.push(ExposureFinding {
+                code: "data_store_in_public_subnet",
+                severity: Level::High,
+                node_ids: stores,
+                message: format!(
+                    "{} はインターネットから直接到達できるパブリックサブネットですが、データストアが配置されています。",
+                    subnet.display_name()
+                ),
+            });
+        }
+        let label = subnet.label.as_deref().unwrap_or("").to_lowercase();
+        if label.contains("private") || label.contains("プライベート") {
+            findings.push(ExposureFinding {
+                code: "private_subnet_exposed",
+                severity: Level::Medium,
+                node_ids: vec![subnet.id.clone()],
+                message: format!(
+                    "{} はプライベートとされていますが、インターネットから直接到達できるノードを含んでいます。",
+                    subnet.display_name()
+                ),
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    ExposureReport {
+        internet_reachable: sorted(&reachable),
+        unfiltered: sorted(&unfiltered),
+        public_subnets: public_subnets.iter().map(|s| s.to_string()).collect(),
+        findings,
+    }
+}
+
+fn is_labeled_public(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("public") || name.contains("パブリック")
+}
+
+// 起点から接続をたどって到達できるノード (pass_through が false のノードの先へは進まない)
+fn traverse<'a>(
+    diagram: &'a Diagram,
+    roots: &[&'a str],
+    pass_through: &dyn Fn(&str) -> bool,
+) -> HashSet<&'a str> {
+    let mut visited: HashSet<&str> = roots.iter().copied().collect();
+    let mut queue: VecDeque<&str> = roots.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        if !pass_through(current) {
+            continue;
+        }
+        for edge in &diagram.edges {
+            let next = if edge.source == current {
+                edge.target.as_str()
+            } else if edge.bidirectional && edge.target == current {
+                edge.source.as_str()
+            } else {
+                continue;
+            };
+            if diagram.node(next).is_some() && visited.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    visited
+}
+
+fn sorted(ids: &HashSet<&str>) -> Vec<String> {
+    let mut ids: Vec<String> = ids.iter().map(|s| s.to_string()).collect();
+    ids.sort();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    // (ID, 型, 親, ラベル)
+    fn node(id: &str, node_type: &str, parent: Option<&str>, label: Option<&str>) -> Value {
+        json!({
+            "id": id, "type": node_type, "parentNode": parent, "label": label,
+            "position": { "x": 0, "y": 0 }
+        })
+    }
+
+    fn report(nodes: Vec<Value>, edges: &[(&str, &str)]) -> ExposureReport {
+        let edges: Vec<Value> = edges
+            .iter()
+            .map(|(source, target)| json!({ "source": source, "target": target }))
+            .collect();
+        let diagram: Diagram =
+            serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap();
+        analyze(&diagram, &test_catalog())
+    }
+
+    fn codes(report: &ExposureReport) -> Vec<(&'static str, String)> {
+        report
+            .findings
+            .iter()
+            .map(|f| (f.code, f.node_ids.join(",")))
+            .collect()
+    }
+
+    #[test]
+    fn data_stores_behind_the_application_tier_are_not_findings() {
+        let report = report(
+            vec![
+                node("browser", "Web Browser", None, None),
+                node("lb", "Load Balancer", None, None),
+                node("app", "App Server", None, None),
+                node("db", "RDBMS (SQL)", None, None),
+                node("cache", "Distributed Cache", None, None),
+            ],
+            &[
+                ("browser", "lb"),
+                ("lb", "app"),
+                ("app", "db"),
+                ("app", "cache"),
+            ],
+        );
+        // WAF がないことの指摘 (Low) のみで、データストアについての指摘はない
+        assert_eq!(
+            codes(&report),
+            vec![("compute_unfiltered", "app".to_string())]
+        );
+        assert_eq!(report.internet_reachable, vec!["app", "cache", "db", "lb"]);
+    }
+
+    #[test]
+    fn data_stores_reachable_without_an_application_tier_are_findings() {
+        // (データストアの型, Load Balancer を経由するか, コード, 重要度)
+        let cases = [
+            (
+                "RDBMS (SQL)",
+                false,
+                "data_store_direct_access",
+                Level::Critical,
+            ),
+            ("NoSQL (Doc)", true, "data_store_exposed", Level::Medium),
+            (
+                "Object Storage",
+                false,
+                "public_object_storage",
+                Level::Medium,
+            ),
+        ];
+        for (store, via_lb, code, severity) in cases {
+            let mut nodes = vec![
+                node("browser", "Web Browser", None, None),
+                node("db", store, None, None),
+            ];
+            let edges: &[(&str, &str)] = if via_lb {
+                nodes.push(node("lb", "Load Balancer", None, None));
+                &[("browser", "lb"), ("lb", "db")]
+            } else {
+                &[("browser", "db")]
+            };
+            let report = report(nodes, edges);
+            assert_eq!(report.findings.len(), 1, "{:?}", codes(&report));
+            assert_eq!(report.findings[0].code, code);
+            assert_eq!(report.findings[0].severity, severity);
+            assert_eq!(report.findings[0].node_ids, vec!["db"]);
+        }
+    }
+
+    #[test]
+    fn entry_points_inside_a_vpc_need_a_public_subnet() {
+        let network = |subnet_label: &str| {
+            vec![
+                node("vpc", "VPC (Network)", None, None),
+                node("az", "Availability Zone", Some("vpc"), None),
+                node("sn", "Subnet", Some("az"), Some(subnet_label)),
+                node("lb", "Load Balancer", Some("sn"), None),
+                node("app", "App Server", Some("sn"), None),
+            ]
+        };
+
+        // 内部向けのロードバランサーはインターネットの入口ではない
+        let internal = report(network("private-1a"), &[("lb", "app")]);
+        assert!(internal.internet_reachable.is_empty());
+        assert!(internal.public_subnets.is_empty());
+        assert!(internal.findings.is_empty());
+
+        let public = report(network("public-1a"), &[("lb", "app")]);
+        assert_eq!(public.internet_reachable, vec!["app", "lb"]);
+        assert_eq!(public.public_subnets, vec!["sn"]);
+
+        // VPC の外にある入口は、呼び出し元がなくても公開されている
+        let outside = report(
+            vec![
+                node("cdn", "CDN (CloudFront)", None, None),
+                node("app", "App Server", None, None),
+            ],
+            &[("cdn", "app")],
+        );
+        assert_eq!(outside.internet_reachable, vec!["app", "cdn"]);
+    }
+
+    #[test]
+    fn data_stores_in_public_subnets_are_findings() {
+        let report = report(
+            vec![
+                node("browser", "Web Browser", None, None),
+                node("vpc", "VPC (Network)", None, None),
+                node("az", "Availability Zone", Some("vpc"), None),
+                node("sn", "Subnet", Some("az"), Some("private-app")),
+                node("lb", "Load Balancer", Some("sn"), None),
+                node("app", "App Server", Some("sn"), None),
+                node("db", "RDBMS (SQL)", Some("sn"), None),
+            ],
+            &[("browser", "lb"), ("lb", "app"), ("app", "db")],
+        );
+        assert_eq!(
+            codes(&report),
+            vec![
+                ("data_store_in_public_subnet", "db".to_string()),
+                ("private_subnet_exposed", "sn".to_string()),
+                ("compute_unfiltered", "app".to_string()),
+            ]
+        );
+    }
+}
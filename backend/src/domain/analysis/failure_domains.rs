@@ -0,0 +1,95 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::domain::model::catalog::{ComponentCatalog, Hosting};
+use crate::domain::model::diagram::{CommunicationStyle, Diagram};
+
+// 同期通信でつながったノードの集まり (障害ドメイン)
+// 非同期 (Message Queue 等を経由) の接続は障害の伝播を断ち切るものとして扱う
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureDomain {
+    pub node_ids: Vec<String>,
+}
+
+// 同期接続の隣接関係 (双方向)
+fn sync_adjacency(diagram: &Diagram) -> HashMap<&str, Vec<&str>> {
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &diagram.edges {
+        if edge.effective_style(diagram) == CommunicationStyle::Async {
+            continue;
+        }
+        adjacency
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+        adjacency
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+    adjacency
+}
+
+pub fn failure_domains(diagram: &Diagram, catalog: &ComponentCatalog) -> Vec<FailureDomain> {
+    // グループと利用者端末は障害ドメインの構成要素に含めない
+    let participates = |id: &str| {
+        diagram
+            .node(id)
+            .and_then(|n| catalog.get(&n.type_label))
+            .is_none_or(|c| !c.semantics.group && c.semantics.hosting != Hosting::Client)
+    };
+    let adjacency = sync_adjacency(diagram);
+
+    let mut visited = HashSet::new();
+    let mut domains = Vec::new();
+    for node in &diagram.nodes {
+        if visited.contains(node.id.as_str()) || !participates(&node.id) {
+            continue;
+        }
+        let mut members = BTreeSet::new();
+        let mut queue = VecDeque::from([node.id.as_str()]);
+        visited.insert(node.id.as_str());
+        while let Some(current) = queue.pop_front() {
+            members.insert(current.to_string());
+            for &next in adjacency.get(current).into_iter().flatten() {
+                if participates(next) && visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        domains.push(FailureDomain {
+            node_ids: members.into_iter().collect(),
+        });
+    }
+    domains
+}
+
+// 指定ノードに同期的に依存している (呼び出し元となる) ノード
+// このノードが停止すると、これらのノードも応答できなくなる
+pub fn sync_dependents(diagram: &Diagram, node_id: &str) -> Vec<String> {
+    let mut dependents = BTreeSet::new();
+    let mut queue = VecDeque::from([node_id]);
+    while let Some(current) = queue.pop_front() {
+        for edge in &diagram.edges {
+            if edge.effective_style(diagram) == CommunicationStyle::Async {
+                continue;
+            }
+            let caller = if edge.target == current {
+                Some(edge.source.as_str())
+            } else if edge.bidirectional && edge.source == current {
+                Some(edge.target.as_str())
+            } else {
+                None
+            };
+            if let Some(caller) = caller
+                && caller != node_id
+                && dependents.insert(caller.to_string())
+            {
+                queue.push_back(caller);
+            }
+        }
+    }
+    dependents.into_iter().collect()
+}
@@ -0,0 +1,8 @@
+pub mod capacity;
+pub mod constraints;
+pub mod edge_rules;
+pub mod exposure;
+pub mod failure_domains;
+pub mod nesting;
+pub mod properties;
+pub mod threat_model;
@@ -0,0 +1,220 @@
+use serde::Serialize;
+
+use super::edge_rules::Severity;
+use crate::domain::model::catalog::{ComponentCatalog, Hosting};
+use crate::domain::model::containment::{ContainmentTree, GroupKind};
+use crate::domain::model::diagram::Diagram;
+
+// グループの入れ子 (parentNode) の検証
+// Subnet ⊂ Availability Zone ⊂ VPC の順に入れ子になっているかを確認する
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NestingIssue {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub message: String,
+}
+
+pub fn validate_nesting(diagram: &Diagram, catalog: &ComponentCatalog) -> Vec<NestingIssue> {
+    let tree = ContainmentTree::new(diagram);
+    let mut issues = Vec::new();
+
+    for node in &diagram.nodes {
+        let mut issue = |code, severity, message: String| {
+            issues.push(NestingIssue {
+                code,
+                severity,
+                node_id: node.id.clone(),
+                parent_id: node.parent_node.clone(),
+                message,
+            })
+        };
+        let kind = GroupKind::from_type(&node.type_label);
+
+        // 1. 親の参照が壊れていないか
+        let parent = match node.parent_node.as_deref() {
+            None => None,
+            Some(parent_id) => match diagram.node(parent_id) {
+                Some(parent) => Some(parent),
+                None => {
+                    issue(
+                        "missing_parent",
+                        Severity::Error,
+                        format!(
+                            "{} の所属先 {} が存在しません。",
+                            node.display_name(),
+                            parent_id
+                        ),
+                    );
+                    continue;
+                }
+            },
+        };
+        let parent_kind = parent.and_then(|p| GroupKind::from_type(&p.type_label));
+        if let Some(parent) = parent {
+            let parent_is_group = catalog
+                .get(&parent.type_label)
+                .is_some_and(|c| c.semantics.group);
+            if parent_kind.is_none() && !parent_is_group {
+                issue(
+                    "parent_not_group",
+                    Severity::Error,
+                    format!(
+                        "{} はグループではないため、{} を含めることはできません。",
+                        parent.display_name(),
+                        node.display_name()
+                    ),
+                );
+                continue;
+            }
+            if tree.is_in_cycle(&node.id) {
+                issue(
+                    "containment_cycle",
+                    Severity::Error,
+                    format!("{} の所属関係が循環しています。", node.display_name()),
+                );
+                continue;
+            }
+        }
+
+        // 2. グループ同士の入れ子の順序
+        if let Some(kind) = kind
+            && !kind.allows_parent(parent_kind)
+        {
+            let placed = parent.map_or("トップレベル".to_string(), |p| p.display_name());
+            issue(
+                "invalid_nesting",
+                Severity::Warning,
+                format!(
+                    "{} は {} に配置されていますが、{} の中に置く必要があります。",
+                    kind.as_str(),
+                    placed,
+                    kind.expected_parent()
+                ),
+            );
+        }
+
+        // 3. 利用者の端末はネットワークの外側にある
+        let is_client = catalog
+            .get(&node.type_label)
+            .is_some_and(|c| c.semantics.hosting == Hosting::Client);
+        if is_client && parent.is_some() {
+            issue(
+                "client_inside_network",
+                Severity::Warning,
+                format!(
+                    "{} は利用者の端末のため、VPC等のグループの外に配置してください。",
+                    node.display_name()
+                ),
+            );
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::json;
+
+    // (ID, 型, 親)
+    type Spec<'a> = (&'a str, &'a str, Option<&'a str>);
+
+    // 検出された (ノードID, コード)
+    fn issues(nodes: &[Spec]) -> Vec<(String, &'static str)> {
+        let nodes: Vec<serde_json::Value> = nodes
+            .iter()
+            .map(|(id, node_type, parent)| {
+                json!({ "id": id, "type": node_type, "parentNode": parent, "position": { "x": 0, "y": 0 } })
+            })
+            .collect();
+        let diagram: Diagram =
+            serde_json::from_value(json!({ "nodes": nodes, "edges": [] })).unwrap();
+        validate_nesting(&diagram, &test_catalog())
+            .into_iter()
+            .map(|i| (i.node_id, i.code))
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_networks_have_no_issues() {
+        let found = issues(&[
+            ("browser", "Web Browser", None),
+            ("vpc", "VPC (Network)", None),
+            ("az", "Availability Zone", Some("vpc")),
+            ("sn", "Subnet", Some("az")),
+            ("sg", "Security Group", Some("vpc")),
+            ("app", "App Server", Some("sn")),
+            ("db", "RDBMS (SQL)", Some("sg")),
+            ("cdn", "CDN (CloudFront)", None),
+        ]);
+        assert!(found.is_empty(), "{:?}", found);
+    }
+
+    #[test]
+    fn each_violation_is_reported() {
+        let cases: [(&[Spec], (&str, &str)); 7] = [
+            (
+                &[("app", "App Server", Some("ghost"))],
+                ("app", "missing_parent"),
+            ),
+            (
+                &[
+                    ("lb", "Load Balancer", None),
+                    ("app", "App Server", Some("lb")),
+                ],
+                ("app", "parent_not_group"),
+            ),
+            (
+                &[("az", "Availability Zone", None)],
+                ("az", "invalid_nesting"),
+            ),
+            (
+                &[
+                    ("vpc", "VPC (Network)", None),
+                    ("sn", "Subnet", Some("vpc")),
+                ],
+                ("sn", "invalid_nesting"),
+            ),
+            (
+                &[
+                    ("a", "VPC (Network)", None),
+                    ("b", "VPC (Network)", Some("a")),
+                ],
+                ("b", "invalid_nesting"),
+            ),
+            (&[("sg", "Security Group", None)], ("sg", "invalid_nesting")),
+            (
+                &[
+                    ("vpc", "VPC (Network)", None),
+                    ("phone", "Mobile App", Some("vpc")),
+                ],
+                ("phone", "client_inside_network"),
+            ),
+        ];
+        for (nodes, (id, code)) in cases {
+            assert_eq!(issues(nodes), vec![(id.to_string(), code)], "{:?}", nodes);
+        }
+    }
+
+    #[test]
+    fn cycles_are_reported_once_per_member() {
+        let found = issues(&[
+            ("az", "Availability Zone", Some("sn")),
+            ("sn", "Subnet", Some("az")),
+            ("app", "App Server", Some("sn")),
+        ]);
+        assert_eq!(
+            found,
+            vec![
+                ("az".to_string(), "containment_cycle"),
+                ("sn".to_string(), "containment_cycle"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use serde::Serialize;
+
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::Diagram;
+
+// ノードのプロパティ (台数・インスタンスサイズ等) の妥当性検証
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertyIssue {
+    pub node_id: String,
+    pub property: &'static str,
+    pub message: String,
+}
+
+const MAX_REPLICAS: u32 = 100;
+const MAX_AUTOSCALING: u32 = 1000;
+// RDS の上限に合わせる
+const MAX_READ_REPLICAS: u32 = 15;
+const MAX_CACHE_TTL_SECONDS: u32 = 365 * 24 * 60 * 60;
+
+pub fn validate_properties(diagram: &Diagram, catalog: &ComponentCatalog) -> Vec<PropertyIssue> {
+    let mut issues = Vec::new();
+
+    for node in &diagram.nodes {
+        let props = &node.properties;
+        let mut issue = |property, message: String| {
+            issues.push(PropertyIssue {
+                node_id: node.id.clone(),
+                property,
+                message,
+            })
+        };
+
+        // 1. コンポーネントの種類ごとに設定できる項目か
+        if let Some(def) = catalog.get(&node.type_label) {
+            for key in props.keys() {
+                if !def.semantics.properties.iter().any(|p| p == key) {
+                    issue(
+                        key,
+                        format!("{} には {} を設定できません。", node.type_label, key),
+                    );
+                }
+            }
+        }
+
+        // 2. 値の範囲
+        if let Some(replicas) = props.replicas
+            && !(1..=MAX_REPLICAS).contains(&replicas)
+        {
+            issue(
+                "replicas",
+                format!("replicas は 1〜{} の範囲で指定してください。", MAX_REPLICAS),
+            );
+        }
+        if let Some(scaling) = props.autoscaling {
+            if scaling.min == 0 || scaling.min > scaling.max || scaling.max > MAX_AUTOSCALING {
+                issue(
+                    "autoscaling",
+                    format!(
+                        "autoscaling は 1 <= min <= max <= {} となるよう指定してください。",
+                        MAX_AUTOSCALING
+                    ),
+                );
+            } else if let Some(replicas) = props.replicas
+                && !(scaling.min..=scaling.max).contains(&replicas)
+            {
+                issue(
+                    "replicas",
+                    "replicas が autoscaling の min〜max の範囲外です。".to_string(),
+                );
+            }
+        }
+        if let Some(read_replicas) = props.read_replicas
+            && read_replicas > MAX_READ_REPLICAS
+        {
+            issue(
+                "readReplicas",
+                format!(
+                    "readReplicas は {} 以下で指定してください。",
+                    MAX_READ_REPLICAS
+                ),
+            );
+        }
+        if let Some(ttl) = props.cache_ttl_seconds
+            && ttl > MAX_CACHE_TTL_SECONDS
+        {
+            issue(
+                "cacheTtlSeconds",
+                "cacheTtlSeconds は1年以内で指定してください。".to_string(),
+            );
+        }
+        if let Some(class) = &props.instance_class
+            && !is_instance_class(class)
+        {
+            issue(
+                "instanceClass",
+                format!(
+                    "instanceClass \"{}\" は \"t3.medium\" のような形式で指定してください。",
+                    class
+                ),
+            );
+        }
+    }
+
+    issues
+}
+
+// "t3.medium" や "db.r6g.large" のような「ファミリー.サイズ」形式か
+fn is_instance_class(class: &str) -> bool {
+    let parts: Vec<&str> = class.split('.').collect();
+    parts.len() >= 2
+        && parts.iter().all(|p| {
+            !p.is_empty()
+                && p.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    fn issues(node_type: &str, properties: Value) -> Vec<&'static str> {
+        let diagram: Diagram = serde_json::from_value(json!({
+            "nodes": [{
+                "id": "n", "type": node_type, "position": { "x": 0, "y": 0 },
+                "properties": properties
+            }],
+            "edges": []
+        }))
+        .unwrap();
+        validate_properties(&diagram, &test_catalog())
+            .iter()
+            .map(|i| i.property)
+            .collect()
+    }
+
+    #[test]
+    fn values_are_checked_per_component_type() {
+        let cases: [(&str, Value, &[&str]); 15] = [
+            (
+                "App Server",
+                json!({ "replicas": 3, "instanceClass": "t3.medium", "autoscaling": { "min": 2, "max": 10 }, "multiAz": true }),
+                &[],
+            ),
+            (
+                "RDBMS (SQL)",
+                json!({ "instanceClass": "db.r6g.large", "multiAz": true, "readReplicas": 15 }),
+                &[],
+            ),
+            ("Distributed Cache", json!({ "cacheTtlSeconds": 3600 }), &[]),
+            // 種類ごとに設定できない項目
+            (
+                "Function (Serverless)",
+                json!({ "replicas": 2 }),
+                &["replicas"],
+            ),
+            ("RDBMS (SQL)", json!({ "replicas": 2 }), &["replicas"]),
+            (
+                "Load Balancer",
+                json!({ "cacheTtlSeconds": 60 }),
+                &["cacheTtlSeconds"],
+            ),
+            // 値の範囲
+            ("App Server", json!({ "replicas": 0 }), &["replicas"]),
+            ("App Server", json!({ "replicas": 101 }), &["replicas"]),
+            (
+                "App Server",
+                json!({ "autoscaling": { "min": 0, "max": 4 } }),
+                &["autoscaling"],
+            ),
+            (
+                "App Server",
+                json!({ "autoscaling": { "min": 5, "max": 4 } }),
+                &["autoscaling"],
+            ),
+            (
+                "App Server",
+                json!({ "autoscaling": { "min": 1, "max": 1001 } }),
+                &["autoscaling"],
+            ),
+            (
+                "App Server",
+                json!({ "replicas": 12, "autoscaling": { "min": 2, "max": 10 } }),
+                &["replicas"],
+            ),
+            (
+                "RDBMS (SQL)",
+                json!({ "readReplicas": 16 }),
+                &["readReplicas"],
+            ),
+            (
+                "CDN (CloudFront)",
+                json!({ "cacheTtlSeconds": 366 * 24 * 60 * 60 }),
+                &["cacheTtlSeconds"],
+            ),
+            // 定義のない型は設定項目を判定せず、値の範囲だけを確認する
+            (
+                "Mainframe",
+                json!({ "replicas": 0, "multiAz": true }),
+                &["replicas"],
+            ),
+        ];
+        for (node_type, properties, expected) in cases {
+            assert_eq!(
+                issues(node_type, properties.clone()),
+                expected,
+                "{} {}",
+                node_type,
+                properties
+            );
+        }
+    }
+
+    #[test]
+    fn instance_classes_must_be_family_and_size() {
+        for class in ["t3.medium", "db.r6g.large", "cache.t4g.micro", "m5.2xlarge"] {
+            assert!(is_instance_class(class), "{}", class);
+        }
+        for class in [
+            "large",
+            "T3.medium",
+            "t3.",
+            ".medium",
+            "t3 medium",
+            "t3..large",
+        ] {
+            assert!(!is_instance_class(class), "{}", class);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+
+use crate::domain::model::catalog::{ComponentCatalog, Hosting};
+use crate::domain::model::containment::{ContainmentTree, GroupKind};
+use crate::domain::model::diagram::{Diagram, Edge, Protocol};
+
+// データフローからの STRIDE 脅威モデルの生成
+// 信頼境界 (インターネット / VPC / サブネット) をまたぐ接続ごとに、想定される脅威と
+// 設計図上の既存コンポーネントによる対策を洗い出す
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Stride {
+    Spoofing,
+    Tampering,
+    Repudiation,
+    InformationDisclosure,
+    DenialOfService,
+    ElevationOfPrivilege,
+}
+
+impl Stride {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Stride::Spoofing => "なりすまし (Spoofing)",
+            Stride::Tampering => "改ざん (Tampering)",
+            Stride::Repudiation => "否認 (Repudiation)",
+            Stride::InformationDisclosure => "情報漏えい (Information Disclosure)",
+            Stride::DenialOfService => "サービス拒否 (Denial of Service)",
+            Stride::ElevationOfPrivilege => "権限昇格 (Elevation of Privilege)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustBoundary {
+    // 利用者の端末 (インターネット) とシステムの間
+    Internet,
+    // VPCの内側と外側、または異なるVPCの間
+    Vpc,
+    // 同じVPC内の異なるサブネットの間
+    Subnet,
+}
+
+impl TrustBoundary {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrustBoundary::Internet => "インターネット",
+            TrustBoundary::Vpc => "VPC",
+            TrustBoundary::Subnet => "サブネット",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mitigation {
+    // 対策となるコンポーネント (プロトコルによる対策の場合は None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Threat {
+    pub category: Stride,
+    pub description: String,
+    pub mitigations: Vec<Mitigation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataFlow {
+    pub source: String,
+    pub target: String,
+    pub boundary: TrustBoundary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Protocol>,
+    pub threats: Vec<Threat>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreatModel {
+    pub data_flows: Vec<DataFlow>,
+    pub total_threats: usize,
+    // 対策となるコンポーネントが見つからなかった脅威の数
+    pub unmitigated_threats: usize,
+}
+
+// 監査ログ・追跡の基盤 (否認への対策)
+const AUDIT_TRAIL: [&str; 2] = ["Log Aggregator", "Dist. Tracer"];
+
+pub fn generate(diagram: &Diagram, catalog: &ComponentCatalog) -> ThreatModel {
+    let tree = ContainmentTree::new(diagram);
+    let is_client = |id: &str| {
+        diagram
+            .node(id)
+            .and_then(|n| catalog.get(&n.type_label))
+            .is_some_and(|c| c.semantics.hosting == Hosting::Client)
+    };
+    let audit: Vec<Mitigation> = diagram
+        .nodes
+        .iter()
+        .filter(|n| AUDIT_TRAIL.contains(&n.type_label.as_str()))
+        .map(|n| mitigation(n.id.as_str(), n.display_name()))
+        .collect();
+
+    let mut data_flows = Vec::new();
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (diagram.node(&edge.source), diagram.node(&edge.target))
+        else {
+            continue;
+        };
+        let enclosing = |id: &str, kind| tree.enclosing(id, kind).map(|n| n.id.as_str());
+        let boundary = if is_client(&source.id) || is_client(&target.id) {
+            TrustBoundary::Internet
+        } else if enclosing(&source.id, GroupKind::Vpc) != enclosing(&target.id, GroupKind::Vpc) {
+            TrustBoundary::Vpc
+        } else if enclosing(&source.id, GroupKind::Subnet)
+            != enclosing(&target.id, GroupKind::Subnet)
+        {
+            TrustBoundary::Subnet
+        } else {
+            continue;
+        };
+
+        // 受け手側のノード (利用者の端末へ向かう接続は送り手側) を守る対象とする
+        let protected = if is_client(&target.id) {
+            source
+        } else {
+            target
+        };
+        let protected_def = catalog.get(&protected.type_label);
+        let stateful = protected_def.is_some_and(|c| c.semantics.stateful);
+        let compute = protected_def.is_some_and(|c| c.category == "compute");
+        let internet = boundary == TrustBoundary::Internet;
+
+        // この接続より上流 (または接続の両端) にある検査・制限のコンポーネント
+        let upstream = upstream_of(diagram, edge);
+        let on_path = |types: &[&str]| -> Vec<Mitigation> {
+            diagram
+                .nodes
+                .iter()
+                .filter(|n| types.contains(&n.type_label.as_str()))
+                .filter(|n| upstream.contains(n.id.as_str()))
+                .map(|n| mitigation(n.id.as_str(), n.display_name()))
+                .collect()
+        };
+        let security_group: Vec<Mitigation> = tree
+            .enclosing(&protected.id, GroupKind::SecurityGroup)
+            .map(|sg| mitigation(sg.id.as_str(), sg.display_name()))
+            .into_iter()
+            .collect();
+        let tls: Vec<Mitigation> = match edge.protocol {
+            Some(Protocol::Https) => vec![Mitigation {
+                node_id: None,
+                name: "TLS (HTTPS)".to_string(),
+            }],
+            _ => Vec::new(),
+        };
+        let name = protected.display_name();
+
+        let mut threats = Vec::new();
+        if internet || stateful {
+            threats.push(Threat {
+                category: Stride::Spoofing,
+                description: format!("正規の利用者やサービスを装って {} にアクセスされる", name),
+                mitigations: on_path(&["API Gateway"]),
+            });
+        }
+        threats.push(Threat {
+            category: Stride::Tampering,
+            description:
+                "通信経路上でリクエストやデータを改ざんされる、または不正な入力を送り込まれる"
+                    .to_string(),
+            mitigations: [tls.clone(), on_path(&["WAF (Firewall)"])].concat(),
+        });
+        if compute || stateful {
+            threats.push(Threat {
+                category: Stride::Repudiation,
+                description: format!("{} に対する操作の記録が残らず、実行者を特定できない", name),
+                mitigations: audit.clone(),
+            });
+        }
+        if internet || stateful || edge.protocol == Some(Protocol::Http) {
+            threats.push(Threat {
+                category: Stride::InformationDisclosure,
+                description: format!("{} との通信や保持するデータが第三者に漏えいする", name),
+                mitigations: [tls, security_group.clone()].concat(),
+            });
+        }
+        if internet {
+            threats.push(Threat {
+                category: Stride::DenialOfService,
+                description: format!("大量のリクエストにより {} が応答できなくなる", name),
+                mitigations: on_path(&["WAF (Firewall)", "API Gateway", "CDN (CloudFront)"]),
+            });
+        }
+        if compute || stateful {
+            threats.push(Threat {
+                category: Stride::ElevationOfPrivilege,
+                description: format!("{} を足がかりに、本来許可されていない操作を行われる", name),
+                mitigations: [security_group, on_path(&["API Gateway"])].concat(),
+            });
+        }
+
+        data_flows.push(DataFlow {
+            source: source.id.clone(),
+            target: target.id.clone(),
+            boundary,
+            protocol: edge.protocol,
+            threats,
+        });
+    }
+
+    let total_threats = data_flows.iter().map(|f| f.threats.len()).sum();
+    let unmitigated_threats = data_flows
+        .iter()
+        .flat_map(|f| &f.threats)
+        .filter(|t| t.mitigations.is_empty())
+        .count();
+    ThreatModel {
+        data_flows,
+        total_threats,
+        unmitigated_threats,
+    }
+}
+
+fn mitigation(node_id: &str, name: String) -> Mitigation {
+    Mitigation {
+        node_id: Some(node_id.to_string()),
+        name,
+    }
+}
+
+// 接続の両端と、接続元に至るまでに通過しうるノード
+fn upstream_of<'a>(diagram: &'a Diagram, edge: &'a Edge) -> HashSet<&'a str> {
+    let mut visited = HashSet::from([edge.source.as_str(), edge.target.as_str()]);
+    let mut queue = VecDeque::from([edge.source.as_str()]);
+    while let Some(current) = queue.pop_front() {
+        for e in diagram.edges.iter().filter(|e| e.target == current) {
+            if visited.insert(e.source.as_str()) {
+                queue.push_back(e.source.as_str());
+            }
+        }
+    }
+    visited
+}
+
+impl ThreatModel {
+    // 研修資料として配布できる Markdown 形式
+    pub fn to_markdown(&self, diagram: &Diagram) -> String {
+        // 見出しは1行に収める
+        let name = |id: &str| {
+            let name = diagram
+                .node(id)
+                .map(|n| n.display_name())
+                .unwrap_or_else(|| id.to_string());
+            name.split_whitespace().collect::<Vec<_>>().join(" ")
+        };
+        let mut md = String::from("# 脅威モデル (STRIDE)\n\n");
+        md.push_str(&format!(
+            "信頼境界をまたぐデータフロー: {}件 / 脅威: {}件 (対策なし: {}件)\n",
+            self.data_flows.len(),
+            self.total_threats,
+            self.unmitigated_threats
+        ));
+        if self.data_flows.is_empty() {
+            md.push_str("\n信頼境界をまたぐデータフローはありません。\n");
+        }
+        for flow in &self.data_flows {
+            md.push_str(&format!(
+                "\n## {} → {}\n\n- 信頼境界: {}\n",
+                name(&flow.source),
+                name(&flow.target),
+                flow.boundary.label()
+            ));
+            if let Some(protocol) = flow.protocol {
+                md.push_str(&format!("- プロトコル: {}\n", protocol.as_str()));
+            }
+            md.push_str("\n| 脅威 | 内容 | 既存の対策 |\n|---|---|---|\n");
+            for threat in &flow.threats {
+                let mitigations = if threat.mitigations.is_empty() {
+                    "⚠️ なし".to_string()
+                } else {
+                    threat
+                        .mitigations
+                        .iter()
+                        .map(|m| table_cell(&m.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                md.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    threat.category.label(),
+                    table_cell(&threat.description),
+                    mitigations
+                ));
+            }
+        }
+        md
+    }
+}
+
+// ラベル等に含まれる "|" や改行で表が崩れないようにする
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+
+    #[test]
+    fn markdown_escapes_labels_in_headings_and_tables() {
+        let diagram: Diagram = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "id": "browser", "type": "Web Browser", "label": "Shop|front\nend", "position": { "x": 0, "y": 0 } },
+                { "id": "lb", "type": "Load Balancer", "label": "LB | main", "position": { "x": 200, "y": 0 } }
+            ],
+            "edges": [{ "source": "browser", "target": "lb", "protocol": "HTTP" }]
+        }))
+        .unwrap();
+        let model = generate(&diagram, &test_catalog());
+        let markdown = model.to_markdown(&diagram);
+
+        assert!(!model.data_flows.is_empty());
+        for line in markdown.lines().filter(|l| l.starts_with("| ")) {
+            // 区切りの "|" 以外はすべてエスケープされている
+            let separators = line.matches('|').count() - line.matches("\\|").count();
+            assert_eq!(separators, 4, "{}", line);
+        }
+        assert!(markdown.contains("## Web Browser「Shop|front end」 → "));
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use super::{LayoutTree, edge_label, identifiers, is_group, node_lines};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{CommunicationStyle, Diagram, Node};
+
+// Graphviz の DOT 形式への書き出し
+// VPC / AZ / Subnet 等のグループはクラスタ (subgraph cluster_*) として入れ子にする
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let ids = identifiers(diagram);
+    let layout = LayoutTree::build(diagram, catalog);
+    let mut out = String::from("digraph architecture {\n");
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    compound=true;\n");
+    out.push_str("    node [fontname=\"sans-serif\", fontsize=12];\n");
+    out.push_str("    edge [fontname=\"sans-serif\", fontsize=10];\n");
+
+    for node in &layout.roots {
+        write_node(&mut out, node, &layout, &ids, catalog, 1);
+    }
+
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (ids.get(&edge.source), ids.get(&edge.target)) else {
+            continue;
+        };
+        let mut attrs = Vec::new();
+        let label = edge_label(edge);
+        if !label.is_empty() {
+            attrs.push(format!("label=\"{}\"", escape(&label)));
+        }
+        match edge.effective_style(diagram) {
+            CommunicationStyle::Async => attrs.push("style=dashed".to_string()),
+            CommunicationStyle::Stream => attrs.push("style=bold".to_string()),
+            CommunicationStyle::Sync => {}
+        }
+        if edge.bidirectional {
+            attrs.push("dir=both".to_string());
+        }
+        // グループへの接続は、クラスタ内の目印のノードを経由して枠線に向けて引く
+        for (end, key) in [(&edge.source, "ltail"), (&edge.target, "lhead")] {
+            if diagram.node(end).is_some_and(|n| is_group(n, catalog)) {
+                attrs.push(format!("{}=cluster_{}", key, ids[end.as_str()]));
+            }
+        }
+
+        out.push_str(&format!("    {} -> {}", source, target));
+        if !attrs.is_empty() {
+            out.push_str(&format!(" [{}]", attrs.join(", ")));
+        }
+        out.push_str(";\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn write_node(
+    out: &mut String,
+    node: &Node,
+    layout: &LayoutTree,
+    ids: &HashMap<String, String>,
+    catalog: &ComponentCatalog,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+    let id = &ids[&node.id];
+    let label = node_lines(node)
+        .iter()
+        .map(|l| escape(l))
+        .collect::<Vec<_>>()
+        .join("\\n");
+    let def = catalog.get(&node.type_label);
+
+    if is_group(node, catalog) {
+        out.push_str(&format!("{}subgraph cluster_{} {{\n", indent, id));
+        out.push_str(&format!("{}    label=\"{}\";\n", indent, label));
+        out.push_str(&format!("{}    style=\"rounded,dashed\";\n", indent));
+        if let Some(def) = def {
+            out.push_str(&format!("{}    color=\"{}\";\n", indent, def.color));
+        }
+        // 空のグループや、グループ自体への接続のための目印
+        out.push_str(&format!(
+            "{}    {} [shape=point, style=invis, width=0, label=\"\"];\n",
+            indent, id
+        ));
+        for child in layout.children(&node.id) {
+            write_node(out, child, layout, ids, catalog, depth + 1);
+        }
+        out.push_str(&format!("{}}}\n", indent));
+        return;
+    }
+
+    // カテゴリに応じた形状と、architecture_defs.json の配色
+    let shape = match def.map(|d| d.category.as_str()) {
+        Some("database") => "cylinder",
+        Some("client") => "ellipse",
+        Some("traffic") => "hexagon",
+        _ => "box",
+    };
+    let mut attrs = vec![
+        format!("label=\"{}\"", label),
+        format!("shape={}", shape),
+        "style=\"filled,rounded\"".to_string(),
+    ];
+    if let Some(def) = def {
+        attrs.push(format!("fillcolor=\"{}\"", def.bg_color));
+        attrs.push(format!("color=\"{}\"", def.color));
+    }
+    out.push_str(&format!("{}{} [{}];\n", indent, id, attrs.join(", ")));
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::export;
+    use crate::domain::model::catalog::test_catalog;
+
+    #[test]
+    fn sample_matches_golden_output() {
+        let out = render(&export::sample(), &test_catalog());
+        assert_eq!(out, include_str!("fixtures/sample.dot"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use super::{LayoutTree, edge_label, is_group};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::GroupKind;
+use crate::domain::model::diagram::{CommunicationStyle, Diagram, Node};
+
+// draw.io (diagrams.net) の mxGraph XML 形式への書き出し
+// グループは AWS アイコンの枠、コンポーネントはカテゴリの配色の図形として描き、
+// 型やプロパティは object 要素の属性に持たせて、取り込み時に復元できるようにする
+
+// ノードのIDと重ならない、ルートとレイヤーのID
+const ROOT_ID: &str = "sandbox-root";
+const LAYER_ID: &str = "sandbox-layer";
+
+// フロントエンドでの既定サイズ
+const GROUP_SIZE: (f64, f64) = (300.0, 200.0);
+const NODE_SIZE: (f64, f64) = (150.0, 40.0);
+const GROUP_PADDING: f64 = 20.0;
+
+const GROUP_BASE: &str = "container=1;collapsible=0;recursiveResize=0;verticalAlign=top;align=left;spacingLeft=30;html=1;whiteSpace=wrap;";
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let layout = LayoutTree::build(diagram, catalog);
+    let mut sizes = HashMap::new();
+    for node in &layout.roots {
+        measure(node, &layout, catalog, &mut sizes);
+    }
+
+    let mut out = String::from(
+        "<mxfile host=\"archtecture-sandbox\">\n  <diagram id=\"architecture\" name=\"Architecture\">\n    <mxGraphModel grid=\"1\" gridSize=\"10\" guides=\"1\" connect=\"1\" arrows=\"1\" page=\"0\">\n      <root>\n",
+    );
+    out.push_str(&format!("        <mxCell id=\"{}\" />\n", ROOT_ID));
+    out.push_str(&format!(
+        "        <mxCell id=\"{}\" parent=\"{}\" />\n",
+        LAYER_ID, ROOT_ID
+    ));
+
+    // 親を子より先に出力する
+    let mut stack: Vec<(&Node, &str)> = layout.roots.iter().rev().map(|n| (*n, LAYER_ID)).collect();
+    while let Some((node, parent)) = stack.pop() {
+        write_node(&mut out, node, parent, sizes[node.id.as_str()], catalog);
+        for child in layout.children(&node.id).iter().rev() {
+            stack.push((child, node.id.as_str()));
+        }
+    }
+
+    for (i, edge) in diagram.edges.iter().enumerate() {
+        if diagram.node(&edge.source).is_none() || diagram.node(&edge.target).is_none() {
+            continue;
+        }
+        let id = edge.id.clone().unwrap_or_else(|| format!("edge-{}", i + 1));
+        let mut style =
+            String::from("edgeStyle=orthogonalEdgeStyle;rounded=0;html=1;endArrow=classic;");
+        match edge.effective_style(diagram) {
+            CommunicationStyle::Async => style.push_str("dashed=1;"),
+            CommunicationStyle::Stream => style.push_str("strokeWidth=3;"),
+            CommunicationStyle::Sync => {}
+        }
+        if edge.bidirectional {
+            style.push_str("startArrow=classic;");
+        }
+        let mut attributes = vec![("label", edge_label(edge)), ("id", id)];
+        if let Some(protocol) = edge.protocol {
+            attributes.push(("protocol", protocol.as_str().to_string()));
+        }
+        if let Some(style) = edge.style {
+            attributes.push(("communicationStyle", style.as_str().to_string()));
+        }
+        out.push_str(&format!(
+            "        <object{}>\n          <mxCell style=\"{}\" edge=\"1\" parent=\"{}\" source=\"{}\" target=\"{}\">\n            <mxGeometry relative=\"1\" as=\"geometry\" />\n          </mxCell>\n        </object>\n",
+            attribute_list(&attributes),
+            escape(&style),
+            LAYER_ID,
+            escape(&edge.source),
+            escape(&edge.target)
+        ));
+    }
+
+    out.push_str("      </root>\n    </mxGraphModel>\n  </diagram>\n</mxfile>\n");
+    out
+}
+
+// サイズの指定がないグループは、子が収まる大きさにする
+fn measure<'a>(
+    node: &'a Node,
+    layout: &LayoutTree<'a>,
+    catalog: &ComponentCatalog,
+    sizes: &mut HashMap<&'a str, (f64, f64)>,
+) -> (f64, f64) {
+    let group = is_group(node, catalog);
+    let (mut width, mut height) = if group { GROUP_SIZE } else { NODE_SIZE };
+    for child in layout.children(&node.id) {
+        let (w, h) = measure(child, layout, catalog, sizes);
+        width = width.max(child.position.x + w + GROUP_PADDING);
+        height = height.max(child.position.y + h + GROUP_PADDING);
+    }
+    let size = (node.width.unwrap_or(width), node.height.unwrap_or(height));
+    sizes.insert(node.id.as_str(), size);
+    size
+}
+
+fn write_node(
+    out: &mut String,
+    node: &Node,
+    parent: &str,
+    (width, height): (f64, f64),
+    catalog: &ComponentCatalog,
+) {
+    let def = catalog.get(&node.type_label);
+    let style = match GroupKind::from_type(&node.type_label) {
+        Some(GroupKind::Vpc) => format!(
+            "shape=mxgraph.aws4.group;grIcon=mxgraph.aws4.group_vpc2;strokeColor=#8C4FFF;fillColor=none;fontColor=#AAB7B8;{}",
+            GROUP_BASE
+        ),
+        Some(GroupKind::AvailabilityZone) => format!(
+            "fillColor=none;strokeColor=#147EBA;dashed=1;fontColor=#147EBA;{}",
+            GROUP_BASE
+        ),
+        Some(GroupKind::Subnet) => format!(
+            "shape=mxgraph.aws4.group;grIcon=mxgraph.aws4.group_security_group;grStroke=0;strokeColor=#00A4A6;fillColor=#E6F6F7;fontColor=#147EBA;{}",
+            GROUP_BASE
+        ),
+        Some(GroupKind::SecurityGroup) => format!(
+            "fillColor=none;strokeColor=#DD3522;fontColor=#DD3522;{}",
+            GROUP_BASE
+        ),
+        None => {
+            let shape = match def.map(|d| d.category.as_str()) {
+                Some("database") => "shape=cylinder3;boundedLbl=1;size=8;",
+                _ => "rounded=1;",
+            };
+            let colors = def
+                .map(|d| format!("fillColor={};strokeColor={};", d.bg_color, d.color))
+                .unwrap_or_default();
+            format!("{}{}", shape, colors)
+        }
+    };
+
+    let mut attributes = vec![
+        (
+            "label",
+            node.label
+                .clone()
+                .filter(|l| !l.is_empty())
+                .unwrap_or_else(|| node.type_label.clone()),
+        ),
+        ("id", node.id.clone()),
+        ("sandboxType", node.type_label.clone()),
+    ];
+    if let Some(desc) = node.description.as_deref().filter(|d| !d.is_empty()) {
+        attributes.push(("tooltip", desc.to_string()));
+    }
+    if !node.properties.is_empty()
+        && let Ok(properties) = serde_json::to_string(&node.properties)
+    {
+        attributes.push(("sandboxProperties", properties));
+    }
+    out.push_str(&format!(
+        "        <object{}>\n          <mxCell style=\"{}\" vertex=\"1\" parent=\"{}\">\n            <mxGeometry x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" as=\"geometry\" />\n          </mxCell>\n        </object>\n",
+        attribute_list(&attributes),
+        escape(&style),
+        escape(parent),
+        node.position.x,
+        node.position.y,
+        width,
+        height
+    ));
+}
+
+fn attribute_list(attributes: &[(&str, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!(" {}=\"{}\"", name, escape(value)))
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
@@ -0,0 +1,202 @@
+// Terraform の HCL を `terraform fmt` と同じ体裁で書き出すための最小限の構造
+// 連続する1行の属性は `=` の位置を揃え、複数行の値やコメントでその揃えを区切る
+
+pub enum Value {
+    // そのまま出力する式 (参照や関数呼び出し等)
+    Raw(String),
+    List(Vec<Value>),
+    Object(Vec<Item>),
+    // jsonencode({ ... }) のような、オブジェクトを受け取る関数呼び出し
+    Call(&'static str, Box<Value>),
+}
+
+impl Value {
+    pub fn string(text: &str) -> Self {
+        Value::Raw(quote(text))
+    }
+
+    pub fn reference(expr: impl Into<String>) -> Self {
+        Value::Raw(expr.into())
+    }
+
+    pub fn object(entries: Vec<(&str, Value)>) -> Self {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| Item::Attr(k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    pub fn strings(texts: &[&str]) -> Self {
+        Value::List(texts.iter().map(|t| Value::string(t)).collect())
+    }
+
+    pub fn references(exprs: Vec<String>) -> Self {
+        Value::List(exprs.into_iter().map(Value::Raw).collect())
+    }
+
+    fn is_multiline(&self) -> bool {
+        match self {
+            Value::Raw(_) => false,
+            Value::List(items) => items.iter().any(Value::is_multiline),
+            Value::Object(_) | Value::Call(..) => true,
+        }
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        match self {
+            Value::Raw(expr) => out.push_str(expr),
+            Value::List(items) if !self.is_multiline() => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|item| {
+                        let mut s = String::new();
+                        item.write(&mut s, depth);
+                        s
+                    })
+                    .collect();
+                out.push_str(&format!("[{}]", items.join(", ")));
+            }
+            Value::List(items) => {
+                out.push_str("[\n");
+                for item in items {
+                    out.push_str(&indent(depth + 1));
+                    item.write(out, depth + 1);
+                    out.push_str(",\n");
+                }
+                out.push_str(&format!("{}]", indent(depth)));
+            }
+            Value::Object(items) => {
+                out.push_str("{\n");
+                write_items(out, items, depth + 1);
+                out.push_str(&format!("{}}}", indent(depth)));
+            }
+            Value::Call(name, arg) => {
+                out.push_str(&format!("{}(", name));
+                arg.write(out, depth);
+                out.push(')');
+            }
+        }
+    }
+}
+
+pub enum Item {
+    Attr(String, Value),
+    Block(Block),
+    Comment(String),
+    Blank,
+}
+
+pub struct Block {
+    header: String,
+    items: Vec<Item>,
+}
+
+impl Block {
+    // 例: Block::new("resource", &["aws_vpc", "main"])
+    pub fn new(kind: &str, labels: &[&str]) -> Self {
+        let mut header = kind.to_string();
+        for label in labels {
+            header.push_str(&format!(" {}", quote(label)));
+        }
+        Self {
+            header,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn attr(mut self, key: &str, value: Value) -> Self {
+        self.items.push(Item::Attr(key.to_string(), value));
+        self
+    }
+
+    pub fn attr_if(self, condition: bool, key: &str, value: impl FnOnce() -> Value) -> Self {
+        if condition {
+            self.attr(key, value())
+        } else {
+            self
+        }
+    }
+
+    pub fn block(mut self, block: Block) -> Self {
+        self.items.push(Item::Block(block));
+        self
+    }
+
+    pub fn comment(mut self, text: &str) -> Self {
+        self.items.push(Item::Comment(text.to_string()));
+        self
+    }
+
+    pub fn blank(mut self) -> Self {
+        self.items.push(Item::Blank);
+        self
+    }
+
+    pub fn write(&self, out: &mut String, depth: usize) {
+        out.push_str(&format!("{}{} {{", indent(depth), self.header));
+        if self.items.is_empty() {
+            out.push_str("}\n");
+            return;
+        }
+        out.push('\n');
+        write_items(out, &self.items, depth + 1);
+        out.push_str(&format!("{}}}\n", indent(depth)));
+    }
+}
+
+fn write_items(out: &mut String, items: &[Item], depth: usize) {
+    let mut i = 0;
+    while i < items.len() {
+        match &items[i] {
+            Item::Attr(_, value) if !value.is_multiline() => {
+                // 1行の属性が続く範囲で、キーの幅を揃える
+                let run: Vec<(&String, &Value)> = items[i..]
+                    .iter()
+                    .map_while(|item| match item {
+                        Item::Attr(k, v) if !v.is_multiline() => Some((k, v)),
+                        _ => None,
+                    })
+                    .collect();
+                let width = run.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+                for (key, value) in &run {
+                    out.push_str(&format!("{}{:width$} = ", indent(depth), key));
+                    value.write(out, depth);
+                    out.push('\n');
+                }
+                i += run.len();
+                continue;
+            }
+            Item::Attr(key, value) => {
+                out.push_str(&format!("{}{} = ", indent(depth), key));
+                value.write(out, depth);
+                out.push('\n');
+            }
+            Item::Block(block) => block.write(out, depth),
+            Item::Comment(text) => out.push_str(&format!("{}# {}\n", indent(depth), text)),
+            Item::Blank => out.push('\n'),
+        }
+        i += 1;
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+// HCL の文字列リテラル (テンプレートの "${" もエスケープする)
+pub fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace("${", "$${")
+        .replace("%{", "%%{");
+    format!("\"{}\"", escaped)
+}
+
+// 変数の埋め込みを含む文字列 (text はエスケープ済みとして扱う)
+pub fn template(text: &str) -> Value {
+    Value::Raw(format!("\"{}\"", text))
+}
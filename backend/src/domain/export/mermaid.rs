@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{LayoutTree, edge_label, identifiers, is_group, node_lines};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{CommunicationStyle, Diagram, Node};
+
+// Mermaid のフローチャート形式への書き出し
+// GitHub の PR や Wiki にそのまま貼り付けて表示できる
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let ids = identifiers(diagram);
+    let layout = LayoutTree::build(diagram, catalog);
+    let mut out = String::from("flowchart LR\n");
+
+    for node in &layout.roots {
+        write_node(&mut out, node, &layout, &ids, catalog, 1);
+    }
+
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (ids.get(&edge.source), ids.get(&edge.target)) else {
+            continue;
+        };
+        let arrow = match (edge.bidirectional, edge.effective_style(diagram)) {
+            (true, CommunicationStyle::Async) => "<-.->",
+            (true, CommunicationStyle::Stream) => "<==>",
+            (true, CommunicationStyle::Sync) => "<-->",
+            (false, CommunicationStyle::Async) => "-.->",
+            (false, CommunicationStyle::Stream) => "==>",
+            (false, CommunicationStyle::Sync) => "-->",
+        };
+        // 通信方式と双方向の指定は矢印の種類で表すため、ラベルとプロトコルのみ書く
+        let label = edge_label(edge);
+        if label.is_empty() {
+            out.push_str(&format!("    {} {} {}\n", source, arrow, target));
+        } else {
+            out.push_str(&format!(
+                "    {} {}|\"{}\"| {}\n",
+                source,
+                arrow,
+                escape(&label),
+                target
+            ));
+        }
+    }
+
+    // カテゴリごとの配色 (architecture_defs.json の色)
+    let mut by_category: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for node in diagram.nodes.iter().filter(|n| !is_group(n, catalog)) {
+        if let Some(def) = catalog.get(&node.type_label) {
+            by_category
+                .entry(def.category.as_str())
+                .or_default()
+                .push(ids[&node.id].as_str());
+        }
+    }
+    for (category, members) in &by_category {
+        let Some(def) = catalog.components().find(|c| c.category == *category) else {
+            continue;
+        };
+        out.push_str(&format!(
+            "    classDef {} fill:{},stroke:{},color:#1f2937\n",
+            category, def.bg_color, def.color
+        ));
+        out.push_str(&format!("    class {} {}\n", members.join(","), category));
+    }
+    out
+}
+
+fn write_node(
+    out: &mut String,
+    node: &Node,
+    layout: &LayoutTree,
+    ids: &HashMap<String, String>,
+    catalog: &ComponentCatalog,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+    let id = &ids[&node.id];
+    let label = node_lines(node)
+        .iter()
+        .map(|l| escape(l))
+        .collect::<Vec<_>>()
+        .join("<br/>");
+
+    if is_group(node, catalog) {
+        out.push_str(&format!("{}subgraph {}[\"{}\"]\n", indent, id, label));
+        for child in layout.children(&node.id) {
+            write_node(out, child, layout, ids, catalog, depth + 1);
+        }
+        out.push_str(&format!("{}end\n", indent));
+        return;
+    }
+
+    // カテゴリに応じた形状 (データストアは円柱、利用者の端末は角丸)
+    let category = catalog
+        .get(&node.type_label)
+        .map(|c| c.category.as_str())
+        .unwrap_or("");
+    let shape = match category {
+        "database" => format!("[(\"{}\")]", label),
+        "client" => format!("([\"{}\"])", label),
+        "traffic" => format!("{{{{\"{}\"}}}}", label),
+        _ => format!("[\"{}\"]", label),
+    };
+    out.push_str(&format!("{}{}{}\n", indent, id, shape));
+}
+
+// Mermaid のラベル内で特別な意味を持つ文字をエスケープする
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::export;
+    use crate::domain::model::catalog::test_catalog;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn sample_matches_golden_output() {
+        let out = render(&export::sample(), &test_catalog());
+        assert_eq!(out, include_str!("fixtures/sample.mmd"));
+    }
+
+    fn render_json(nodes: Value, edges: Value) -> String {
+        let diagram: Diagram =
+            serde_json::from_value(json!({ "nodes": nodes, "edges": edges })).unwrap();
+        render(&diagram, &test_catalog())
+    }
+
+    fn pair() -> Value {
+        json!([
+            { "id": "a", "type": "App Server", "position": { "x": 0, "y": 0 } },
+            { "id": "b", "type": "Worker (Async)", "position": { "x": 0, "y": 0 } }
+        ])
+    }
+
+    #[test]
+    fn arrows_follow_style_and_direction() {
+        let cases = [
+            (json!({}), "n_a --> n_b"),
+            (json!({ "style": "async" }), "n_a -.-> n_b"),
+            (json!({ "style": "stream" }), "n_a ==> n_b"),
+            (json!({ "bidirectional": true }), "n_a <--> n_b"),
+            (
+                json!({ "style": "async", "bidirectional": true }),
+                "n_a <-.-> n_b",
+            ),
+            (
+                json!({ "style": "stream", "bidirectional": true }),
+                "n_a <==> n_b",
+            ),
+            (
+                json!({ "protocol": "gRPC", "label": "jobs" }),
+                "n_a -->|\"jobs, gRPC\"| n_b",
+            ),
+        ];
+        for (attrs, expected) in cases {
+            let mut edge = json!({ "source": "a", "target": "b" });
+            edge.as_object_mut()
+                .unwrap()
+                .extend(attrs.as_object().unwrap().clone());
+            let out = render_json(pair(), json!([edge]));
+            assert!(
+                out.lines().any(|l| l.trim() == expected),
+                "{}\n{}",
+                attrs,
+                out
+            );
+        }
+    }
+
+    #[test]
+    fn groups_become_nested_subgraphs() {
+        let out = render_json(
+            json!([
+                { "id": "vpc", "type": "VPC (Network)", "label": "main", "position": { "x": 0, "y": 0 } },
+                { "id": "az", "type": "Availability Zone", "parentNode": "vpc", "position": { "x": 0, "y": 0 } },
+                { "id": "db", "type": "RDBMS (SQL)", "parentNode": "az", "position": { "x": 0, "y": 0 } },
+                { "id": "user", "type": "Web Browser", "position": { "x": 0, "y": 0 } }
+            ]),
+            json!([]),
+        );
+        let expected = [
+            "    subgraph n_vpc[\"main<br/>(VPC (Network))\"]",
+            "        subgraph n_az[\"Availability Zone\"]",
+            "            n_db[(\"RDBMS (SQL)\")]",
+            "        end",
+            "    end",
+            "    n_user([\"Web Browser\"])",
+        ];
+        let lines: Vec<&str> = out.lines().skip(1).take(expected.len()).collect();
+        assert_eq!(lines, expected);
+        // グループには配色を付けない
+        assert!(out.contains("    class n_db database\n"));
+        assert!(out.contains("    class n_user client\n"));
+        assert!(!out.contains("n_vpc,") && !out.contains(" n_vpc group"));
+    }
+
+    #[test]
+    fn labels_are_escaped_and_dangling_edges_are_dropped() {
+        let out = render_json(
+            json!([
+                { "id": "a", "type": "App Server", "label": "say \"hi\" <b>", "position": { "x": 0, "y": 0 } },
+                { "id": "b", "type": "Worker (Async)", "position": { "x": 0, "y": 0 } }
+            ]),
+            json!([
+                { "source": "a", "target": "b", "label": "<tag>" },
+                { "source": "a", "target": "missing" }
+            ]),
+        );
+        assert!(
+            out.contains("n_a[\"say #quot;hi#quot; #lt;b#gt;<br/>(App Server)\"]"),
+            "{}",
+            out
+        );
+        assert!(out.contains("n_a -->|\"#lt;tag#gt;\"| n_b"), "{}", out);
+        assert!(!out.contains("missing"));
+    }
+}
@@ -0,0 +1,121 @@
+pub mod dot;
+pub mod drawio;
+mod hcl;
+pub mod mermaid;
+pub mod plantuml;
+pub mod structurizr;
+pub mod svg;
+pub mod terraform;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::{ContainmentTree, GroupKind};
+use crate::domain::model::diagram::{Diagram, Edge, Node};
+
+// 設計図を外部の図表形式 (Mermaid 等) に書き出す際の共通処理
+
+pub fn is_group(node: &Node, catalog: &ComponentCatalog) -> bool {
+    GroupKind::from_type(&node.type_label).is_some()
+        || catalog
+            .get(&node.type_label)
+            .is_some_and(|c| c.semantics.group)
+}
+
+// 描画上の入れ子構造
+// 親が存在しない・グループでない・循環している場合はトップレベルに置く
+pub struct LayoutTree<'a> {
+    pub roots: Vec<&'a Node>,
+    children: HashMap<&'a str, Vec<&'a Node>>,
+}
+
+impl<'a> LayoutTree<'a> {
+    pub fn build(diagram: &'a Diagram, catalog: &ComponentCatalog) -> Self {
+        let tree = ContainmentTree::new(diagram);
+        let mut roots = Vec::new();
+        let mut children: HashMap<&str, Vec<&Node>> = HashMap::new();
+        for node in &diagram.nodes {
+            match tree.parent(&node.id) {
+                Some(parent) if is_group(parent, catalog) && !tree.is_in_cycle(&node.id) => {
+                    children.entry(parent.id.as_str()).or_default().push(node)
+                }
+                _ => roots.push(node),
+            }
+        }
+        Self { roots, children }
+    }
+
+    pub fn children(&self, id: &str) -> &[&'a Node] {
+        self.children.get(id).map(|c| c.as_slice()).unwrap_or(&[])
+    }
+}
+
+// 各形式で安全に使える識別子 (英数字とアンダースコアのみ、重複なし)
+pub fn identifiers(diagram: &Diagram) -> HashMap<String, String> {
+    let mut used = HashSet::new();
+    let mut ids = HashMap::new();
+    for node in &diagram.nodes {
+        let clean: String = node
+            .id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let base = format!("n_{}", clean);
+        let mut candidate = base.clone();
+        let mut suffix = 1;
+        while !used.insert(candidate.clone()) {
+            suffix += 1;
+            candidate = format!("{}_{}", base, suffix);
+        }
+        ids.insert(node.id.clone(), candidate);
+    }
+    ids
+}
+
+// ノードに表示する文言 (ラベル・型・台数など)
+pub fn node_lines(node: &Node) -> Vec<String> {
+    let mut lines = Vec::new();
+    match node.label.as_deref() {
+        Some(label) if !label.is_empty() && label != node.type_label => {
+            lines.push(label.to_string());
+            lines.push(format!("({})", node.type_label));
+        }
+        _ => lines.push(node.type_label.clone()),
+    }
+    let properties = node.properties.describe();
+    if !properties.is_empty() {
+        lines.push(properties);
+    }
+    lines
+}
+
+// 接続に表示する文言 (ラベルとプロトコル。通信方式は線の種類で表す)
+pub fn edge_label(edge: &Edge) -> String {
+    let mut parts = Vec::new();
+    if let Some(label) = edge.label.as_deref().filter(|l| !l.is_empty()) {
+        parts.push(label.to_string());
+    }
+    if let Some(protocol) = edge.protocol {
+        parts.push(protocol.as_str().to_string());
+    }
+    parts.join(", ")
+}
+
+// 各形式の書き出しテストで共通に使う設計図
+#[cfg(test)]
+pub fn sample() -> Diagram {
+    serde_json::from_value(serde_json::json!({
+        "nodes": [
+            { "id": "vpc", "type": "VPC (Network)", "position": { "x": 0, "y": 0 } },
+            { "id": "app", "type": "App Server", "label": "API", "position": { "x": 40, "y": 60 },
+              "parentNode": "vpc", "properties": { "replicas": 3 } },
+            { "id": "db", "type": "RDBMS (SQL)", "position": { "x": 240, "y": 60 }, "parentNode": "vpc" },
+            { "id": "user", "type": "Web Browser", "position": { "x": -200, "y": 0 } }
+        ],
+        "edges": [
+            { "id": "e1", "source": "user", "target": "app", "protocol": "HTTPS" },
+            { "id": "e2", "source": "app", "target": "db", "protocol": "SQL", "label": "orders" }
+        ]
+    }))
+    .unwrap()
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use super::{LayoutTree, edge_label, identifiers, is_group, node_lines};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::GroupKind;
+use crate::domain::model::diagram::{CommunicationStyle, Diagram, Node};
+
+// PlantUML の配置図 (deployment diagram) 形式への書き出し
+// グループは入れ子の配置ノード、コンポーネントはカテゴリに応じた要素で表す
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let ids = identifiers(diagram);
+    let layout = LayoutTree::build(diagram, catalog);
+    let mut out = String::from("@startuml\nleft to right direction\n");
+
+    for node in &layout.roots {
+        write_node(&mut out, node, &layout, &ids, catalog, 0);
+    }
+
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (ids.get(&edge.source), ids.get(&edge.target)) else {
+            continue;
+        };
+        let line = match edge.effective_style(diagram) {
+            CommunicationStyle::Async => "..",
+            CommunicationStyle::Stream => "-[bold]-",
+            CommunicationStyle::Sync => "--",
+        };
+        let head = if edge.bidirectional { "<" } else { "" };
+        out.push_str(&format!("{} {}{}> {}", source, head, line, target));
+        let label = edge_label(edge);
+        if !label.is_empty() {
+            out.push_str(&format!(" : {}", escape(&label)));
+        }
+        out.push('\n');
+    }
+    out.push_str("@enduml\n");
+    out
+}
+
+fn write_node(
+    out: &mut String,
+    node: &Node,
+    layout: &LayoutTree,
+    ids: &HashMap<String, String>,
+    catalog: &ComponentCatalog,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let id = &ids[&node.id];
+    let label = node_lines(node)
+        .iter()
+        .map(|l| escape(l))
+        .collect::<Vec<_>>()
+        .join("\\n");
+    let def = catalog.get(&node.type_label);
+    let color = def.map(|d| format!(" {}", d.bg_color)).unwrap_or_default();
+
+    if is_group(node, catalog) {
+        let keyword = match GroupKind::from_type(&node.type_label) {
+            Some(GroupKind::Vpc) => "cloud",
+            Some(GroupKind::SecurityGroup) => "rectangle",
+            _ => "node",
+        };
+        out.push_str(&format!(
+            "{}{} \"{}\" as {} {{\n",
+            indent, keyword, label, id
+        ));
+        for child in layout.children(&node.id) {
+            write_node(out, child, layout, ids, catalog, depth + 1);
+        }
+        out.push_str(&format!("{}}}\n", indent));
+        return;
+    }
+
+    let keyword = match (def.map(|d| d.category.as_str()), node.type_label.as_str()) {
+        (_, "Object Storage") => "storage",
+        (_, "Message Queue") => "queue",
+        (Some("client"), _) => "actor",
+        (Some("traffic"), _) => "boundary",
+        (Some("compute"), _) => "node",
+        (Some("database"), _) => "database",
+        _ => "component",
+    };
+    out.push_str(&format!(
+        "{}{} \"{}\" as {}{}\n",
+        indent, keyword, label, id, color
+    ));
+}
+
+// PlantUML の引用符内では二重引用符をエスケープできないため置き換える
+// 改行は行の区切りとして解釈されるため、PlantUML の改行記法 (\n) にする
+fn escape(text: &str) -> String {
+    text.replace('"', "'")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+
+    #[test]
+    fn labels_cannot_break_out_of_their_line() {
+        let diagram: Diagram = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "id": "a", "type": "App Server", "label": "say \"hi\"\r\nnow", "position": { "x": 0, "y": 0 } },
+                { "id": "b", "type": "RDBMS (SQL)", "position": { "x": 0, "y": 0 } }
+            ],
+            "edges": [
+                { "source": "a", "target": "b", "label": "orders\n@enduml\rx", "protocol": "SQL" }
+            ]
+        }))
+        .unwrap();
+        let out = render(&diagram, &test_catalog());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "@startuml",
+                "left to right direction",
+                "node \"say 'hi'\\nnow\\n(App Server)\" as n_a #fff7ed",
+                "database \"RDBMS (SQL)\" as n_b #ecfdf5",
+                "n_a --> n_b : orders\\n@enduml\\nx, SQL",
+                "@enduml",
+            ]
+        );
+    }
+}
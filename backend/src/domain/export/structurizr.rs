@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{LayoutTree, identifiers, is_group};
+use crate::domain::model::catalog::{ComponentCatalog, Hosting};
+use crate::domain::model::diagram::{CommunicationStyle, Diagram, Node};
+
+// C4 モデル (Structurizr DSL) 形式への書き出し
+// 利用者の端末は person、それ以外のコンポーネントは1つのソフトウェアシステム内の container、
+// グループは配置ビューの deploymentNode として表す
+
+const SYSTEM_ID: &str = "system";
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let ids = identifiers(diagram);
+    let layout = LayoutTree::build(diagram, catalog);
+    let is_person = |node: &Node| {
+        catalog
+            .get(&node.type_label)
+            .is_some_and(|c| c.semantics.hosting == Hosting::Client)
+    };
+    let elements: Vec<&Node> = diagram
+        .nodes
+        .iter()
+        .filter(|n| !is_group(n, catalog))
+        .collect();
+
+    let mut out = String::from("workspace \"Architecture Sandbox\" {\n\n    model {\n");
+    for node in elements.iter().filter(|n| is_person(n)) {
+        out.push_str(&format!(
+            "        {} = person \"{}\" \"{}\"\n",
+            ids[&node.id],
+            escape(&name(node)),
+            escape(&node.type_label)
+        ));
+    }
+
+    out.push_str(&format!(
+        "        {} = softwareSystem \"System\" {{\n",
+        SYSTEM_ID
+    ));
+    for node in elements.iter().filter(|n| !is_person(n)) {
+        out.push_str(&format!(
+            "            {} = container \"{}\" \"{}\" \"{}\" \"{}\"\n",
+            ids[&node.id],
+            escape(&name(node)),
+            escape(&description(node)),
+            escape(&node.type_label),
+            tags(node, catalog).join(",")
+        ));
+    }
+    out.push_str("        }\n\n");
+
+    // グループ自体への接続は C4 の要素間の関係として表せないため除く
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (diagram.node(&edge.source), diagram.node(&edge.target))
+        else {
+            continue;
+        };
+        if is_group(source, catalog) || is_group(target, catalog) {
+            continue;
+        }
+        let label = edge
+            .label
+            .as_deref()
+            .filter(|l| !l.is_empty())
+            .unwrap_or("Uses");
+        let technology = edge.protocol.map(|p| p.as_str()).unwrap_or("");
+        let tag = match edge.effective_style(diagram) {
+            CommunicationStyle::Async => " \"Async\"",
+            _ => "",
+        };
+        out.push_str(&format!(
+            "        {} -> {} \"{}\" \"{}\"{}\n",
+            ids[&source.id],
+            ids[&target.id],
+            escape(label),
+            escape(technology),
+            tag
+        ));
+    }
+
+    // 配置ビュー: グループを deploymentNode、台数を instances で表す
+    out.push_str("\n        production = deploymentEnvironment \"Production\" {\n");
+    let ungrouped: Vec<&Node> = layout
+        .roots
+        .iter()
+        .copied()
+        .filter(|n| !is_group(n, catalog) && !is_person(n))
+        .collect();
+    for node in layout.roots.iter().filter(|n| is_group(n, catalog)) {
+        write_deployment(&mut out, node, &layout, &ids, catalog, &is_person, 3);
+    }
+    if !ungrouped.is_empty() {
+        out.push_str("            deploymentNode \"Cloud\" {\n");
+        for node in ungrouped {
+            write_instance(&mut out, node, &ids, 4);
+        }
+        out.push_str("            }\n");
+    }
+    out.push_str("        }\n    }\n\n");
+
+    // ビューとスタイル (カテゴリの配色は architecture_defs.json から)
+    out.push_str("    views {\n");
+    out.push_str(&format!(
+        "        container {} \"Containers\" {{\n            include *\n            autoLayout lr\n        }}\n",
+        SYSTEM_ID
+    ));
+    out.push_str(&format!(
+        "        deployment {} production \"Deployment\" {{\n            include *\n            autoLayout lr\n        }}\n",
+        SYSTEM_ID
+    ));
+    out.push_str("        styles {\n");
+    let mut categories: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+    for def in catalog.components().filter(|d| !d.semantics.group) {
+        categories
+            .entry(def.category.as_str())
+            .or_insert((def.bg_color.as_str(), def.color.as_str()));
+    }
+    for (category, (background, stroke)) in &categories {
+        out.push_str(&format!(
+            "            element \"{}\" {{\n                background {}\n                stroke {}\n            }}\n",
+            category, background, stroke
+        ));
+    }
+    out.push_str("            element \"Person\" {\n                shape Person\n            }\n");
+    out.push_str(
+        "            element \"Database\" {\n                shape Cylinder\n            }\n",
+    );
+    out.push_str("            element \"Queue\" {\n                shape Pipe\n            }\n");
+    out.push_str(
+        "            relationship \"Async\" {\n                dashed true\n            }\n",
+    );
+    out.push_str("        }\n    }\n}\n");
+    out
+}
+
+fn write_deployment(
+    out: &mut String,
+    node: &Node,
+    layout: &LayoutTree,
+    ids: &HashMap<String, String>,
+    catalog: &ComponentCatalog,
+    is_person: &dyn Fn(&Node) -> bool,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+    out.push_str(&format!(
+        "{}deploymentNode \"{}\" \"\" \"{}\" {{\n",
+        indent,
+        escape(&name(node)),
+        escape(&node.type_label)
+    ));
+    for child in layout.children(&node.id) {
+        if is_group(child, catalog) {
+            write_deployment(out, child, layout, ids, catalog, is_person, depth + 1);
+        } else if !is_person(child) {
+            write_instance(out, child, ids, depth + 1);
+        }
+    }
+    out.push_str(&format!("{}}}\n", indent));
+}
+
+fn write_instance(out: &mut String, node: &Node, ids: &HashMap<String, String>, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let count = node.instance_count();
+    if count > 1 {
+        // 複数台構成は、台数つきの deploymentNode で包む
+        out.push_str(&format!(
+            "{}deploymentNode \"{}\" \"\" \"\" \"\" {} {{\n{}    containerInstance {}\n{}}}\n",
+            indent,
+            escape(&name(node)),
+            count,
+            indent,
+            ids[&node.id],
+            indent
+        ));
+    } else {
+        out.push_str(&format!("{}containerInstance {}\n", indent, ids[&node.id]));
+    }
+}
+
+fn name(node: &Node) -> String {
+    match node.label.as_deref() {
+        Some(label) if !label.is_empty() => label.to_string(),
+        _ => node.type_label.clone(),
+    }
+}
+
+fn description(node: &Node) -> String {
+    let mut parts = Vec::new();
+    if let Some(desc) = node.description.as_deref().filter(|d| !d.is_empty()) {
+        parts.push(desc.to_string());
+    }
+    let properties = node.properties.describe();
+    if !properties.is_empty() {
+        parts.push(properties);
+    }
+    parts.join(" / ")
+}
+
+// C4 のタグ (カテゴリ名と、形状を変えるための種別)
+fn tags(node: &Node, catalog: &ComponentCatalog) -> Vec<String> {
+    let mut tags = Vec::new();
+    if let Some(def) = catalog.get(&node.type_label) {
+        tags.push(def.category.clone());
+        if def.category == "database" {
+            tags.push("Database".to_string());
+        }
+    }
+    if node.type_label == "Message Queue" {
+        tags.push("Queue".to_string());
+    }
+    tags
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+}
@@ -0,0 +1,116 @@
+use layout::backends::svg::SVGWriter;
+use layout::core::base::Orientation;
+use layout::core::color::Color;
+use layout::core::style::{LineStyleKind, StyleAttr};
+use layout::std_shapes::render::get_shape_size;
+use layout::std_shapes::shapes::{Arrow, Element, LineEndKind, ShapeKind};
+use layout::topo::layout::VisualGraph;
+use std::collections::HashMap;
+
+use super::{edge_label, is_group, node_lines};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::ContainmentTree;
+use crate::domain::model::diagram::{CommunicationStyle, Diagram};
+
+// 外部のバイナリ (Graphviz) を使わずに、サーバー側で SVG 画像を描画する
+// 描画ライブラリが入れ子のクラスタに対応していないため、所属グループはノードの注記で表す
+
+const FONT_SIZE: usize = 12;
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    let tree = ContainmentTree::new(diagram);
+    let orientation = Orientation::LeftToRight;
+    let mut graph = VisualGraph::new(orientation);
+    let mut handles = HashMap::new();
+
+    for node in diagram.nodes.iter().filter(|n| !is_group(n, catalog)) {
+        let mut lines = node_lines(node);
+        if let Some(group) = tree.ancestors(&node.id).first() {
+            lines.push(format!("@ {}", group.display_name()));
+        }
+        let shape = ShapeKind::new_box(&lines.join("\n"));
+
+        let def = catalog.get(&node.type_label);
+        let line_color = def.map_or(Color::fast("black"), |d| Color::fast(&d.color));
+        let fill_color = def.map_or(Color::fast("white"), |d| Color::fast(&d.bg_color));
+        let look = StyleAttr::new(line_color, 1, Some(fill_color), 8, FONT_SIZE);
+        // 描画ライブラリは縦横を入れ替えて寸法を求めるため、向きを反転して渡す
+        let size = get_shape_size(orientation.flip(), &shape, FONT_SIZE, false);
+        let element = Element::create(shape, look, orientation.flip(), size);
+        handles.insert(node.id.as_str(), graph.add_node(element));
+    }
+
+    for edge in &diagram.edges {
+        let (Some(source), Some(target)) = (
+            handles.get(edge.source.as_str()),
+            handles.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        // 自己ループは描画できないため除く
+        if source == target {
+            continue;
+        }
+        let line_style = match edge.effective_style(diagram) {
+            CommunicationStyle::Async => LineStyleKind::Dashed,
+            _ => LineStyleKind::Normal,
+        };
+        let start = if edge.bidirectional {
+            LineEndKind::Arrow
+        } else {
+            LineEndKind::None
+        };
+        let look = StyleAttr::new(Color::fast("#4b5563"), 1, None, 0, FONT_SIZE - 2);
+        let arrow = Arrow::new(
+            start,
+            LineEndKind::Arrow,
+            line_style,
+            &edge_label(edge),
+            &look,
+            &None,
+            &None,
+        );
+        graph.add_edge(arrow, *source, *target);
+    }
+
+    let mut svg = SVGWriter::new();
+    if graph.num_nodes() > 0 {
+        graph.do_it(false, false, false, &mut svg);
+    }
+    sort_style_rules(svg.finalize())
+}
+
+// 描画ライブラリは文字サイズごとのクラスを HashMap で管理しており、出力順が毎回変わる
+// 同じ設計から同じ画像が得られるよう、<style> 内の定義を並べ替える
+fn sort_style_rules(svg: String) -> String {
+    let (Some(start), Some(end)) = (svg.find("<style>\n"), svg.find("</style>")) else {
+        return svg;
+    };
+    let body = start + "<style>\n".len();
+    if end < body {
+        return svg;
+    }
+    let mut rules: Vec<&str> = svg[body..end].lines().collect();
+    rules.sort_unstable();
+    let mut sorted = String::with_capacity(svg.len());
+    sorted.push_str(&svg[..body]);
+    for rule in rules {
+        sorted.push_str(rule);
+        sorted.push('\n');
+    }
+    sorted.push_str(&svg[end..]);
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::export;
+    use crate::domain::model::catalog::test_catalog;
+
+    #[test]
+    fn sample_matches_golden_output() {
+        let out = render(&export::sample(), &test_catalog());
+        assert_eq!(out, include_str!("fixtures/sample.svg"));
+    }
+}
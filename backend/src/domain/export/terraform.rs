@@ -0,0 +1,1603 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::hcl::{Block, Value, quote, template};
+use super::is_group;
+use crate::domain::analysis::exposure;
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::{ContainmentTree, GroupKind};
+use crate::domain::model::diagram::{Diagram, Node};
+
+// 設計図から AWS の Terraform の雛形を生成する (クラウドへの問い合わせは行わない)
+// グループは VPC / Subnet / Security Group、コンポーネントは対応するリソースに置き換え、
+// 接続はセキュリティグループのルールやターゲットグループ、購読などの結び付けとして表す
+
+// 1つの設計図あたり1つとする既定値 (生成後に利用者が書き換える前提)
+const DEFAULT_REGION: &str = "ap-northeast-1";
+const DOMAIN_NAME: &str = "example.com";
+
+// 独自のセキュリティグループを持たせるコンポーネント (VPC 内に置かれた場合)
+const NEEDS_SECURITY_GROUP: [&str; 9] = [
+    "Load Balancer",
+    "Web Server",
+    "App Server",
+    "Worker (Async)",
+    "Function (Serverless)",
+    "RDBMS (SQL)",
+    "NoSQL (Doc)",
+    "NoSQL (Graph)",
+    "Distributed Cache",
+];
+
+// ロードバランサーがターゲットグループを作って転送する接続先
+const LB_TARGETS: [&str; 3] = ["Web Server", "App Server", "Function (Serverless)"];
+
+// 接続を受け付けるポート (セキュリティグループのルールに使う)
+fn port(type_label: &str) -> Option<u16> {
+    match type_label {
+        "Load Balancer" | "Web Server" => Some(80),
+        "App Server" => Some(8080),
+        "RDBMS (SQL)" => Some(5432),
+        "NoSQL (Doc)" => Some(27017),
+        "NoSQL (Graph)" => Some(8182),
+        "Distributed Cache" => Some(6379),
+        _ => None,
+    }
+}
+
+pub fn render(diagram: &Diagram, catalog: &ComponentCatalog) -> String {
+    Generator::new(diagram, catalog).render()
+}
+
+struct Generator<'a> {
+    diagram: &'a Diagram,
+    catalog: &'a ComponentCatalog,
+    tree: ContainmentTree<'a>,
+    names: HashMap<&'a str, String>,
+    public_subnets: HashSet<String>,
+    // 生成中に必要になった共通の定義
+    needs_db_password: bool,
+    needs_zones: bool,
+    needs_ami: bool,
+    needs_default_vpc: bool,
+    // 対応するリソースがなく、生成しなかったコンポーネント
+    unsupported: BTreeSet<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(diagram: &'a Diagram, catalog: &'a ComponentCatalog) -> Self {
+        let tree = ContainmentTree::new(diagram);
+        // 公開サブネット: 利用者から直接届くものに加え、VPC の外 (CDN 等) から接続されるものも含む
+        let mut public_subnets: HashSet<String> = exposure::analyze(diagram, catalog)
+            .public_subnets
+            .into_iter()
+            .collect();
+        for edge in &diagram.edges {
+            if tree.enclosing(&edge.source, GroupKind::Vpc).is_some() {
+                continue;
+            }
+            if let Some(subnet) = tree.enclosing(&edge.target, GroupKind::Subnet) {
+                public_subnets.insert(subnet.id.clone());
+            }
+        }
+        Self {
+            diagram,
+            catalog,
+            tree,
+            names: resource_names(diagram),
+            public_subnets,
+            needs_db_password: false,
+            needs_zones: false,
+            needs_ami: false,
+            needs_default_vpc: false,
+            unsupported: BTreeSet::new(),
+        }
+    }
+
+    fn render(mut self) -> String {
+        let network = self.network();
+        let security = self.security_groups();
+        let mut components: Vec<(String, Vec<Block>)> = Vec::new();
+        for node in &self.diagram.nodes {
+            if is_group(node, self.catalog) {
+                continue;
+            }
+            let blocks = self.component(node);
+            if !blocks.is_empty() {
+                components.push((format!("{} [{}]", node.display_name(), node.id), blocks));
+            }
+        }
+        let lambda_role = self.lambda_role();
+
+        let mut out = String::from(
+            "# 設計図から生成した Terraform の雛形です。\n# 名前・サイズ・エンジンのバージョン等は要件に合わせて調整してください。\n",
+        );
+        if !self.unsupported.is_empty() {
+            let list: Vec<&str> = self.unsupported.iter().map(|s| s.as_str()).collect();
+            out.push_str(&format!(
+                "# 対応するリソースがないため生成しなかったもの: {}\n",
+                list.join(", ")
+            ));
+        }
+        out.push('\n');
+        for block in self.preamble() {
+            block.write(&mut out, 0);
+            out.push('\n');
+        }
+        write_section(&mut out, "ネットワーク", &network);
+        write_section(&mut out, "セキュリティグループ", &security);
+        write_section(&mut out, "IAM", &lambda_role);
+        for (title, blocks) in &components {
+            write_section(&mut out, title, blocks);
+        }
+        // 末尾の空行は1つにする
+        while out.ends_with("\n\n") {
+            out.pop();
+        }
+        out
+    }
+
+    // --- 共通の定義 ---
+
+    fn preamble(&self) -> Vec<Block> {
+        let mut blocks = vec![
+            Block::new("terraform", &[]).block(Block::new("required_providers", &[]).attr(
+                "aws",
+                Value::object(vec![
+                    ("source", Value::string("hashicorp/aws")),
+                    ("version", Value::string("~> 5.0")),
+                ]),
+            )),
+            Block::new("provider", &["aws"]).attr("region", Value::reference("var.region")),
+            Block::new("variable", &["project_name"])
+                .attr("type", Value::reference("string"))
+                .attr("default", Value::string("sandbox")),
+            Block::new("variable", &["region"])
+                .attr("type", Value::reference("string"))
+                .attr("default", Value::string(DEFAULT_REGION)),
+        ];
+        if self.needs_db_password {
+            blocks.push(
+                Block::new("variable", &["db_password"])
+                    .attr("type", Value::reference("string"))
+                    .attr("sensitive", Value::reference("true")),
+            );
+        }
+        if self.needs_zones {
+            blocks.push(
+                Block::new("data", &["aws_availability_zones", "available"])
+                    .attr("state", Value::string("available")),
+            );
+        }
+        if self.needs_ami {
+            blocks.push(
+                Block::new("data", &["aws_ami", "amazon_linux"])
+                    .attr("most_recent", Value::reference("true"))
+                    .attr("owners", Value::strings(&["amazon"]))
+                    .blank()
+                    .block(
+                        Block::new("filter", &[])
+                            .attr("name", Value::string("name"))
+                            .attr("values", Value::strings(&["al2023-ami-*-x86_64"])),
+                    ),
+            );
+        }
+        // VPC の外に置かれたロードバランサーは、既定の VPC とそのサブネットを使う
+        if self.needs_default_vpc {
+            blocks.push(
+                Block::new("data", &["aws_vpc", "default"])
+                    .attr("default", Value::reference("true")),
+            );
+            blocks.push(
+                Block::new("data", &["aws_subnets", "default"]).block(
+                    Block::new("filter", &[])
+                        .attr("name", Value::string("vpc-id"))
+                        .attr(
+                            "values",
+                            Value::references(vec!["data.aws_vpc.default.id".to_string()]),
+                        ),
+                ),
+            );
+        }
+        blocks
+    }
+
+    // --- ネットワーク (VPC / Subnet / インターネットゲートウェイ) ---
+
+    fn network(&mut self) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let vpcs = self.groups(GroupKind::Vpc);
+        let zones: Vec<&str> = self
+            .groups(GroupKind::AvailabilityZone)
+            .iter()
+            .map(|z| z.id.as_str())
+            .collect();
+
+        for (i, vpc) in vpcs.iter().enumerate() {
+            let name = self.name(vpc);
+            blocks.push(
+                Block::new("resource", &["aws_vpc", &name])
+                    .attr("cidr_block", Value::string(&format!("10.{}.0.0/16", i)))
+                    .attr("enable_dns_hostnames", Value::reference("true"))
+                    .attr("tags", self.tags(vpc)),
+            );
+
+            let subnets = self.subnets_in(vpc);
+            let public: Vec<&Node> = subnets
+                .iter()
+                .copied()
+                .filter(|s| self.public_subnets.contains(&s.id))
+                .collect();
+
+            for (j, subnet) in subnets.iter().enumerate() {
+                let zone = self
+                    .tree
+                    .zone_of(&subnet.id)
+                    .and_then(|z| zones.iter().position(|id| *id == z.id));
+                if zone.is_some() {
+                    self.needs_zones = true;
+                }
+                blocks.push(
+                    Block::new("resource", &["aws_subnet", &self.name(subnet)])
+                        .attr("vpc_id", Value::reference(format!("aws_vpc.{}.id", name)))
+                        .attr(
+                            "cidr_block",
+                            Value::reference(format!(
+                                "cidrsubnet(aws_vpc.{}.cidr_block, 8, {})",
+                                name, j
+                            )),
+                        )
+                        .attr_if(zone.is_some(), "availability_zone", || {
+                            Value::reference(format!(
+                                "data.aws_availability_zones.available.names[{}]",
+                                zone.unwrap_or_default()
+                            ))
+                        })
+                        .attr_if(
+                            self.public_subnets.contains(&subnet.id),
+                            "map_public_ip_on_launch",
+                            || Value::reference("true"),
+                        )
+                        .attr("tags", self.tags(subnet)),
+                );
+            }
+
+            // インターネットから到達できるサブネットには、インターネットゲートウェイへの経路を作る
+            if public.is_empty() {
+                continue;
+            }
+            blocks.push(
+                Block::new("resource", &["aws_internet_gateway", &name])
+                    .attr("vpc_id", Value::reference(format!("aws_vpc.{}.id", name))),
+            );
+            let route_table = format!("{}_public", name);
+            blocks.push(
+                Block::new("resource", &["aws_route_table", &route_table])
+                    .attr("vpc_id", Value::reference(format!("aws_vpc.{}.id", name)))
+                    .blank()
+                    .block(
+                        Block::new("route", &[])
+                            .attr("cidr_block", Value::string("0.0.0.0/0"))
+                            .attr(
+                                "gateway_id",
+                                Value::reference(format!("aws_internet_gateway.{}.id", name)),
+                            ),
+                    ),
+            );
+            for subnet in public {
+                let subnet_name = self.name(subnet);
+                blocks.push(
+                    Block::new("resource", &["aws_route_table_association", &subnet_name])
+                        .attr(
+                            "subnet_id",
+                            Value::reference(format!("aws_subnet.{}.id", subnet_name)),
+                        )
+                        .attr(
+                            "route_table_id",
+                            Value::reference(format!("aws_route_table.{}.id", route_table)),
+                        ),
+                );
+            }
+        }
+        blocks
+    }
+
+    // --- セキュリティグループと、接続ごとの通信許可 ---
+
+    fn security_groups(&mut self) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut created = BTreeSet::new();
+        for node in &self.diagram.nodes {
+            let Some(sg) = self.security_group(node) else {
+                continue;
+            };
+            if !created.insert(sg.clone()) {
+                continue;
+            }
+            // Security Group ノード、または所属する VPC
+            let owner = self
+                .tree
+                .enclosing(&node.id, GroupKind::SecurityGroup)
+                .unwrap_or(node);
+            let vpc = self.vpc_of(owner).map(|v| self.name(v));
+            blocks.push(
+                Block::new("resource", &["aws_security_group", &sg])
+                    .attr("name", self.aws_name(&sg))
+                    .attr_if(vpc.is_some(), "vpc_id", || {
+                        Value::reference(format!("aws_vpc.{}.id", vpc.clone().unwrap_or_default()))
+                    }),
+            );
+            blocks.push(
+                Block::new("resource", &["aws_vpc_security_group_egress_rule", &sg])
+                    .attr(
+                        "security_group_id",
+                        Value::reference(format!("aws_security_group.{}.id", sg)),
+                    )
+                    .attr("cidr_ipv4", Value::string("0.0.0.0/0"))
+                    .attr("ip_protocol", Value::string("-1")),
+            );
+        }
+
+        let mut rules = BTreeSet::new();
+        for edge in &self.diagram.edges {
+            let (Some(source), Some(target)) = (
+                self.diagram.node(&edge.source),
+                self.diagram.node(&edge.target),
+            ) else {
+                continue;
+            };
+            let (Some(target_sg), Some(port)) =
+                (self.security_group(target), port(&target.type_label))
+            else {
+                continue;
+            };
+            let rule = format!("{}_to_{}", self.name(source), self.name(target));
+            if !rules.insert(rule.clone()) {
+                continue;
+            }
+            let mut block = Block::new("resource", &["aws_vpc_security_group_ingress_rule", &rule])
+                .attr(
+                    "security_group_id",
+                    Value::reference(format!("aws_security_group.{}.id", target_sg)),
+                );
+            // VPC の外 (利用者や CDN 等) からの接続は、送信元を絞れない
+            block = match (self.security_group(source), self.vpc_of(source)) {
+                (Some(source_sg), _) => block.attr(
+                    "referenced_security_group_id",
+                    Value::reference(format!("aws_security_group.{}.id", source_sg)),
+                ),
+                (None, Some(vpc)) => block.attr(
+                    "cidr_ipv4",
+                    Value::reference(format!("aws_vpc.{}.cidr_block", self.name(vpc))),
+                ),
+                (None, None) => block.attr("cidr_ipv4", Value::string("0.0.0.0/0")),
+            };
+            blocks.push(
+                block
+                    .attr("ip_protocol", Value::string("tcp"))
+                    .attr("from_port", Value::reference(port.to_string()))
+                    .attr("to_port", Value::reference(port.to_string())),
+            );
+        }
+        blocks
+    }
+
+    // Lambda 関数の実行ロール (関数がある場合のみ)
+    fn lambda_role(&self) -> Vec<Block> {
+        let functions: Vec<&Node> = self.of_type("Function (Serverless)");
+        if functions.is_empty() {
+            return Vec::new();
+        }
+        let in_vpc = functions.iter().any(|f| self.vpc_of(f).is_some());
+        let policy = if in_vpc {
+            "service-role/AWSLambdaVPCAccessExecutionRole"
+        } else {
+            "service-role/AWSLambdaBasicExecutionRole"
+        };
+        vec![
+            Block::new("resource", &["aws_iam_role", "lambda_exec"])
+                .attr("name", self.aws_name("lambda-exec"))
+                .attr(
+                    "assume_role_policy",
+                    Value::Call(
+                        "jsonencode",
+                        Box::new(Value::object(vec![
+                            ("Version", Value::string("2012-10-17")),
+                            (
+                                "Statement",
+                                Value::List(vec![Value::object(vec![
+                                    ("Action", Value::string("sts:AssumeRole")),
+                                    ("Effect", Value::string("Allow")),
+                                    (
+                                        "Principal",
+                                        Value::object(vec![(
+                                            "Service",
+                                            Value::string("lambda.amazonaws.com"),
+                                        )]),
+                                    ),
+                                ])]),
+                            ),
+                        ])),
+                    ),
+                ),
+            Block::new(
+                "resource",
+                &["aws_iam_role_policy_attachment", "lambda_exec"],
+            )
+            .attr("role", Value::reference("aws_iam_role.lambda_exec.name"))
+            .attr(
+                "policy_arn",
+                Value::string(&format!("arn:aws:iam::aws:policy/{}", policy)),
+            ),
+        ]
+    }
+
+    // --- コンポーネント ---
+
+    fn component(&mut self, node: &'a Node) -> Vec<Block> {
+        let name = self.name(node);
+        match node.type_label.as_str() {
+            "Load Balancer" => self.load_balancer(node),
+            "Web Server" | "App Server" | "Worker (Async)" => self.compute(node),
+            "Function (Serverless)" => self.function(node),
+            "API Gateway" => self.api_gateway(node),
+            "CDN (CloudFront)" => self.cdn(node),
+            "DNS (Route53)" => self.dns(node),
+            "WAF (Firewall)" => self.waf(node),
+            "Batch Job" => vec![
+                Block::new("resource", &["aws_batch_job_definition", &name])
+                    .attr("name", self.aws_name(&name))
+                    .attr("type", Value::string("container"))
+                    .attr(
+                        "container_properties",
+                        Value::Call(
+                            "jsonencode",
+                            Box::new(Value::object(vec![
+                                (
+                                    "image",
+                                    Value::string("public.ecr.aws/amazonlinux/amazonlinux:latest"),
+                                ),
+                                ("command", Value::strings(&["echo", "hello"])),
+                                (
+                                    "resourceRequirements",
+                                    Value::List(vec![
+                                        Value::object(vec![
+                                            ("type", Value::string("VCPU")),
+                                            ("value", Value::string("1")),
+                                        ]),
+                                        Value::object(vec![
+                                            ("type", Value::string("MEMORY")),
+                                            ("value", Value::string("2048")),
+                                        ]),
+                                    ]),
+                                ),
+                            ])),
+                        ),
+                    ),
+            ],
+            "RDBMS (SQL)" => self.rdbms(node),
+            "NoSQL (KV)" => vec![
+                Block::new("resource", &["aws_dynamodb_table", &name])
+                    .attr("name", self.aws_name(&name))
+                    .attr("billing_mode", Value::string("PAY_PER_REQUEST"))
+                    .attr("hash_key", Value::string("id"))
+                    .blank()
+                    .block(
+                        Block::new("attribute", &[])
+                            .attr("name", Value::string("id"))
+                            .attr("type", Value::string("S")),
+                    ),
+            ],
+            "NoSQL (Doc)" => {
+                self.needs_db_password = true;
+                let mut blocks = self.subnet_group(node, "aws_docdb_subnet_group");
+                blocks.push(
+                    Block::new("resource", &["aws_docdb_cluster", &name])
+                        .attr("cluster_identifier", self.aws_name(&name))
+                        .attr("engine", Value::string("docdb"))
+                        .attr("master_username", Value::string("app"))
+                        .attr("master_password", Value::reference("var.db_password"))
+                        .attr_if(!blocks.is_empty(), "db_subnet_group_name", || {
+                            Value::reference(format!("aws_docdb_subnet_group.{}.name", name))
+                        })
+                        .attr_if(
+                            self.security_group(node).is_some(),
+                            "vpc_security_group_ids",
+                            || self.security_group_ids(node),
+                        )
+                        .attr("skip_final_snapshot", Value::reference("true")),
+                );
+                blocks.push(
+                    Block::new("resource", &["aws_docdb_cluster_instance", &name])
+                        .attr("count", Value::reference(node.instance_count().to_string()))
+                        .attr(
+                            "identifier",
+                            template(&format!(
+                                "${{var.project_name}}-{}-${{count.index}}",
+                                dashed(&name)
+                            )),
+                        )
+                        .attr(
+                            "cluster_identifier",
+                            Value::reference(format!("aws_docdb_cluster.{}.id", name)),
+                        )
+                        .attr("instance_class", self.instance_class(node, "db.t3.medium")),
+                );
+                blocks
+            }
+            "NoSQL (Graph)" => {
+                let mut blocks = self.subnet_group(node, "aws_neptune_subnet_group");
+                blocks.push(
+                    Block::new("resource", &["aws_neptune_cluster", &name])
+                        .attr("cluster_identifier", self.aws_name(&name))
+                        .attr("engine", Value::string("neptune"))
+                        .attr_if(!blocks.is_empty(), "neptune_subnet_group_name", || {
+                            Value::reference(format!("aws_neptune_subnet_group.{}.name", name))
+                        })
+                        .attr_if(
+                            self.security_group(node).is_some(),
+                            "vpc_security_group_ids",
+                            || self.security_group_ids(node),
+                        )
+                        .attr("skip_final_snapshot", Value::reference("true")),
+                );
+                blocks.push(
+                    Block::new("resource", &["aws_neptune_cluster_instance", &name])
+                        .attr("count", Value::reference(node.instance_count().to_string()))
+                        .attr(
+                            "cluster_identifier",
+                            Value::reference(format!("aws_neptune_cluster.{}.id", name)),
+                        )
+                        .attr("instance_class", self.instance_class(node, "db.t3.medium")),
+                );
+                blocks
+            }
+            "Object Storage" => vec![
+                Block::new("resource", &["aws_s3_bucket", &name])
+                    .attr("bucket", self.aws_name(&name)),
+            ],
+            "Search Engine" => vec![
+                Block::new("resource", &["aws_opensearch_domain", &name])
+                    .attr("domain_name", self.aws_name(&name))
+                    .attr("engine_version", Value::string("OpenSearch_2.13"))
+                    .blank()
+                    .block(
+                        Block::new("cluster_config", &[])
+                            .attr(
+                                "instance_type",
+                                self.instance_class(node, "t3.small.search"),
+                            )
+                            .attr(
+                                "instance_count",
+                                Value::reference(node.instance_count().to_string()),
+                            ),
+                    )
+                    .blank()
+                    .block(
+                        Block::new("ebs_options", &[])
+                            .attr("ebs_enabled", Value::reference("true"))
+                            .attr("volume_size", Value::reference("10")),
+                    ),
+            ],
+            "Distributed Cache" => {
+                let mut blocks = self.subnet_group(node, "aws_elasticache_subnet_group");
+                blocks.push(
+                    Block::new("resource", &["aws_elasticache_cluster", &name])
+                        .attr("cluster_id", self.aws_name(&name))
+                        .attr("engine", Value::string("redis"))
+                        .attr("node_type", self.instance_class(node, "cache.t3.micro"))
+                        .attr("num_cache_nodes", Value::reference("1"))
+                        .attr("parameter_group_name", Value::string("default.redis7"))
+                        .attr("port", Value::reference("6379"))
+                        .attr_if(!blocks.is_empty(), "subnet_group_name", || {
+                            Value::reference(format!("aws_elasticache_subnet_group.{}.name", name))
+                        })
+                        .attr_if(
+                            self.security_group(node).is_some(),
+                            "security_group_ids",
+                            || self.security_group_ids(node),
+                        ),
+                );
+                blocks
+            }
+            "Message Queue" => {
+                let mut blocks = vec![
+                    Block::new("resource", &["aws_sqs_queue", &name])
+                        .attr("name", self.aws_name(&name)),
+                ];
+                // キューを読み出す Lambda 関数
+                for target in self.targets(node, &["Function (Serverless)"]) {
+                    let target_name = self.name(target);
+                    blocks.push(
+                        Block::new(
+                            "resource",
+                            &[
+                                "aws_lambda_event_source_mapping",
+                                &format!("{}_to_{}", name, target_name),
+                            ],
+                        )
+                        .attr(
+                            "event_source_arn",
+                            Value::reference(format!("aws_sqs_queue.{}.arn", name)),
+                        )
+                        .attr(
+                            "function_name",
+                            Value::reference(format!("aws_lambda_function.{}.arn", target_name)),
+                        ),
+                    );
+                }
+                blocks
+            }
+            "Pub/Sub" | "Alert Manager" => {
+                let mut blocks = vec![
+                    Block::new("resource", &["aws_sns_topic", &name])
+                        .attr("name", self.aws_name(&name)),
+                ];
+                for target in self.targets(node, &["Message Queue", "Function (Serverless)"]) {
+                    let target_name = self.name(target);
+                    let (protocol, endpoint) = match target.type_label.as_str() {
+                        "Message Queue" => ("sqs", format!("aws_sqs_queue.{}.arn", target_name)),
+                        _ => ("lambda", format!("aws_lambda_function.{}.arn", target_name)),
+                    };
+                    blocks.push(
+                        Block::new(
+                            "resource",
+                            &[
+                                "aws_sns_topic_subscription",
+                                &format!("{}_to_{}", name, target_name),
+                            ],
+                        )
+                        .attr(
+                            "topic_arn",
+                            Value::reference(format!("aws_sns_topic.{}.arn", name)),
+                        )
+                        .attr("protocol", Value::string(protocol))
+                        .attr("endpoint", Value::reference(endpoint)),
+                    );
+                }
+                blocks
+            }
+            "Event Bus" => {
+                let mut blocks = vec![
+                    Block::new("resource", &["aws_cloudwatch_event_bus", &name])
+                        .attr("name", self.aws_name(&name)),
+                ];
+                let targets =
+                    self.targets(node, &["Message Queue", "Pub/Sub", "Function (Serverless)"]);
+                if !targets.is_empty() {
+                    blocks.push(
+                        Block::new("resource", &["aws_cloudwatch_event_rule", &name])
+                            .attr("name", self.aws_name(&name))
+                            .attr(
+                                "event_bus_name",
+                                Value::reference(format!("aws_cloudwatch_event_bus.{}.name", name)),
+                            )
+                            .attr(
+                                "event_pattern",
+                                Value::Call(
+                                    "jsonencode",
+                                    Box::new(Value::object(vec![(
+                                        "source",
+                                        Value::strings(&["app"]),
+                                    )])),
+                                ),
+                            ),
+                    );
+                }
+                for target in targets {
+                    let target_name = self.name(target);
+                    blocks.push(
+                        Block::new(
+                            "resource",
+                            &[
+                                "aws_cloudwatch_event_target",
+                                &format!("{}_to_{}", name, target_name),
+                            ],
+                        )
+                        .attr(
+                            "rule",
+                            Value::reference(format!("aws_cloudwatch_event_rule.{}.name", name)),
+                        )
+                        .attr(
+                            "event_bus_name",
+                            Value::reference(format!("aws_cloudwatch_event_bus.{}.name", name)),
+                        )
+                        .attr("arn", Value::reference(self.arn(target))),
+                    );
+                }
+                blocks
+            }
+            "Log Aggregator" => vec![
+                Block::new("resource", &["aws_cloudwatch_log_group", &name])
+                    .attr(
+                        "name",
+                        template(&format!("/${{var.project_name}}/{}", dashed(&name))),
+                    )
+                    .attr("retention_in_days", Value::reference("30")),
+            ],
+            "Health Checker" => {
+                let fqdn = match self.targets(node, &["Load Balancer"]).first() {
+                    Some(lb) => Value::reference(format!("aws_lb.{}.dns_name", self.name(lb))),
+                    None => Value::string(DOMAIN_NAME),
+                };
+                vec![
+                    Block::new("resource", &["aws_route53_health_check", &name])
+                        .attr("fqdn", fqdn)
+                        .attr("port", Value::reference("80"))
+                        .attr("type", Value::string("HTTP"))
+                        .attr("resource_path", Value::string("/health"))
+                        .attr("failure_threshold", Value::reference("3"))
+                        .attr("request_interval", Value::reference("30")),
+                ]
+            }
+            _ => {
+                // 利用者の端末は AWS のリソースではない
+                let is_client = self
+                    .catalog
+                    .get(&node.type_label)
+                    .is_some_and(|c| c.category == "client");
+                if !is_client {
+                    self.unsupported.insert(node.type_label.clone());
+                }
+                Vec::new()
+            }
+        }
+    }
+
+    fn load_balancer(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        let public = self
+            .tree
+            .enclosing(&node.id, GroupKind::Subnet)
+            .is_some_and(|s| self.public_subnets.contains(&s.id))
+            || self.vpc_of(node).is_none();
+        // ALB は複数AZのサブネットに置く (公開なら公開サブネット、そうでなければ非公開サブネット)
+        // VPC の外にある場合は、既定の VPC のサブネットに置く
+        let (subnets, vpc_id) = match self.vpc_of(node) {
+            Some(vpc) => (
+                Value::references(
+                    self.subnets_in(vpc)
+                        .into_iter()
+                        .filter(|s| self.public_subnets.contains(&s.id) == public)
+                        .map(|s| format!("aws_subnet.{}.id", self.name(s)))
+                        .collect(),
+                ),
+                format!("aws_vpc.{}.id", self.name(vpc)),
+            ),
+            None => {
+                self.needs_default_vpc = true;
+                (
+                    Value::reference("data.aws_subnets.default.ids"),
+                    "data.aws_vpc.default.id".to_string(),
+                )
+            }
+        };
+        let mut blocks = vec![
+            Block::new("resource", &["aws_lb", &name])
+                .attr("name", self.aws_name(&name))
+                .attr("internal", Value::reference((!public).to_string()))
+                .attr("load_balancer_type", Value::string("application"))
+                .attr_if(
+                    self.security_group(node).is_some(),
+                    "security_groups",
+                    || self.security_group_ids(node),
+                )
+                .attr("subnets", subnets),
+        ];
+
+        let targets = self.targets(node, &LB_TARGETS);
+        for target in &targets {
+            let group = format!("{}_{}", name, self.name(target));
+            let lambda = target.type_label == "Function (Serverless)";
+            let block = Block::new("resource", &["aws_lb_target_group", &group])
+                .attr("name", self.aws_name(&group));
+            blocks.push(if lambda {
+                block.attr("target_type", Value::string("lambda"))
+            } else {
+                block
+                    .attr(
+                        "port",
+                        Value::reference(port(&target.type_label).unwrap_or(80).to_string()),
+                    )
+                    .attr("protocol", Value::string("HTTP"))
+                    .attr("vpc_id", Value::reference(vpc_id.clone()))
+            });
+            if lambda {
+                blocks.push(
+                    Block::new("resource", &["aws_lb_target_group_attachment", &group])
+                        .attr(
+                            "target_group_arn",
+                            Value::reference(format!("aws_lb_target_group.{}.arn", group)),
+                        )
+                        .attr(
+                            "target_id",
+                            Value::reference(format!(
+                                "aws_lambda_function.{}.arn",
+                                self.name(target)
+                            )),
+                        ),
+                );
+            }
+        }
+
+        // 最初の接続先へ転送し、接続先がなければ 404 を返す
+        let action = match targets.first() {
+            Some(target) => Block::new("default_action", &[])
+                .attr("type", Value::string("forward"))
+                .attr(
+                    "target_group_arn",
+                    Value::reference(format!(
+                        "aws_lb_target_group.{}_{}.arn",
+                        name,
+                        self.name(target)
+                    )),
+                ),
+            None => Block::new("default_action", &[])
+                .attr("type", Value::string("fixed-response"))
+                .blank()
+                .block(
+                    Block::new("fixed_response", &[])
+                        .attr("content_type", Value::string("text/plain"))
+                        .attr("status_code", Value::string("404")),
+                ),
+        };
+        blocks.push(
+            Block::new("resource", &["aws_lb_listener", &name])
+                .attr(
+                    "load_balancer_arn",
+                    Value::reference(format!("aws_lb.{}.arn", name)),
+                )
+                .attr("port", Value::reference("80"))
+                .attr("protocol", Value::string("HTTP"))
+                .blank()
+                .block(action),
+        );
+        blocks
+    }
+
+    // 常時稼働のサーバーは、起動テンプレートと Auto Scaling グループで表す
+    fn compute(&mut self, node: &Node) -> Vec<Block> {
+        self.needs_ami = true;
+        let name = self.name(node);
+        let count = node.instance_count();
+        let (min, max) = match node.properties.autoscaling {
+            Some(scaling) => (scaling.min.max(1), scaling.max.max(count)),
+            None => (count, count),
+        };
+        let subnets = self.subnet_ids(node);
+        if subnets.is_empty() {
+            self.needs_zones = true;
+        }
+        // ロードバランサーから転送されるターゲットグループ (load_balancer で作るものに限る)
+        let target_groups: Vec<String> = if LB_TARGETS.contains(&node.type_label.as_str()) {
+            self.sources(node, &["Load Balancer"])
+                .iter()
+                .map(|lb| format!("aws_lb_target_group.{}_{}.arn", self.name(lb), name))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        vec![
+            Block::new("resource", &["aws_launch_template", &name])
+                .attr(
+                    "name_prefix",
+                    template(&format!("${{var.project_name}}-{}-", dashed(&name))),
+                )
+                .attr("image_id", Value::reference("data.aws_ami.amazon_linux.id"))
+                .attr("instance_type", self.instance_class(node, "t3.micro"))
+                .attr_if(
+                    self.security_group(node).is_some(),
+                    "vpc_security_group_ids",
+                    || self.security_group_ids(node),
+                ),
+            Block::new("resource", &["aws_autoscaling_group", &name])
+                .attr("name", self.aws_name(&name))
+                .attr("min_size", Value::reference(min.to_string()))
+                .attr("max_size", Value::reference(max.to_string()))
+                .attr(
+                    "desired_capacity",
+                    Value::reference(count.max(min).to_string()),
+                )
+                .attr_if(!subnets.is_empty(), "vpc_zone_identifier", || {
+                    Value::references(subnets.clone())
+                })
+                .attr_if(subnets.is_empty(), "availability_zones", || {
+                    Value::reference("data.aws_availability_zones.available.names")
+                })
+                .attr_if(!target_groups.is_empty(), "target_group_arns", || {
+                    Value::references(target_groups.clone())
+                })
+                .blank()
+                .block(
+                    Block::new("launch_template", &[])
+                        .attr(
+                            "id",
+                            Value::reference(format!("aws_launch_template.{}.id", name)),
+                        )
+                        .attr("version", Value::string("$Latest")),
+                ),
+        ]
+    }
+
+    fn function(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        let subnets = self.subnet_ids(node);
+        let mut block = Block::new("resource", &["aws_lambda_function", &name])
+            .attr("function_name", self.aws_name(&name))
+            .attr("role", Value::reference("aws_iam_role.lambda_exec.arn"))
+            .attr("runtime", Value::string("nodejs20.x"))
+            .attr("handler", Value::string("index.handler"))
+            .attr("filename", Value::string("lambda.zip"));
+        if !subnets.is_empty() {
+            block = block.blank().block(
+                Block::new("vpc_config", &[])
+                    .attr("subnet_ids", Value::references(subnets))
+                    .attr("security_group_ids", self.security_group_ids(node)),
+            );
+        }
+        let mut blocks = vec![block];
+
+        // 関数を呼び出すサービスごとの実行許可
+        let callers = self.sources(
+            node,
+            &["API Gateway", "Pub/Sub", "Alert Manager", "Event Bus"],
+        );
+        for caller in callers {
+            let caller_name = self.name(caller);
+            let (principal, source_arn) = match caller.type_label.as_str() {
+                "API Gateway" => (
+                    "apigateway.amazonaws.com",
+                    template(&format!(
+                        "${{aws_apigatewayv2_api.{}.execution_arn}}/*/*",
+                        caller_name
+                    )),
+                ),
+                "Event Bus" => (
+                    "events.amazonaws.com",
+                    Value::reference(format!("aws_cloudwatch_event_rule.{}.arn", caller_name)),
+                ),
+                _ => (
+                    "sns.amazonaws.com",
+                    Value::reference(format!("aws_sns_topic.{}.arn", caller_name)),
+                ),
+            };
+            blocks.push(
+                Block::new(
+                    "resource",
+                    &[
+                        "aws_lambda_permission",
+                        &format!("{}_to_{}", caller_name, name),
+                    ],
+                )
+                .attr(
+                    "statement_id",
+                    Value::string(&format!("Allow-{}", dashed(&caller_name))),
+                )
+                .attr("action", Value::string("lambda:InvokeFunction"))
+                .attr(
+                    "function_name",
+                    Value::reference(format!("aws_lambda_function.{}.function_name", name)),
+                )
+                .attr("principal", Value::string(principal))
+                .attr("source_arn", source_arn),
+            );
+        }
+        blocks
+    }
+
+    fn api_gateway(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        let mut blocks = vec![
+            Block::new("resource", &["aws_apigatewayv2_api", &name])
+                .attr("name", self.aws_name(&name))
+                .attr("protocol_type", Value::string("HTTP")),
+            Block::new("resource", &["aws_apigatewayv2_stage", &name])
+                .attr(
+                    "api_id",
+                    Value::reference(format!("aws_apigatewayv2_api.{}.id", name)),
+                )
+                .attr("name", Value::string("$default"))
+                .attr("auto_deploy", Value::reference("true")),
+        ];
+        for target in self.targets(node, &["Function (Serverless)", "Load Balancer"]) {
+            let target_name = self.name(target);
+            let integration = format!("{}_to_{}", name, target_name);
+            let block = Block::new("resource", &["aws_apigatewayv2_integration", &integration])
+                .attr(
+                    "api_id",
+                    Value::reference(format!("aws_apigatewayv2_api.{}.id", name)),
+                );
+            blocks.push(match target.type_label.as_str() {
+                "Load Balancer" => block
+                    .attr("integration_type", Value::string("HTTP_PROXY"))
+                    .attr("integration_method", Value::string("ANY"))
+                    .attr(
+                        "integration_uri",
+                        template(&format!("http://${{aws_lb.{}.dns_name}}", target_name)),
+                    ),
+                _ => block
+                    .attr("integration_type", Value::string("AWS_PROXY"))
+                    .attr(
+                        "integration_uri",
+                        Value::reference(format!("aws_lambda_function.{}.invoke_arn", target_name)),
+                    )
+                    .attr("payload_format_version", Value::string("2.0")),
+            });
+            blocks.push(
+                Block::new("resource", &["aws_apigatewayv2_route", &integration])
+                    .attr(
+                        "api_id",
+                        Value::reference(format!("aws_apigatewayv2_api.{}.id", name)),
+                    )
+                    .attr(
+                        "route_key",
+                        Value::string(&format!("ANY /{}/{{proxy+}}", dashed(&target_name))),
+                    )
+                    .attr(
+                        "target",
+                        template(&format!(
+                            "integrations/${{aws_apigatewayv2_integration.{}.id}}",
+                            integration
+                        )),
+                    ),
+            );
+        }
+        blocks
+    }
+
+    fn cdn(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        let mut origins = Vec::new();
+        for target in self.targets(node, &["Load Balancer", "API Gateway", "Object Storage"]) {
+            let target_name = self.name(target);
+            let domain = match target.type_label.as_str() {
+                "Load Balancer" => format!("aws_lb.{}.dns_name", target_name),
+                "API Gateway" => format!(
+                    "replace(aws_apigatewayv2_api.{}.api_endpoint, \"https://\", \"\")",
+                    target_name
+                ),
+                _ => format!("aws_s3_bucket.{}.bucket_regional_domain_name", target_name),
+            };
+            let origin = Block::new("origin", &[])
+                .attr("domain_name", Value::reference(domain))
+                .attr("origin_id", Value::string(&target_name));
+            origins.push((
+                target_name,
+                if target.type_label == "Object Storage" {
+                    origin
+                } else {
+                    origin.blank().block(
+                        Block::new("custom_origin_config", &[])
+                            .attr("http_port", Value::reference("80"))
+                            .attr("https_port", Value::reference("443"))
+                            .attr(
+                                "origin_protocol_policy",
+                                Value::string(if target.type_label == "API Gateway" {
+                                    "https-only"
+                                } else {
+                                    "http-only"
+                                }),
+                            )
+                            .attr("origin_ssl_protocols", Value::strings(&["TLSv1.2"])),
+                    )
+                },
+            ));
+        }
+        if origins.is_empty() {
+            origins.push((
+                "default".to_string(),
+                Block::new("origin", &[])
+                    .comment("TODO: 配信元を指定してください")
+                    .attr("domain_name", Value::string(DOMAIN_NAME))
+                    .attr("origin_id", Value::string("default")),
+            ));
+        }
+        let waf = self
+            .sources(node, &["WAF (Firewall)"])
+            .first()
+            .map(|w| self.name(w));
+
+        let default_origin = origins[0].0.clone();
+        let mut block = Block::new("resource", &["aws_cloudfront_distribution", &name])
+            .attr("enabled", Value::reference("true"))
+            .attr_if(waf.is_some(), "web_acl_id", || {
+                Value::reference(format!(
+                    "aws_wafv2_web_acl.{}.arn",
+                    waf.clone().unwrap_or_default()
+                ))
+            });
+        for (_, origin) in origins {
+            block = block.blank().block(origin);
+        }
+        vec![
+            block
+                .blank()
+                .block(
+                    Block::new("default_cache_behavior", &[])
+                        .attr("target_origin_id", Value::string(&default_origin))
+                        .attr("viewer_protocol_policy", Value::string("redirect-to-https"))
+                        .attr(
+                            "allowed_methods",
+                            Value::strings(&["GET", "HEAD", "OPTIONS"]),
+                        )
+                        .attr("cached_methods", Value::strings(&["GET", "HEAD"]))
+                        .comment("マネージドキャッシュポリシー: CachingOptimized")
+                        .attr(
+                            "cache_policy_id",
+                            Value::string("658327ea-f89d-4fab-a63d-7e88639e58f6"),
+                        ),
+                )
+                .blank()
+                .block(
+                    Block::new("restrictions", &[]).block(
+                        Block::new("geo_restriction", &[])
+                            .attr("restriction_type", Value::string("none")),
+                    ),
+                )
+                .blank()
+                .block(
+                    Block::new("viewer_certificate", &[])
+                        .attr("cloudfront_default_certificate", Value::reference("true")),
+                ),
+        ]
+    }
+
+    fn dns(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        let mut blocks = vec![
+            Block::new("resource", &["aws_route53_zone", &name])
+                .attr("name", Value::string(DOMAIN_NAME)),
+        ];
+        for target in self.targets(node, &["CDN (CloudFront)", "Load Balancer"]) {
+            let target_name = self.name(target);
+            let (alias_name, zone_id) = match target.type_label.as_str() {
+                "CDN (CloudFront)" => (
+                    format!("aws_cloudfront_distribution.{}.domain_name", target_name),
+                    format!("aws_cloudfront_distribution.{}.hosted_zone_id", target_name),
+                ),
+                _ => (
+                    format!("aws_lb.{}.dns_name", target_name),
+                    format!("aws_lb.{}.zone_id", target_name),
+                ),
+            };
+            blocks.push(
+                Block::new(
+                    "resource",
+                    &[
+                        "aws_route53_record",
+                        &format!("{}_to_{}", name, target_name),
+                    ],
+                )
+                .attr(
+                    "zone_id",
+                    Value::reference(format!("aws_route53_zone.{}.zone_id", name)),
+                )
+                .attr(
+                    "name",
+                    Value::string(&format!("{}.{}", dashed(&target_name), DOMAIN_NAME)),
+                )
+                .attr("type", Value::string("A"))
+                .blank()
+                .block(
+                    Block::new("alias", &[])
+                        .attr("name", Value::reference(alias_name))
+                        .attr("zone_id", Value::reference(zone_id))
+                        .attr("evaluate_target_health", Value::reference("false")),
+                ),
+            );
+        }
+        blocks
+    }
+
+    fn waf(&mut self, node: &Node) -> Vec<Block> {
+        let name = self.name(node);
+        // CloudFront の前段に置く場合は CLOUDFRONT (us-east-1 で作成する必要がある)
+        let cloudfront = !self.targets(node, &["CDN (CloudFront)"]).is_empty();
+        let visibility = |metric: &str| {
+            Block::new("visibility_config", &[])
+                .attr("cloudwatch_metrics_enabled", Value::reference("true"))
+                .attr("metric_name", Value::string(metric))
+                .attr("sampled_requests_enabled", Value::reference("true"))
+        };
+        let mut acl = Block::new("resource", &["aws_wafv2_web_acl", &name]);
+        if cloudfront {
+            acl = acl
+                .comment("CloudFront 用の Web ACL は us-east-1 のプロバイダーで作成してください");
+        }
+        let mut blocks = vec![
+            acl.attr("name", self.aws_name(&name))
+                .attr(
+                    "scope",
+                    Value::string(if cloudfront { "CLOUDFRONT" } else { "REGIONAL" }),
+                )
+                .blank()
+                .block(Block::new("default_action", &[]).block(Block::new("allow", &[])))
+                .blank()
+                .block(
+                    Block::new("rule", &[])
+                        .attr("name", Value::string("common-rule-set"))
+                        .attr("priority", Value::reference("1"))
+                        .blank()
+                        .block(Block::new("override_action", &[]).block(Block::new("none", &[])))
+                        .blank()
+                        .block(
+                            Block::new("statement", &[]).block(
+                                Block::new("managed_rule_group_statement", &[])
+                                    .attr("name", Value::string("AWSManagedRulesCommonRuleSet"))
+                                    .attr("vendor_name", Value::string("AWS")),
+                            ),
+                        )
+                        .blank()
+                        .block(visibility("common-rule-set")),
+                )
+                .blank()
+                .block(visibility(&dashed(&name))),
+        ];
+        for target in self.targets(node, &["Load Balancer"]) {
+            let target_name = self.name(target);
+            blocks.push(
+                Block::new(
+                    "resource",
+                    &[
+                        "aws_wafv2_web_acl_association",
+                        &format!("{}_to_{}", name, target_name),
+                    ],
+                )
+                .attr(
+                    "resource_arn",
+                    Value::reference(format!("aws_lb.{}.arn", target_name)),
+                )
+                .attr(
+                    "web_acl_arn",
+                    Value::reference(format!("aws_wafv2_web_acl.{}.arn", name)),
+                ),
+            );
+        }
+        blocks
+    }
+
+    fn rdbms(&mut self, node: &Node) -> Vec<Block> {
+        self.needs_db_password = true;
+        let name = self.name(node);
+        let mut blocks = self.subnet_group(node, "aws_db_subnet_group");
+        blocks.push(
+            Block::new("resource", &["aws_db_instance", &name])
+                .attr("identifier", self.aws_name(&name))
+                .attr("engine", Value::string("postgres"))
+                .attr("engine_version", Value::string("16"))
+                .attr("instance_class", self.instance_class(node, "db.t3.micro"))
+                .attr("allocated_storage", Value::reference("20"))
+                .attr("username", Value::string("app"))
+                .attr("password", Value::reference("var.db_password"))
+                .attr(
+                    "multi_az",
+                    Value::reference((node.properties.multi_az == Some(true)).to_string()),
+                )
+                .attr_if(!blocks.is_empty(), "db_subnet_group_name", || {
+                    Value::reference(format!("aws_db_subnet_group.{}.name", name))
+                })
+                .attr_if(
+                    self.security_group(node).is_some(),
+                    "vpc_security_group_ids",
+                    || self.security_group_ids(node),
+                )
+                .attr("skip_final_snapshot", Value::reference("true")),
+        );
+        if let Some(replicas) = node.properties.read_replicas.filter(|r| *r > 0) {
+            blocks.push(
+                Block::new(
+                    "resource",
+                    &["aws_db_instance", &format!("{}_replica", name)],
+                )
+                .attr("count", Value::reference(replicas.to_string()))
+                .attr(
+                    "identifier",
+                    template(&format!(
+                        "${{var.project_name}}-{}-replica-${{count.index}}",
+                        dashed(&name)
+                    )),
+                )
+                .attr(
+                    "replicate_source_db",
+                    Value::reference(format!("aws_db_instance.{}.identifier", name)),
+                )
+                .attr("instance_class", self.instance_class(node, "db.t3.micro"))
+                .attr("skip_final_snapshot", Value::reference("true")),
+            );
+        }
+        blocks
+    }
+
+    // データベース用のサブネットグループ (同じ VPC の非公開サブネットすべて)
+    fn subnet_group(&self, node: &Node, resource: &str) -> Vec<Block> {
+        let Some(vpc) = self.vpc_of(node) else {
+            return Vec::new();
+        };
+        let mut subnets: Vec<String> = self
+            .subnets_in(vpc)
+            .into_iter()
+            .filter(|s| !self.public_subnets.contains(&s.id))
+            .map(|s| format!("aws_subnet.{}.id", self.name(s)))
+            .collect();
+        if subnets.is_empty() {
+            subnets = self.subnet_ids(node);
+        }
+        if subnets.is_empty() {
+            return Vec::new();
+        }
+        let name = self.name(node);
+        vec![
+            Block::new("resource", &[resource, &name])
+                .attr("name", self.aws_name(&name))
+                .attr("subnet_ids", Value::references(subnets)),
+        ]
+    }
+
+    // --- 参照の組み立て ---
+
+    fn name(&self, node: &Node) -> String {
+        self.names
+            .get(node.id.as_str())
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    // AWS 上のリソース名 (例: "${var.project_name}-app-server")
+    fn aws_name(&self, name: &str) -> Value {
+        template(&format!("${{var.project_name}}-{}", dashed(name)))
+    }
+
+    fn tags(&self, node: &Node) -> Value {
+        let label = node.label.as_deref().unwrap_or(&node.type_label);
+        Value::Raw(format!("{{ Name = {} }}", quote(label)))
+    }
+
+    fn instance_class(&self, node: &Node, default: &str) -> Value {
+        Value::string(node.properties.instance_class.as_deref().unwrap_or(default))
+    }
+
+    fn groups(&self, kind: GroupKind) -> Vec<&'a Node> {
+        self.diagram
+            .nodes
+            .iter()
+            .filter(|n| GroupKind::from_type(&n.type_label) == Some(kind))
+            .collect()
+    }
+
+    fn of_type(&self, type_label: &str) -> Vec<&'a Node> {
+        self.diagram
+            .nodes
+            .iter()
+            .filter(|n| n.type_label == type_label)
+            .collect()
+    }
+
+    fn vpc_of(&self, node: &Node) -> Option<&'a Node> {
+        self.tree.enclosing(&node.id, GroupKind::Vpc)
+    }
+
+    fn subnets_in(&self, vpc: &Node) -> Vec<&'a Node> {
+        self.groups(GroupKind::Subnet)
+            .into_iter()
+            .filter(|s| self.vpc_of(s).is_some_and(|v| v.id == vpc.id))
+            .collect()
+    }
+
+    // サブネットは VPC ごとに作るため、VPC の外に置かれたサブネットは参照しない
+    fn subnet_ids(&self, node: &Node) -> Vec<String> {
+        self.tree
+            .enclosing(&node.id, GroupKind::Subnet)
+            .filter(|s| self.vpc_of(s).is_some())
+            .map(|s| vec![format!("aws_subnet.{}.id", self.name(s))])
+            .unwrap_or_default()
+    }
+
+    // 所属する Security Group ノード、なければ VPC 内のコンポーネント自身のセキュリティグループ
+    fn security_group(&self, node: &Node) -> Option<String> {
+        if let Some(group) = self.tree.enclosing(&node.id, GroupKind::SecurityGroup) {
+            return Some(self.name(group));
+        }
+        (NEEDS_SECURITY_GROUP.contains(&node.type_label.as_str()) && self.vpc_of(node).is_some())
+            .then(|| self.name(node))
+    }
+
+    fn security_group_ids(&self, node: &Node) -> Value {
+        Value::references(
+            self.security_group(node)
+                .map(|sg| vec![format!("aws_security_group.{}.id", sg)])
+                .unwrap_or_default(),
+        )
+    }
+
+    fn arn(&self, node: &Node) -> String {
+        let name = self.name(node);
+        match node.type_label.as_str() {
+            "Message Queue" => format!("aws_sqs_queue.{}.arn", name),
+            "Pub/Sub" | "Alert Manager" => format!("aws_sns_topic.{}.arn", name),
+            _ => format!("aws_lambda_function.{}.arn", name),
+        }
+    }
+
+    // node からの接続先のうち、指定した型のもの
+    fn targets(&self, node: &Node, types: &[&str]) -> Vec<&'a Node> {
+        let mut seen = HashSet::new();
+        self.diagram
+            .edges
+            .iter()
+            .filter(|e| e.source == node.id)
+            .filter_map(|e| self.diagram.node(&e.target))
+            .filter(|n| types.contains(&n.type_label.as_str()) && seen.insert(n.id.as_str()))
+            .collect()
+    }
+
+    // node への接続元のうち、指定した型のもの
+    fn sources(&self, node: &Node, types: &[&str]) -> Vec<&'a Node> {
+        let mut seen = HashSet::new();
+        self.diagram
+            .edges
+            .iter()
+            .filter(|e| e.target == node.id)
+            .filter_map(|e| self.diagram.node(&e.source))
+            .filter(|n| types.contains(&n.type_label.as_str()) && seen.insert(n.id.as_str()))
+            .collect()
+    }
+}
+
+fn write_section(out: &mut String, title: &str, blocks: &[Block]) {
+    if blocks.is_empty() {
+        return;
+    }
+    out.push_str(&format!("# --- {} ---\n\n", title));
+    for block in blocks {
+        block.write(out, 0);
+        out.push('\n');
+    }
+}
+
+// Terraform のリソース名 (小文字・数字・アンダースコア、重複なし)
+fn resource_names(diagram: &Diagram) -> HashMap<&str, String> {
+    let mut used = HashSet::new();
+    let mut names = HashMap::new();
+    for node in &diagram.nodes {
+        let source = match node.label.as_deref() {
+            Some(label) if label.is_ascii() && !label.trim().is_empty() => label,
+            _ => node.id.as_str(),
+        };
+        let mut base = String::new();
+        for c in source.to_ascii_lowercase().chars() {
+            if c.is_ascii_alphanumeric() {
+                base.push(c);
+            } else if !base.ends_with('_') {
+                base.push('_');
+            }
+        }
+        let mut base = base.trim_matches('_').to_string();
+        if base.is_empty() || base.starts_with(|c: char| c.is_ascii_digit()) {
+            base = format!("n_{}", base);
+        }
+        let mut candidate = base.clone();
+        let mut suffix = 1;
+        while !used.insert(candidate.clone()) {
+            suffix += 1;
+            candidate = format!("{}_{}", base, suffix);
+        }
+        names.insert(node.id.as_str(), candidate);
+    }
+    names
+}
+
+// AWS 上の名前に使う形 (アンダースコアをハイフンに)
+fn dashed(name: &str) -> String {
+    name.replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use crate::domain::model::template::TemplateLibrary;
+
+    // resource "T" "N" として宣言されていない aws_T.N.* への参照
+    fn dangling_references(hcl: &str) -> Vec<String> {
+        let mut declared = HashSet::new();
+        for line in hcl.lines() {
+            let words: Vec<&str> = line.split('"').collect();
+            if line.starts_with("resource ") && words.len() >= 4 {
+                declared.insert(format!("{}.{}", words[1], words[3]));
+            }
+        }
+        let mut dangling = Vec::new();
+        for line in hcl.lines().filter(|l| !l.starts_with("resource ")) {
+            let tokens = line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'));
+            for token in tokens.filter(|t| t.starts_with("aws_")) {
+                let parts: Vec<&str> = token.split('.').collect();
+                if parts.len() >= 3 {
+                    let reference = format!("{}.{}", parts[0], parts[1]);
+                    if !declared.contains(&reference) {
+                        dangling.push(reference);
+                    }
+                }
+            }
+        }
+        dangling
+    }
+
+    // aws_lb ブロックに subnets が指定されているか
+    fn load_balancers_have_subnets(hcl: &str) -> bool {
+        hcl.split("\n\n")
+            .filter(|block| block.contains("resource \"aws_lb\""))
+            .all(|block| block.contains("subnets"))
+    }
+
+    #[test]
+    fn templates_render_without_dangling_references() {
+        let catalog = test_catalog();
+        let library = TemplateLibrary::from_json(include_str!(
+            "../../infrastructure/architecture_templates.json"
+        ))
+        .unwrap();
+        for template in library.templates() {
+            let hcl = render(&template.diagram, &catalog);
+            assert_eq!(
+                dangling_references(&hcl),
+                Vec::<String>::new(),
+                "{}",
+                template.id
+            );
+            assert!(load_balancers_have_subnets(&hcl), "{}", template.id);
+        }
+
+        // VPC に入っていないサブネット (サブネットのリソースは作られない)
+        let diagram: Diagram = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "id": "sn", "type": "Subnet", "position": { "x": 0, "y": 0 } },
+                { "id": "app", "type": "App Server", "parentNode": "sn", "position": { "x": 0, "y": 0 } },
+                { "id": "fn", "type": "Function (Serverless)", "parentNode": "sn", "position": { "x": 0, "y": 0 } },
+                { "id": "db", "type": "RDBMS (SQL)", "parentNode": "sn", "position": { "x": 0, "y": 0 } }
+            ],
+            "edges": [
+                { "source": "app", "target": "db" },
+                { "source": "fn", "target": "db" }
+            ]
+        }))
+        .unwrap();
+        let hcl = render(&diagram, &catalog);
+        assert_eq!(dangling_references(&hcl), Vec::<String>::new(), "{}", hcl);
+        assert!(!hcl.contains("aws_subnet."), "{}", hcl);
+    }
+
+    #[test]
+    fn ungrouped_load_balancer_uses_default_vpc() {
+        let diagram: Diagram = serde_json::from_value(serde_json::json!({
+            "nodes": [
+                { "id": "browser", "type": "Web Browser", "position": { "x": 0, "y": 0 } },
+                { "id": "lb", "type": "Load Balancer", "position": { "x": 0, "y": 100 } },
+                { "id": "app", "type": "App Server", "position": { "x": 0, "y": 200 } },
+                { "id": "worker", "type": "Worker (Async)", "position": { "x": 200, "y": 200 } },
+                { "id": "db", "type": "RDBMS (SQL)", "position": { "x": 0, "y": 300 } }
+            ],
+            "edges": [
+                { "source": "browser", "target": "lb" },
+                { "source": "lb", "target": "app" },
+                { "source": "lb", "target": "worker" },
+                { "source": "app", "target": "db" }
+            ]
+        }))
+        .unwrap();
+        let hcl = render(&diagram, &test_catalog());
+
+        assert!(hcl.contains("data \"aws_subnets\" \"default\""), "{}", hcl);
+        assert!(
+            hcl.contains("subnets            = data.aws_subnets.default.ids"),
+            "{}",
+            hcl
+        );
+        assert!(
+            hcl.contains("vpc_id   = data.aws_vpc.default.id"),
+            "{}",
+            hcl
+        );
+        assert_eq!(dangling_references(&hcl), Vec::<String>::new(), "{}", hcl);
+        assert!(load_balancers_have_subnets(&hcl));
+    }
+}
@@ -0,0 +1,237 @@
+use serde_yaml::Value;
+
+use super::container::{container_type, host_references, image_protocol, is_loopback};
+use super::{add_edge, allows_property, auto_layout, new_node};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{Diagram, NodeProperties, Position};
+use crate::domain::model::interchange::{ImportResult, SkippedItem};
+
+// docker-compose (compose.yaml) から設計図を取り込む
+// サービスをイメージ名から推定したコンポーネントに、depends_on と環境変数・コマンド中の
+// ホスト名 (サービス名や localhost の公開ポート) への参照を接続にする
+
+struct Service<'a> {
+    name: &'a str,
+    // 他のサービスから参照されうる名前 (サービス名・コンテナ名・ネットワークのエイリアス)
+    aliases: Vec<&'a str>,
+    image: Option<&'a str>,
+    // ホスト側に公開しているポート
+    published: Vec<i64>,
+    definition: &'a Value,
+}
+
+pub fn import(
+    yaml: &str,
+    catalog: &ComponentCatalog,
+) -> Result<ImportResult, Box<dyn std::error::Error>> {
+    let doc: Value = serde_yaml::from_str(yaml)?;
+    let definitions = doc["services"]
+        .as_mapping()
+        .ok_or("services がありません (compose.yaml の形式を指定してください)")?;
+
+    let mut skipped = Vec::new();
+    let mut services = Vec::new();
+    for (name, definition) in definitions {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        if !definition.is_mapping() {
+            skipped.push(SkippedItem::new(name, "サービスの定義が読み取れません"));
+            continue;
+        }
+        let mut aliases = vec![name];
+        aliases.extend(definition["container_name"].as_str());
+        aliases.extend(definition["hostname"].as_str());
+        for network in definition["networks"].as_mapping().into_iter().flatten() {
+            aliases.extend(strings(&network.1["aliases"]));
+        }
+        services.push(Service {
+            name,
+            aliases,
+            image: definition["image"].as_str(),
+            published: published_ports(&definition["ports"]),
+            definition,
+        });
+    }
+
+    let mut nodes = Vec::new();
+    for service in &services {
+        let type_label = container_type(service.image, service.name);
+        let label = service.definition["container_name"]
+            .as_str()
+            .unwrap_or(service.name);
+        let mut node = new_node(
+            service.name.to_string(),
+            type_label,
+            Some(label.to_string()),
+            Position { x: 0.0, y: 0.0 },
+        );
+        let replicas = service.definition["deploy"]["replicas"]
+            .as_u64()
+            .or_else(|| service.definition["scale"].as_u64());
+        if allows_property(catalog, type_label, "replicas") {
+            node.properties = NodeProperties {
+                replicas: replicas.map(|n| n as u32),
+                ..NodeProperties::default()
+            };
+        }
+        nodes.push(node);
+    }
+
+    let mut edges = Vec::new();
+    for service in &services {
+        // 接続の詳細が分かる環境変数・コマンドを先に見て、depends_on で補う
+        for text in texts(service.definition) {
+            for reference in host_references(&text) {
+                let target = if is_loopback(reference.host) {
+                    reference
+                        .port
+                        .and_then(|port| services.iter().find(|s| s.published.contains(&port)))
+                } else {
+                    services
+                        .iter()
+                        .find(|s| s.aliases.contains(&reference.host))
+                };
+                if let Some(target) = target {
+                    let protocol = reference.protocol.or(image_protocol(target.image));
+                    add_edge(&mut edges, service.name, target.name, protocol);
+                }
+            }
+        }
+        for dependency in dependencies(service.definition) {
+            if let Some(target) = services.iter().find(|s| s.name == dependency) {
+                add_edge(
+                    &mut edges,
+                    service.name,
+                    target.name,
+                    image_protocol(target.image),
+                );
+            }
+        }
+    }
+    for (i, edge) in edges.iter_mut().enumerate() {
+        edge.id = Some(format!("edge-{}", i + 1));
+    }
+
+    let mut diagram = Diagram { nodes, edges };
+    auto_layout(&mut diagram);
+    Ok(ImportResult { diagram, skipped })
+}
+
+// depends_on (一覧、またはサービス名をキーとする条件付きの形式)
+fn dependencies(definition: &Value) -> Vec<&str> {
+    match &definition["depends_on"] {
+        Value::Mapping(map) => map.keys().filter_map(Value::as_str).collect(),
+        other => strings(other),
+    }
+    .into_iter()
+    .chain(
+        // links は "service:alias" の形式がある
+        strings(&definition["links"])
+            .into_iter()
+            .map(|l| l.split(':').next().unwrap_or(l)),
+    )
+    .collect()
+}
+
+// 接続先のホスト名が書かれうる文字列 (環境変数の値・コマンド)
+fn texts(definition: &Value) -> Vec<String> {
+    let mut texts = Vec::new();
+    match &definition["environment"] {
+        Value::Mapping(map) => texts.extend(map.values().filter_map(scalar)),
+        // "KEY=value" の一覧
+        other => texts.extend(
+            strings(other)
+                .into_iter()
+                .filter_map(|e| e.split_once('=').map(|(_, v)| v.to_string())),
+        ),
+    }
+    for key in ["command", "entrypoint"] {
+        match &definition[key] {
+            Value::String(command) => texts.push(command.clone()),
+            other => texts.extend(strings(other).into_iter().map(str::to_string)),
+        }
+    }
+    texts
+}
+
+fn strings(value: &Value) -> Vec<&str> {
+    value
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// ホスト側に公開するポート ("8080:8080" / "127.0.0.1:8080:80/tcp" の短い形式と、published を持つ長い形式)
+fn published_ports(ports: &Value) -> Vec<i64> {
+    let mut published = Vec::new();
+    for port in ports.as_sequence().into_iter().flatten() {
+        let found = match port {
+            Value::String(spec) => {
+                let spec = spec.split('/').next().unwrap_or(spec);
+                match spec.split(':').collect::<Vec<_>>().as_slice() {
+                    [host, _] | [_, host, _] => {
+                        host.split('-').next().and_then(|p| p.parse::<i64>().ok())
+                    }
+                    _ => None,
+                }
+            }
+            _ => scalar(&port["published"]).and_then(|p| p.parse().ok()),
+        };
+        published.extend(found);
+    }
+    published
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+    use crate::domain::model::diagram::Protocol;
+
+    const COMPOSE: &str = include_str!("fixtures/compose.yaml");
+
+    #[test]
+    fn imports_services_without_false_edges() {
+        let result = import(COMPOSE, &test_catalog()).unwrap();
+        let diagram = &result.diagram;
+        let type_of = |id: &str| diagram.node(id).map(|n| n.type_label.as_str());
+        assert_eq!(type_of("app"), Some("App Server"));
+        assert_eq!(type_of("worker"), Some("Worker (Async)"));
+        assert_eq!(type_of("db"), Some("RDBMS (SQL)"));
+        assert_eq!(type_of("cache"), Some("Distributed Cache"));
+        assert_eq!(type_of("queue"), Some("Message Queue"));
+        assert_eq!(type_of("docs"), Some("NoSQL (Doc)"));
+        // 管理画面・エクスポーターはデータストアではない
+        assert_eq!(type_of("mongo-express"), Some("App Server"));
+        assert_eq!(type_of("metrics"), Some("App Server"));
+
+        let mut edges: Vec<(&str, &str, Option<Protocol>)> = diagram
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.protocol))
+            .collect();
+        edges.sort_by_key(|(s, t, _)| (*s, *t));
+        // db の POSTGRES_USER: app や worker の "--mode app" は接続ではない
+        assert_eq!(
+            edges,
+            vec![
+                ("app", "cache", Some(Protocol::Tcp)),
+                ("app", "db", Some(Protocol::Sql)),
+                ("metrics", "db", Some(Protocol::Sql)),
+                ("mongo-express", "docs", Some(Protocol::Tcp)),
+                ("worker", "queue", Some(Protocol::Amqp)),
+            ]
+        );
+    }
+}
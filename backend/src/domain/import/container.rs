@@ -0,0 +1,200 @@
+use super::{compute_type, port_protocol};
+use crate::domain::model::diagram::Protocol;
+
+// コンテナの定義 (docker-compose / Kubernetes) に共通する推定
+// イメージ名からコンポーネントの型を、環境変数やコマンドの文字列から接続先のホスト名を読み取る
+
+// イメージ名の語の先頭と、カタログの型・そのコンテナが話すプロトコルの対応
+// (例: "postgres" は "postgres" / "postgresql" / "cp-postgres" に一致する)
+// 先頭から順に判定するため、より具体的な語句を先に置く
+const IMAGE_TYPES: [(&str, &str, Option<Protocol>); 36] = [
+    ("postgres", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("postgis", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("mysql", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("mariadb", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("mssql", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("cockroach", "RDBMS (SQL)", Some(Protocol::Sql)),
+    ("mongo", "NoSQL (Doc)", Some(Protocol::Tcp)),
+    ("couchdb", "NoSQL (Doc)", Some(Protocol::Http)),
+    ("dynamodb", "NoSQL (KV)", Some(Protocol::Http)),
+    ("cassandra", "NoSQL (KV)", Some(Protocol::Tcp)),
+    ("scylla", "NoSQL (KV)", Some(Protocol::Tcp)),
+    ("neo4j", "NoSQL (Graph)", Some(Protocol::Tcp)),
+    ("minio", "Object Storage", Some(Protocol::Http)),
+    ("elasticsearch", "Search Engine", Some(Protocol::Http)),
+    ("opensearch", "Search Engine", Some(Protocol::Http)),
+    ("meilisearch", "Search Engine", Some(Protocol::Http)),
+    ("solr", "Search Engine", Some(Protocol::Http)),
+    ("redis", "Distributed Cache", Some(Protocol::Tcp)),
+    ("valkey", "Distributed Cache", Some(Protocol::Tcp)),
+    ("memcached", "Distributed Cache", Some(Protocol::Tcp)),
+    ("rabbitmq", "Message Queue", Some(Protocol::Amqp)),
+    ("activemq", "Message Queue", Some(Protocol::Amqp)),
+    ("elasticmq", "Message Queue", Some(Protocol::Http)),
+    ("kafka", "Pub/Sub", Some(Protocol::Tcp)),
+    ("redpanda", "Pub/Sub", Some(Protocol::Tcp)),
+    ("nats", "Pub/Sub", Some(Protocol::Tcp)),
+    ("alertmanager", "Alert Manager", Some(Protocol::Http)),
+    ("prometheus", "Metrics Store", Some(Protocol::Http)),
+    ("influxdb", "Metrics Store", Some(Protocol::Http)),
+    ("jaeger", "Dist. Tracer", Some(Protocol::Grpc)),
+    ("zipkin", "Dist. Tracer", Some(Protocol::Http)),
+    ("loki", "Log Aggregator", Some(Protocol::Http)),
+    ("fluent", "Log Aggregator", Some(Protocol::Tcp)),
+    ("traefik", "Load Balancer", Some(Protocol::Http)),
+    ("haproxy", "Load Balancer", Some(Protocol::Http)),
+    ("nginx", "Web Server", Some(Protocol::Http)),
+];
+
+// データストア等の管理画面やメトリクスの出力など、付随するツールのイメージ名の語の末尾
+// (例: mongo-express / redis-commander / postgres-exporter / redisinsight はデータストアではない)
+const COMPANION_WORDS: [&str; 5] = ["exporter", "express", "commander", "admin", "insight"];
+
+// イメージ名 (レジストリ・タグを除く) と名前から推定したコンポーネントの型
+pub fn container_type(image: Option<&str>, name: &str) -> &'static str {
+    image
+        .and_then(image_entry)
+        .map(|(_, type_label, _)| *type_label)
+        .unwrap_or_else(|| compute_type(name))
+}
+
+// イメージから分かる、そのコンテナへの接続のプロトコル
+pub fn image_protocol(image: Option<&str>) -> Option<Protocol> {
+    image.and_then(image_entry).and_then(|(_, _, p)| *p)
+}
+
+fn image_entry(image: &str) -> Option<&'static (&'static str, &'static str, Option<Protocol>)> {
+    let image = image.split('@').next().unwrap_or(image).to_lowercase();
+    // レジストリや組織名 ("registry:5000/myorg/...") を除いた最後の要素の、タグより前
+    let last = image.rsplit('/').next().unwrap_or(&image);
+    let name = last.split(':').next().unwrap_or(last);
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words
+        .iter()
+        .any(|w| COMPANION_WORDS.iter().any(|c| w.ends_with(c)))
+    {
+        return None;
+    }
+    IMAGE_TYPES
+        .iter()
+        .find(|(keyword, _, _)| words.iter().any(|w| w.starts_with(keyword)))
+}
+
+// 文字列に現れるホスト名への参照 (例: "postgres://user@db:5432/app" → db)
+// スキーム付きの URL か "host:port" の形のものに限る
+// (単独の語はユーザー名や引数の値であることが多く、サービス名と一致しても接続とはみなさない)
+pub struct HostReference<'a> {
+    // ホスト名の最初のラベル (Kubernetes の "api.prod.svc.cluster.local" なら "api")
+    pub host: &'a str,
+    pub port: Option<i64>,
+    pub protocol: Option<Protocol>,
+}
+
+pub fn host_references(text: &str) -> Vec<HostReference<'_>> {
+    let tokens = text.split(|c: char| !(c.is_ascii_alphanumeric() || "._-:/@".contains(c)));
+    let mut references = Vec::new();
+    for token in tokens.filter(|t| !t.is_empty()) {
+        let (scheme, rest) = match token.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme), rest),
+            None => (None, token),
+        };
+        let authority = rest.split('/').next().unwrap_or(rest);
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let (host, port) = match authority.split_once(':') {
+            Some((host, port)) => (host, port.parse().ok()),
+            None => (authority, None),
+        };
+        let host = host.split('.').next().unwrap_or(host);
+        if host.is_empty() || (scheme.is_none() && port.is_none()) {
+            continue;
+        }
+        let protocol = scheme
+            .and_then(scheme_protocol)
+            .or_else(|| port.and_then(port_protocol));
+        references.push(HostReference {
+            host,
+            port,
+            protocol,
+        });
+    }
+    references
+}
+
+fn scheme_protocol(scheme: &str) -> Option<Protocol> {
+    match scheme.to_lowercase().as_str() {
+        "http" => Some(Protocol::Http),
+        "https" => Some(Protocol::Https),
+        "ws" | "wss" => Some(Protocol::WebSocket),
+        "grpc" => Some(Protocol::Grpc),
+        "amqp" | "amqps" => Some(Protocol::Amqp),
+        "postgres" | "postgresql" | "mysql" | "mariadb" | "jdbc" | "sqlserver" => {
+            Some(Protocol::Sql)
+        }
+        "redis" | "rediss" | "mongodb" | "nats" | "kafka" | "tcp" => Some(Protocol::Tcp),
+        _ => None,
+    }
+}
+
+// 自分自身を指すホスト名 (公開ポート経由で他のサービスを指す場合がある)
+pub fn is_loopback(host: &str) -> bool {
+    matches!(host, "localhost" | "127" | "0" | "host")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_type_uses_the_last_path_segment() {
+        let cases = [
+            ("postgres:16", "RDBMS (SQL)"),
+            ("bitnami/postgresql:16", "RDBMS (SQL)"),
+            ("registry:5000/team/redis:7", "Distributed Cache"),
+            ("confluentinc/cp-kafka", "Pub/Sub"),
+            // データストアに付随するツールは、名前から推定する
+            ("mongo-express", "App Server"),
+            ("rediscommander/redis-commander", "App Server"),
+            (
+                "quay.io/prometheuscommunity/postgres-exporter",
+                "App Server",
+            ),
+            // 組織名やレジストリに語句が含まれても一致しない
+            ("postgres-team/app:1.0", "App Server"),
+        ];
+        for (image, expected) in cases {
+            assert_eq!(
+                container_type(Some(image), "service"),
+                expected,
+                "{}",
+                image
+            );
+        }
+    }
+
+    #[test]
+    fn host_references_need_a_scheme_or_port() {
+        let hosts = |text| -> Vec<(&str, Option<i64>)> {
+            host_references(text)
+                .into_iter()
+                .map(|r| (r.host, r.port))
+                .collect()
+        };
+        assert_eq!(
+            hosts("postgres://app:secret@db:5432/app"),
+            vec![("db", Some(5432))]
+        );
+        assert_eq!(
+            hosts("http://api.prod.svc.cluster.local/v1"),
+            vec![("api", None)]
+        );
+        assert_eq!(hosts("--broker redis:6379"), vec![("redis", Some(6379))]);
+        assert!(hosts("app").is_empty());
+        assert!(hosts("--mode app").is_empty());
+        assert!(hosts("myorg/app:latest").is_empty());
+    }
+}
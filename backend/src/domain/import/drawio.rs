@@ -0,0 +1,516 @@
+use base64::Engine;
+use std::collections::HashMap;
+
+use super::new_node;
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::GroupKind;
+use crate::domain::model::diagram::{
+    CommunicationStyle, Diagram, Edge, NodeProperties, Position, Protocol,
+};
+use crate::domain::model::interchange::{ImportResult, SkippedItem};
+
+// draw.io (diagrams.net) の mxGraph XML から設計図を取り込む
+// 座標・グループの包含関係・接続のラベルを保ち、AWS アイコンの形状名をカタログの型に対応付ける
+
+// AWS アイコン (mxgraph.aws4.* / mxgraph.aws3.*) の形状名とカタログの型の対応
+// 先頭一致で判定するため、より具体的な名前を先に置く
+const AWS_SHAPES: [(&str, &str); 46] = [
+    ("group_vpc", "VPC (Network)"),
+    ("group_availability_zone", "Availability Zone"),
+    ("group_public_subnet", "Subnet"),
+    ("group_private_subnet", "Subnet"),
+    ("group_subnet", "Subnet"),
+    // AWS4 の Public/Private subnet は group_security_group のアイコンで描かれる
+    ("group_security_group", "Subnet"),
+    ("route_53", "DNS (Route53)"),
+    ("route53", "DNS (Route53)"),
+    ("hosted_zone", "DNS (Route53)"),
+    ("cloudfront", "CDN (CloudFront)"),
+    ("elastic_load_balancing", "Load Balancer"),
+    ("application_load_balancer", "Load Balancer"),
+    ("network_load_balancer", "Load Balancer"),
+    ("classic_load_balancer", "Load Balancer"),
+    ("elb", "Load Balancer"),
+    ("api_gateway", "API Gateway"),
+    ("waf", "WAF (Firewall)"),
+    ("lambda", "Function (Serverless)"),
+    ("batch", "Batch Job"),
+    ("ec2", "App Server"),
+    ("instance", "App Server"),
+    ("ecs", "App Server"),
+    ("fargate", "App Server"),
+    ("eks", "App Server"),
+    ("elastic_beanstalk", "App Server"),
+    ("aurora", "RDBMS (SQL)"),
+    ("rds", "RDBMS (SQL)"),
+    ("dynamodb", "NoSQL (KV)"),
+    ("documentdb", "NoSQL (Doc)"),
+    ("neptune", "NoSQL (Graph)"),
+    ("simple_storage_service", "Object Storage"),
+    ("s3", "Object Storage"),
+    ("opensearch", "Search Engine"),
+    ("elasticsearch", "Search Engine"),
+    ("elasticache", "Distributed Cache"),
+    ("memorydb", "Distributed Cache"),
+    ("simple_queue_service", "Message Queue"),
+    ("sqs", "Message Queue"),
+    ("simple_notification_service", "Pub/Sub"),
+    ("sns", "Pub/Sub"),
+    ("eventbridge", "Event Bus"),
+    ("cloudwatch_logs", "Log Aggregator"),
+    ("cloudwatch", "Metrics Store"),
+    ("x_ray", "Dist. Tracer"),
+    ("mobile_client", "Mobile App"),
+    ("client", "Web Browser"),
+];
+
+// 形状名で判定できない枠について、ラベルの語句から推定するグループの種類
+const GROUP_KEYWORDS: [(&str, &str); 5] = [
+    ("security group", "Security Group"),
+    ("availability zone", "Availability Zone"),
+    ("subnet", "Subnet"),
+    ("vpc", "VPC (Network)"),
+    ("az", "Availability Zone"),
+];
+
+// draw.io で図形1つの既定サイズ
+const DEFAULT_WIDTH: f64 = 120.0;
+const DEFAULT_HEIGHT: f64 = 60.0;
+
+// 圧縮された図を展開した後の上限 (小さな入力から巨大なデータを作る圧縮爆弾に備える)
+const MAX_INFLATED_BYTES: usize = 16 * 1024 * 1024;
+
+struct Cell {
+    id: String,
+    label: String,
+    style: HashMap<String, String>,
+    parent: Option<String>,
+    // object / UserObject 要素に付けられたユーザーデータ
+    attributes: HashMap<String, String>,
+    vertex: bool,
+    edge: bool,
+    source: Option<String>,
+    target: Option<String>,
+    geometry: Option<(f64, f64, f64, f64)>,
+}
+
+pub fn import(
+    xml: &str,
+    catalog: &ComponentCatalog,
+) -> Result<ImportResult, Box<dyn std::error::Error>> {
+    let model = graph_model_xml(xml)?;
+    let doc = roxmltree::Document::parse(&model)?;
+    let root = doc
+        .descendants()
+        .find(|n| n.has_tag_name("root"))
+        .ok_or("mxGraphModel の root 要素が見つかりません")?;
+    let cells: Vec<Cell> = root
+        .children()
+        .filter(|n| n.is_element())
+        .filter_map(parse_cell)
+        .collect();
+    let by_id: HashMap<&str, &Cell> = cells.iter().map(|c| (c.id.as_str(), c)).collect();
+
+    // 親子関係を決める前に、取り込む図形の型をすべて判定しておく
+    let mut skipped = Vec::new();
+    let mut types: HashMap<&str, String> = HashMap::new();
+    for cell in cells.iter().filter(|c| c.vertex) {
+        let is_container = cells
+            .iter()
+            .any(|c| c.vertex && c.parent.as_deref() == Some(cell.id.as_str()))
+            || cell.style.get("container").is_some_and(|v| v == "1");
+        match resolve_type(cell, is_container, catalog) {
+            Some(type_label) => {
+                types.insert(cell.id.as_str(), type_label);
+            }
+            // ラベルのない装飾用の図形は黙って除く
+            None if cell.label.is_empty() => {}
+            None => skipped.push(SkippedItem::new(
+                &cell.id,
+                format!("「{}」に対応するコンポーネントが見つかりません", cell.label),
+            )),
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for cell in cells.iter().filter(|c| types.contains_key(c.id.as_str())) {
+        // 取り込まなかった図形の中にある場合も、取り込んだ直近のグループを親にする
+        let parent = ancestors(cell, &by_id).find(|a| {
+            types
+                .get(a.id.as_str())
+                .is_some_and(|t| GroupKind::from_type(t).is_some())
+        });
+        let (x, y) = absolute(cell, &by_id);
+        let (px, py) = parent.map(|p| absolute(p, &by_id)).unwrap_or((0.0, 0.0));
+        let (_, _, width, height) =
+            cell.geometry
+                .unwrap_or((0.0, 0.0, DEFAULT_WIDTH, DEFAULT_HEIGHT));
+
+        let mut node = new_node(
+            cell.id.clone(),
+            &types[cell.id.as_str()],
+            Some(cell.label.clone()),
+            Position {
+                x: x - px,
+                y: y - py,
+            },
+        );
+        node.parent_node = parent.map(|p| p.id.clone());
+        node.width = Some(width);
+        node.height = Some(height);
+        node.description = cell
+            .attributes
+            .get("tooltip")
+            .filter(|t| !t.is_empty())
+            .cloned();
+        node.properties = cell
+            .attributes
+            .get("sandboxProperties")
+            .and_then(|p| serde_json::from_str::<NodeProperties>(p).ok())
+            .unwrap_or_default();
+        nodes.push(node);
+    }
+
+    let mut edges = Vec::new();
+    for cell in cells.iter().filter(|c| c.edge) {
+        let (Some(source), Some(target)) = (cell.source.as_deref(), cell.target.as_deref()) else {
+            skipped.push(SkippedItem::new(&cell.id, "接続元または接続先のない線です"));
+            continue;
+        };
+        if !types.contains_key(source) || !types.contains_key(target) {
+            skipped.push(SkippedItem::new(
+                &cell.id,
+                "取り込まなかった図形につながる接続です",
+            ));
+            continue;
+        }
+        edges.push(to_edge(cell, source, target));
+    }
+
+    Ok(ImportResult {
+        diagram: Diagram { nodes, edges },
+        skipped,
+    })
+}
+
+// mxfile / mxGraphModel のいずれか、または圧縮された diagram 要素から mxGraphModel の XML を取り出す
+fn graph_model_xml(xml: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let doc = roxmltree::Document::parse(xml)?;
+    if doc.descendants().any(|n| n.has_tag_name("mxGraphModel")) {
+        return Ok(xml.to_string());
+    }
+    // 複数ページある場合は最初のページのみ
+    let diagram = doc
+        .descendants()
+        .find(|n| n.has_tag_name("diagram"))
+        .ok_or("draw.io の図 (mxfile / mxGraphModel) ではありません")?;
+    let compressed = diagram.text().map(str::trim).unwrap_or("");
+    if compressed.is_empty() {
+        return Err("図の内容が空です".into());
+    }
+    // 圧縮形式: Base64 → raw deflate の展開 → URL デコード
+    let bytes = base64::engine::general_purpose::STANDARD.decode(compressed)?;
+    let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(&bytes, MAX_INFLATED_BYTES)
+        .map_err(|e| match e.status {
+        miniz_oxide::inflate::TINFLStatus::HasMoreOutput => format!(
+            "展開後の図が大きすぎます (上限 {} MB)",
+            MAX_INFLATED_BYTES / 1024 / 1024
+        ),
+        status => format!("図の展開に失敗しました: {:?}", status),
+    })?;
+    let encoded = String::from_utf8(inflated)?;
+    Ok(percent_encoding::percent_decode_str(&encoded)
+        .decode_utf8()?
+        .into_owned())
+}
+
+// mxCell、またはユーザーデータを持つ object / UserObject で包まれた mxCell を読む
+fn parse_cell(node: roxmltree::Node) -> Option<Cell> {
+    let (wrapper, cell) = match node.tag_name().name() {
+        "mxCell" => (None, node),
+        "object" | "UserObject" => (
+            Some(node),
+            node.children().find(|c| c.has_tag_name("mxCell"))?,
+        ),
+        _ => return None,
+    };
+    let attributes: HashMap<String, String> = wrapper
+        .map(|w| {
+            w.attributes()
+                .map(|a| (a.name().to_string(), a.value().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let id = wrapper.unwrap_or(cell).attribute("id")?.to_string();
+    let label = match wrapper {
+        Some(w) => w.attribute("label"),
+        None => cell.attribute("value"),
+    };
+    let geometry = cell
+        .children()
+        .find(|c| c.has_tag_name("mxGeometry"))
+        .map(|g| {
+            let num = |name: &str, default: f64| {
+                g.attribute(name)
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(default)
+            };
+            (
+                num("x", 0.0),
+                num("y", 0.0),
+                num("width", DEFAULT_WIDTH),
+                num("height", DEFAULT_HEIGHT),
+            )
+        });
+    Some(Cell {
+        id,
+        label: plain_text(label.unwrap_or("")),
+        style: parse_style(cell.attribute("style").unwrap_or("")),
+        parent: cell.attribute("parent").map(str::to_string),
+        attributes,
+        vertex: cell.attribute("vertex") == Some("1"),
+        edge: cell.attribute("edge") == Some("1"),
+        source: cell.attribute("source").map(str::to_string),
+        target: cell.attribute("target").map(str::to_string),
+        geometry,
+    })
+}
+
+// "rounded=1;shape=mxgraph.aws4.group;..." 形式のスタイル
+// 先頭の "ellipse;" のような値のない項目は、形状名として扱う
+fn parse_style(style: &str) -> HashMap<String, String> {
+    style
+        .split(';')
+        .filter(|s| !s.is_empty())
+        .map(|s| match s.split_once('=') {
+            Some((k, v)) => (k.to_string(), v.to_string()),
+            None => ("shape".to_string(), s.to_string()),
+        })
+        .collect()
+}
+
+// html=1 の図形では、ラベルに HTML が入る
+fn plain_text(label: &str) -> String {
+    let mut text = String::new();
+    let mut tag: Option<String> = None;
+    for c in label.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (Some(name), '>') => {
+                // 改行にあたるタグは空白にする
+                let name = name.trim_start_matches('/').to_ascii_lowercase();
+                if name.starts_with("br") || name.starts_with("div") || name.starts_with('p') {
+                    text.push(' ');
+                }
+                tag = None;
+            }
+            (Some(name), _) => name.push(c),
+            (None, _) => text.push(c),
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn resolve_type(cell: &Cell, is_container: bool, catalog: &ComponentCatalog) -> Option<String> {
+    // 1. このアプリから書き出したファイル (型をそのまま持っている)
+    if let Some(type_label) = cell.attributes.get("sandboxType")
+        && catalog.get(type_label).is_some()
+    {
+        return Some(type_label.clone());
+    }
+
+    // 2. AWS アイコンの形状名
+    let label = cell.label.to_lowercase();
+    for key in ["grIcon", "resIcon", "prIcon", "shape"] {
+        let Some(shape) = cell.style.get(key).and_then(|s| aws_shape_name(s)) else {
+            continue;
+        };
+        if let Some((_, type_label)) = AWS_SHAPES.iter().find(|(name, _)| shape.starts_with(name)) {
+            // Subnet と Security Group は同じアイコンのため、ラベルで区別する
+            if *type_label == "Subnet" && label.contains("security group") {
+                return Some("Security Group".to_string());
+            }
+            return Some(type_label.to_string());
+        }
+    }
+
+    // 3. ラベルがカタログの型名・表示名と一致するもの
+    if let Some(def) = catalog
+        .components()
+        .find(|c| c.type_name.to_lowercase() == label || c.label.to_lowercase() == label)
+    {
+        return Some(def.type_name.clone());
+    }
+
+    // 4. 子を持つ枠は、ラベルの語句からグループの種類を推定する
+    if !is_container {
+        return None;
+    }
+    let words: Vec<&str> = label
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let phrase = words.join(" ");
+    GROUP_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| {
+            if keyword.contains(' ') {
+                phrase.contains(keyword)
+            } else {
+                words.contains(keyword)
+            }
+        })
+        .map(|(_, type_label)| type_label.to_string())
+}
+
+// "mxgraph.aws4.application_load_balancer" → "application_load_balancer"
+fn aws_shape_name(shape: &str) -> Option<String> {
+    let (library, name) = shape.strip_prefix("mxgraph.")?.split_once('.')?;
+    library.starts_with("aws").then(|| name.to_lowercase())
+}
+
+// 親をたどる (壊れたファイルの循環に備え、セル数を上限とする)
+fn ancestors<'a>(
+    cell: &'a Cell,
+    by_id: &'a HashMap<&str, &'a Cell>,
+) -> impl Iterator<Item = &'a Cell> {
+    let mut current = cell.parent.as_deref();
+    std::iter::from_fn(move || {
+        let parent = by_id.get(current?).copied()?;
+        current = parent.parent.as_deref();
+        Some(parent)
+    })
+    .take(by_id.len())
+}
+
+// draw.io の座標は親からの相対座標のため、ページ上の絶対座標に直す
+fn absolute(cell: &Cell, by_id: &HashMap<&str, &Cell>) -> (f64, f64) {
+    let (x, y, _, _) = cell.geometry.unwrap_or_default();
+    ancestors(cell, by_id)
+        .filter(|a| a.vertex)
+        .fold((x, y), |(x, y), a| {
+            let (ax, ay, _, _) = a.geometry.unwrap_or_default();
+            (x + ax, y + ay)
+        })
+}
+
+fn to_edge(cell: &Cell, source: &str, target: &str) -> Edge {
+    let attribute = |name: &str| cell.attributes.get(name).filter(|v| !v.is_empty());
+    // このアプリから書き出した線は、表示用のラベルの末尾にプロトコル名を含む
+    let mut text = cell.label.as_str();
+    let explicit = attribute("protocol").and_then(|p| protocol(p));
+    if let Some(p) = explicit {
+        text = text
+            .strip_suffix(p.as_str())
+            .map(|t| t.trim_end().trim_end_matches(','))
+            .unwrap_or(text);
+    }
+    // ラベルがプロトコル名そのものであれば、プロトコルとして扱う
+    let label_protocol = protocol(text);
+    let protocol = explicit.or(label_protocol);
+    let label = (label_protocol.is_none() && !text.is_empty()).then(|| text.to_string());
+
+    let style = match attribute("communicationStyle").map(|s| s.as_str()) {
+        Some("sync") => Some(CommunicationStyle::Sync),
+        Some("async") => Some(CommunicationStyle::Async),
+        Some("stream") => Some(CommunicationStyle::Stream),
+        // 破線は非同期の接続として描かれることが多い
+        _ if cell.style.get("dashed").is_some_and(|v| v == "1") => Some(CommunicationStyle::Async),
+        _ => None,
+    };
+    let has_arrow = |key: &str, default: bool| {
+        cell.style
+            .get(key)
+            .map(|v| !v.is_empty() && v != "none")
+            .unwrap_or(default)
+    };
+    Edge {
+        id: Some(cell.id.clone()),
+        source: source.to_string(),
+        target: target.to_string(),
+        protocol,
+        style,
+        bidirectional: has_arrow("startArrow", false) && has_arrow("endArrow", true),
+        label,
+    }
+}
+
+fn protocol(text: &str) -> Option<Protocol> {
+    Protocol::parse(text).filter(|p| *p != Protocol::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::export;
+    use crate::domain::model::catalog::test_catalog;
+
+    fn assert_round_trip(result: &ImportResult) {
+        assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+        let diagram = &result.diagram;
+        let app = diagram.node("app").expect("app");
+        assert_eq!(app.type_label, "App Server");
+        assert_eq!(app.label.as_deref(), Some("API"));
+        assert_eq!(app.parent_node.as_deref(), Some("vpc"));
+        assert_eq!(app.properties.replicas, Some(3));
+        assert_eq!(
+            diagram.node("db").unwrap().parent_node.as_deref(),
+            Some("vpc")
+        );
+        assert_eq!(diagram.node("user").unwrap().parent_node, None);
+
+        let e2 = diagram.edges.iter().find(|e| e.source == "app").unwrap();
+        assert_eq!(e2.target, "db");
+        assert_eq!(e2.protocol, Some(Protocol::Sql));
+        assert_eq!(e2.label.as_deref(), Some("orders"));
+        assert_eq!(diagram.edges.len(), 2);
+    }
+
+    // draw.io の既定の保存形式: mxGraphModel を URL エンコード → raw deflate → Base64
+    fn compress(xml: &str) -> String {
+        let start = xml.find("<mxGraphModel").unwrap();
+        let end = xml.find("</mxGraphModel>").unwrap() + "</mxGraphModel>".len();
+        let encoded = percent_encoding::utf8_percent_encode(
+            &xml[start..end],
+            percent_encoding::NON_ALPHANUMERIC,
+        )
+        .to_string();
+        let deflated = miniz_oxide::deflate::compress_to_vec(encoded.as_bytes(), 6);
+        format!(
+            "<mxfile><diagram id=\"p1\" name=\"Page-1\">{}</diagram></mxfile>",
+            base64::engine::general_purpose::STANDARD.encode(deflated)
+        )
+    }
+
+    #[test]
+    fn uncompressed_round_trip() {
+        let catalog = test_catalog();
+        let xml = export::drawio::render(&export::sample(), &catalog);
+        assert_round_trip(&import(&xml, &catalog).unwrap());
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let catalog = test_catalog();
+        let xml = compress(&export::drawio::render(&export::sample(), &catalog));
+        assert_round_trip(&import(&xml, &catalog).unwrap());
+    }
+
+    #[test]
+    fn rejects_oversized_inflated_diagram() {
+        let zeros = vec![b'0'; MAX_INFLATED_BYTES + 1];
+        let deflated = miniz_oxide::deflate::compress_to_vec(&zeros, 6);
+        let xml = format!(
+            "<mxfile><diagram>{}</diagram></mxfile>",
+            base64::engine::general_purpose::STANDARD.encode(deflated)
+        );
+        let error = import(&xml, &test_catalog()).err().unwrap();
+        assert!(error.to_string().contains("大きすぎます"), "{}", error);
+    }
+}
@@ -0,0 +1,394 @@
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use super::container::{container_type, host_references, image_protocol};
+use super::{add_edge, allows_property, auto_layout, new_node, port_protocol};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{AutoScaling, Diagram, Node, Position, Protocol};
+use crate::domain::model::interchange::{ImportResult, SkippedItem};
+
+// Kubernetes のマニフェスト (複数ドキュメントの YAML、または kind: List) から設計図を取り込む
+// ワークロードをイメージ名から推定したコンポーネントに、Service のセレクターと
+// 環境変数・引数中の Service 名への参照を接続に、Ingress / LoadBalancer の Service を
+// ロードバランサーにする
+
+const WORKLOADS: [&str; 7] = [
+    "Deployment",
+    "StatefulSet",
+    "DaemonSet",
+    "ReplicaSet",
+    "Pod",
+    "Job",
+    "CronJob",
+];
+// 構成図には現れず、取り込めなかったものとして報告しないリソース
+const SUPPORTING: [&str; 14] = [
+    "ConfigMap",
+    "Secret",
+    "Namespace",
+    "ServiceAccount",
+    "Role",
+    "RoleBinding",
+    "ClusterRole",
+    "ClusterRoleBinding",
+    "PersistentVolume",
+    "PersistentVolumeClaim",
+    "NetworkPolicy",
+    "PodDisruptionBudget",
+    "HorizontalPodAutoscaler",
+    "Endpoints",
+];
+
+struct Workload<'a> {
+    id: String,
+    kind: &'a str,
+    name: &'a str,
+    namespace: &'a str,
+    // Pod テンプレートのラベル (Service のセレクターとの照合に使う)
+    labels: &'a Value,
+    pod: &'a Value,
+    // replicas / parallelism を持つ、Pod テンプレートの外側の spec
+    spec: &'a Value,
+}
+
+struct Service<'a> {
+    name: &'a str,
+    namespace: &'a str,
+    manifest: &'a Value,
+    // セレクターに一致するワークロードのID
+    targets: Vec<String>,
+}
+
+pub fn import(
+    yaml: &str,
+    catalog: &ComponentCatalog,
+) -> Result<ImportResult, Box<dyn std::error::Error>> {
+    let mut manifests = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(yaml) {
+        let value = Value::deserialize(document)?;
+        if value["kind"].as_str() == Some("List") {
+            manifests.extend(value["items"].as_sequence().into_iter().flatten().cloned());
+        } else if !value.is_null() {
+            manifests.push(value);
+        }
+    }
+    if manifests.iter().all(|m| m["kind"].as_str().is_none()) {
+        return Err("kind を持つマニフェストがありません".into());
+    }
+
+    let mut skipped = Vec::new();
+    let mut workloads = Vec::new();
+    for manifest in &manifests {
+        let kind = manifest["kind"].as_str().unwrap_or("");
+        let name = manifest["metadata"]["name"].as_str().unwrap_or("");
+        let namespace = namespace(manifest);
+        if WORKLOADS.contains(&kind) {
+            let (spec, template) = match kind {
+                "Pod" => (&Value::Null, manifest),
+                "CronJob" => {
+                    let spec = &manifest["spec"]["jobTemplate"]["spec"];
+                    (spec, &spec["template"])
+                }
+                _ => (&manifest["spec"], &manifest["spec"]["template"]),
+            };
+            workloads.push(Workload {
+                id: resource_id(namespace, kind, name),
+                kind,
+                name,
+                namespace,
+                labels: &template["metadata"]["labels"],
+                pod: &template["spec"],
+                spec,
+            });
+        } else if !matches!(kind, "Service" | "Ingress") && !SUPPORTING.contains(&kind) {
+            skipped.push(SkippedItem::new(
+                resource_id(namespace, kind, name),
+                format!("「{}」は取り込みの対象外です", kind),
+            ));
+        }
+    }
+
+    let mut services = Vec::new();
+    for manifest in manifests
+        .iter()
+        .filter(|m| m["kind"].as_str() == Some("Service"))
+    {
+        let name = manifest["metadata"]["name"].as_str().unwrap_or("");
+        let namespace = namespace(manifest);
+        let selector = &manifest["spec"]["selector"];
+        let targets = workloads
+            .iter()
+            .filter(|w| w.namespace == namespace && selects(selector, w.labels))
+            .map(|w| w.id.clone())
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            let reason = match manifest["spec"]["type"].as_str() {
+                Some("ExternalName") => "外部のサービスを指す ExternalName のため取り込みません",
+                _ => "セレクターに一致するワークロードがありません",
+            };
+            skipped.push(SkippedItem::new(
+                resource_id(namespace, "Service", name),
+                reason,
+            ));
+        }
+        services.push(Service {
+            name,
+            namespace,
+            manifest,
+            targets,
+        });
+    }
+
+    let mut nodes: Vec<Node> = workloads
+        .iter()
+        .map(|w| workload_node(w, catalog))
+        .collect();
+    apply_autoscalers(&manifests, &mut nodes, catalog);
+
+    let mut edges = Vec::new();
+    // 外部からの入口 (Ingress と type: LoadBalancer の Service)
+    for manifest in manifests
+        .iter()
+        .filter(|m| m["kind"].as_str() == Some("Ingress"))
+    {
+        let name = manifest["metadata"]["name"].as_str().unwrap_or("");
+        let namespace = namespace(manifest);
+        let id = resource_id(namespace, "Ingress", name);
+        let protocol = if manifest["spec"]["tls"].is_sequence() {
+            Protocol::Https
+        } else {
+            Protocol::Http
+        };
+        nodes.push(entry_node(&id, name));
+        for backend in ingress_backends(&manifest["spec"]) {
+            for service in services
+                .iter()
+                .filter(|s| s.namespace == namespace && s.name == backend)
+            {
+                for target in &service.targets {
+                    add_edge(&mut edges, &id, target, Some(protocol));
+                }
+            }
+        }
+    }
+    for service in &services {
+        if service.manifest["spec"]["type"].as_str() != Some("LoadBalancer") {
+            continue;
+        }
+        let id = resource_id(service.namespace, "Service", service.name);
+        nodes.push(entry_node(&id, service.name));
+        for target in &service.targets {
+            add_edge(&mut edges, &id, target, service_protocol(service.manifest));
+        }
+    }
+
+    // ワークロードから Service 名で参照している先 (例: DATABASE_URL=postgres://db:5432/app)
+    for workload in &workloads {
+        for text in texts(workload.pod) {
+            for reference in host_references(&text) {
+                let Some(service) = services.iter().find(|s| {
+                    s.name == reference.host
+                        && (s.namespace == workload.namespace
+                            || text.contains(&format!("{}.{}", s.name, s.namespace)))
+                }) else {
+                    continue;
+                };
+                for target in &service.targets {
+                    let image = workloads
+                        .iter()
+                        .find(|w| &w.id == target)
+                        .and_then(|w| first_image(w.pod));
+                    let protocol = reference
+                        .protocol
+                        .or(image_protocol(image))
+                        .or(service_protocol(service.manifest));
+                    add_edge(&mut edges, &workload.id, target, protocol);
+                }
+            }
+        }
+    }
+    for (i, edge) in edges.iter_mut().enumerate() {
+        edge.id = Some(format!("edge-{}", i + 1));
+    }
+
+    let mut diagram = Diagram { nodes, edges };
+    auto_layout(&mut diagram);
+    Ok(ImportResult { diagram, skipped })
+}
+
+fn workload_node(workload: &Workload, catalog: &ComponentCatalog) -> Node {
+    let image = first_image(workload.pod);
+    let type_label = match workload.kind {
+        "Job" | "CronJob" => "Batch Job",
+        _ => container_type(image, workload.name),
+    };
+    let mut node = new_node(
+        workload.id.clone(),
+        type_label,
+        Some(workload.name.to_string()),
+        Position { x: 0.0, y: 0.0 },
+    );
+    let replicas = match workload.kind {
+        "Job" | "CronJob" => workload.spec["parallelism"].as_u64(),
+        _ => workload.spec["replicas"].as_u64(),
+    };
+    if allows_property(catalog, type_label, "replicas") {
+        node.properties.replicas = replicas.map(|n| n as u32);
+    }
+    node
+}
+
+// HorizontalPodAutoscaler の対象に autoscaling を設定する
+fn apply_autoscalers(manifests: &[Value], nodes: &mut [Node], catalog: &ComponentCatalog) {
+    for manifest in manifests
+        .iter()
+        .filter(|m| m["kind"].as_str() == Some("HorizontalPodAutoscaler"))
+    {
+        let spec = &manifest["spec"];
+        let target = &spec["scaleTargetRef"];
+        let (Some(kind), Some(name), Some(max)) = (
+            target["kind"].as_str(),
+            target["name"].as_str(),
+            spec["maxReplicas"].as_u64(),
+        ) else {
+            continue;
+        };
+        let id = resource_id(namespace(manifest), kind, name);
+        if let Some(node) = nodes.iter_mut().find(|n| n.id == id)
+            && allows_property(catalog, &node.type_label, "autoscaling")
+        {
+            node.properties.autoscaling = Some(AutoScaling {
+                min: spec["minReplicas"].as_u64().unwrap_or(1) as u32,
+                max: max as u32,
+            });
+        }
+    }
+}
+
+fn entry_node(id: &str, name: &str) -> Node {
+    new_node(
+        id.to_string(),
+        "Load Balancer",
+        Some(name.to_string()),
+        Position { x: 0.0, y: 0.0 },
+    )
+}
+
+// Ingress が振り分ける Service の名前 (networking.k8s.io/v1 と旧形式の両方)
+fn ingress_backends<'a>(spec: &'a Value) -> Vec<&'a str> {
+    let mut backends = Vec::new();
+    let mut push = |backend: &'a Value| {
+        let name = backend["service"]["name"]
+            .as_str()
+            .or_else(|| backend["serviceName"].as_str());
+        if let Some(name) = name
+            && !backends.contains(&name)
+        {
+            backends.push(name);
+        }
+    };
+    push(&spec["defaultBackend"]);
+    push(&spec["backend"]);
+    for rule in spec["rules"].as_sequence().into_iter().flatten() {
+        for path in rule["http"]["paths"].as_sequence().into_iter().flatten() {
+            push(&path["backend"]);
+        }
+    }
+    backends
+}
+
+// Service の最初のポートの名前・appProtocol・番号から推定したプロトコル
+fn service_protocol(service: &Value) -> Option<Protocol> {
+    let port = service["spec"]["ports"].get(0)?;
+    let hint = port["appProtocol"]
+        .as_str()
+        .or_else(|| port["name"].as_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if hint.contains("grpc") {
+        Some(Protocol::Grpc)
+    } else if hint.contains("https") {
+        Some(Protocol::Https)
+    } else if hint.contains("http") {
+        Some(Protocol::Http)
+    } else {
+        port["port"].as_i64().and_then(port_protocol)
+    }
+}
+
+// セレクターのラベルがすべて Pod のラベルに含まれるか
+fn selects(selector: &Value, labels: &Value) -> bool {
+    match selector.as_mapping() {
+        Some(selector) if !selector.is_empty() => {
+            selector.iter().all(|(key, value)| &labels[key] == value)
+        }
+        _ => false,
+    }
+}
+
+fn first_image(pod: &Value) -> Option<&str> {
+    pod["containers"].get(0)?["image"].as_str()
+}
+
+// 接続先のホスト名が書かれうる文字列 (コンテナの環境変数の値・コマンド・引数)
+fn texts(pod: &Value) -> Vec<String> {
+    let mut texts = Vec::new();
+    for container in pod["containers"].as_sequence().into_iter().flatten() {
+        for env in container["env"].as_sequence().into_iter().flatten() {
+            texts.extend(env["value"].as_str().map(str::to_string));
+        }
+        for key in ["command", "args"] {
+            for arg in container[key].as_sequence().into_iter().flatten() {
+                texts.extend(arg.as_str().map(str::to_string));
+            }
+        }
+    }
+    texts
+}
+
+fn namespace(manifest: &Value) -> &str {
+    manifest["metadata"]["namespace"]
+        .as_str()
+        .unwrap_or("default")
+}
+
+// 例: "deployment/api"、default 以外の名前空間では "shop/deployment/api"
+fn resource_id(namespace: &str, kind: &str, name: &str) -> String {
+    let local = format!("{}/{}", kind.to_lowercase(), name);
+    if namespace == "default" {
+        local
+    } else {
+        format!("{}/{}", namespace, local)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+
+    const MANIFESTS: &str = include_str!("fixtures/kubernetes.yaml");
+
+    #[test]
+    fn imports_workloads_without_false_edges() {
+        let result = import(MANIFESTS, &test_catalog()).unwrap();
+        assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+        let diagram = &result.diagram;
+        let type_of = |id: &str| diagram.node(id).map(|n| n.type_label.as_str());
+        assert_eq!(type_of("deployment/app"), Some("App Server"));
+        assert_eq!(type_of("deployment/worker"), Some("Worker (Async)"));
+        assert_eq!(type_of("statefulset/db"), Some("RDBMS (SQL)"));
+        assert_eq!(type_of("deployment/redis-commander"), Some("App Server"));
+
+        let edges: Vec<(&str, &str, Option<Protocol>)> = diagram
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.protocol))
+            .collect();
+        // db の POSTGRES_USER: app や worker の引数 "app" は Service への参照ではない
+        assert_eq!(
+            edges,
+            vec![("deployment/app", "statefulset/db", Some(Protocol::Sql))]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+pub mod compose;
+mod container;
+pub mod drawio;
+pub mod kubernetes;
+pub mod terraform;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::containment::GroupKind;
+use crate::domain::model::diagram::{Diagram, Edge, Node, NodeProperties, Position, Protocol};
+use crate::domain::model::interchange::ImportResult;
+
+// 外部ツールの形式から設計図 (Diagram) を組み立てる際の共通処理
+
+// 各形式の取り込み (drawio::import / terraform::import / compose::import / kubernetes::import)
+pub type Importer = fn(&str, &ComponentCatalog) -> Result<ImportResult, Box<dyn std::error::Error>>;
+
+pub fn new_node(id: String, type_label: &str, label: Option<String>, position: Position) -> Node {
+    Node {
+        id,
+        type_label: type_label.to_string(),
+        // 型名と同じラベルは持たない (表示名は型名で足りる)
+        label: label.filter(|l| !l.is_empty() && l != type_label),
+        description: None,
+        position,
+        width: None,
+        height: None,
+        parent_node: None,
+        properties: NodeProperties::default(),
+    }
+}
+
+// コンポーネントの種類ごとに許可されたプロパティか (architecture_defs.json の properties)
+fn allows_property(catalog: &ComponentCatalog, type_label: &str, property: &str) -> bool {
+    catalog
+        .get(type_label)
+        .is_some_and(|d| d.semantics.properties.iter().any(|p| p == property))
+}
+
+// 同じ向きの接続は1本にまとめる (先に見つかったプロトコルを残す)
+fn add_edge(edges: &mut Vec<Edge>, source: &str, target: &str, protocol: Option<Protocol>) {
+    if source == target
+        || edges
+            .iter()
+            .any(|e| e.source == source && e.target == target)
+    {
+        return;
+    }
+    edges.push(Edge {
+        id: None,
+        source: source.to_string(),
+        target: target.to_string(),
+        protocol,
+        style: None,
+        bidirectional: false,
+        label: None,
+    });
+}
+
+// よく使われるポート番号から推定するプロトコル
+fn port_protocol(port: i64) -> Option<Protocol> {
+    match port {
+        443 | 8443 => Some(Protocol::Https),
+        80 | 8000 | 8080 | 3000 | 5173 => Some(Protocol::Http),
+        50051 => Some(Protocol::Grpc),
+        5432 | 3306 | 1433 | 1521 => Some(Protocol::Sql),
+        5672 => Some(Protocol::Amqp),
+        53 => Some(Protocol::Dns),
+        // 0 / -1 はすべてのポートを許可する規則
+        p if p > 0 => Some(Protocol::Tcp),
+        _ => None,
+    }
+}
+
+// 名前から Web / ワーカーを見分け、それ以外はアプリケーションサーバーとみなす
+fn compute_type(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    if ["web", "nginx", "frontend"]
+        .iter()
+        .any(|k| name.contains(k))
+    {
+        "Web Server"
+    } else if ["worker", "consumer", "job"]
+        .iter()
+        .any(|k| name.contains(k))
+    {
+        "Worker (Async)"
+    } else {
+        "App Server"
+    }
+}
+
+// 座標を持たない形式 (Terraform・docker-compose 等) から取り込んだ設計図の配置
+// グループごとに、接続の上流から順に格子状に並べ、グループは中身が収まる大きさにする
+const NODE_SIZE: (f64, f64) = (150.0, 40.0);
+const EMPTY_GROUP_SIZE: (f64, f64) = (300.0, 200.0);
+const SPACING: (f64, f64) = (60.0, 60.0);
+const GROUP_PADDING: f64 = 30.0;
+// グループ名を表示する上端の余白
+const GROUP_HEADER: f64 = 40.0;
+
+type Placement = (Position, Option<(f64, f64)>);
+
+pub fn auto_layout(diagram: &mut Diagram) {
+    let ranks = ranks(diagram);
+    let ids: HashSet<&str> = diagram.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut children: HashMap<Option<&str>, Vec<usize>> = HashMap::new();
+    for (i, node) in diagram.nodes.iter().enumerate() {
+        let parent = node.parent_node.as_deref().filter(|p| ids.contains(p));
+        children.entry(parent).or_default().push(i);
+    }
+    for items in children.values_mut() {
+        items.sort_by_key(|&i| (ranks.get(diagram.nodes[i].id.as_str()).copied(), i));
+    }
+
+    let mut placed: HashMap<usize, Placement> = HashMap::new();
+    place(diagram, &children, None, 0, &mut placed);
+    for (i, (position, size)) in placed {
+        let node = &mut diagram.nodes[i];
+        node.position = position;
+        if let Some((width, height)) = size {
+            node.width = Some(width);
+            node.height = Some(height);
+        }
+    }
+}
+
+// parent の子を並べ、parent の大きさを返す
+fn place(
+    diagram: &Diagram,
+    children: &HashMap<Option<&str>, Vec<usize>>,
+    parent: Option<&str>,
+    depth: usize,
+    placed: &mut HashMap<usize, Placement>,
+) -> (f64, f64) {
+    let items = children.get(&parent).map(|c| c.as_slice()).unwrap_or(&[]);
+    let (origin_x, origin_y) = if parent.is_some() {
+        (GROUP_PADDING, GROUP_HEADER)
+    } else {
+        (0.0, 0.0)
+    };
+    let columns = (items.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let (mut x, mut y) = (origin_x, origin_y);
+    let (mut right, mut row_height) = (origin_x, 0.0_f64);
+    for (n, &i) in items.iter().enumerate() {
+        if n > 0 && n % columns == 0 {
+            x = origin_x;
+            y += row_height + SPACING.1;
+            row_height = 0.0;
+        }
+        let node = &diagram.nodes[i];
+        let is_group = GroupKind::from_type(&node.type_label).is_some();
+        // 親子関係が循環していても止まるよう、深さを制限する
+        let has_children = children.contains_key(&Some(node.id.as_str()));
+        let size = if has_children && depth < diagram.nodes.len() {
+            place(diagram, children, Some(node.id.as_str()), depth + 1, placed)
+        } else if is_group {
+            EMPTY_GROUP_SIZE
+        } else {
+            NODE_SIZE
+        };
+        placed.insert(i, (Position { x, y }, is_group.then_some(size)));
+        right = right.max(x + size.0);
+        row_height = row_height.max(size.1);
+        x += size.0 + SPACING.0;
+    }
+    (
+        (right + GROUP_PADDING).max(EMPTY_GROUP_SIZE.0),
+        (y + row_height + GROUP_PADDING).max(EMPTY_GROUP_SIZE.1),
+    )
+}
+
+// 接続元から数えた段数 (循環があっても、段数はノード数未満で打ち切る)
+fn ranks(diagram: &Diagram) -> HashMap<&str, usize> {
+    let limit = diagram.nodes.len();
+    let mut ranks: HashMap<&str, usize> =
+        diagram.nodes.iter().map(|n| (n.id.as_str(), 0)).collect();
+    for _ in 0..limit {
+        let mut changed = false;
+        for edge in &diagram.edges {
+            let (Some(&from), Some(&to)) = (
+                ranks.get(edge.source.as_str()),
+                ranks.get(edge.target.as_str()),
+            ) else {
+                continue;
+            };
+            if to <= from && from + 1 < limit {
+                ranks.insert(edge.target.as_str(), from + 1);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    ranks
+}
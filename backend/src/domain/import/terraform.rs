@@ -0,0 +1,1123 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{add_edge, allows_property, auto_layout, compute_type, new_node, port_protocol};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::diagram::{
+    AutoScaling, Diagram, Edge, Node, NodeProperties, Position, Protocol,
+};
+use crate::domain::model::interchange::{ImportResult, SkippedItem};
+
+// `terraform show -json` の出力 (state / plan のどちらでもよい) から設計図を取り込む
+// リソースをカタログのコンポーネントに、VPC・サブネットをグループに対応付け、
+// セキュリティグループの許可やターゲットグループ等の参照を接続として復元する
+// plan では未確定の値 (ID 等) が含まれないため、configuration の参照式でも関係を辿る
+
+const SECURITY_GROUPS: [&str; 1] = ["aws_security_group"];
+const SECURITY_GROUP_KEYS: [&str; 3] = [
+    "vpc_security_group_ids",
+    "security_groups",
+    "security_group_ids",
+];
+const SUBNET_KEYS: [&str; 4] = ["subnet_id", "subnet_ids", "subnets", "vpc_zone_identifier"];
+const SUBNET_GROUP_KEYS: [&str; 2] = ["db_subnet_group_name", "subnet_group_name"];
+const SUBNET_GROUPS: [&str; 4] = [
+    "aws_db_subnet_group",
+    "aws_elasticache_subnet_group",
+    "aws_docdb_subnet_group",
+    "aws_neptune_subnet_group",
+];
+const LOAD_BALANCERS: [&str; 3] = ["aws_lb", "aws_alb", "aws_elb"];
+const TARGET_GROUPS: [&str; 2] = ["aws_lb_target_group", "aws_alb_target_group"];
+const LAUNCH_TEMPLATES: [&str; 2] = ["aws_launch_template", "aws_launch_configuration"];
+// Lambda の環境変数から参照されていれば、接続とみなすデータストア等
+const BACKING_SERVICES: [&str; 11] = [
+    "aws_db_instance",
+    "aws_rds_cluster",
+    "aws_dynamodb_table",
+    "aws_docdb_cluster",
+    "aws_neptune_cluster",
+    "aws_s3_bucket",
+    "aws_opensearch_domain",
+    "aws_elasticache_cluster",
+    "aws_elasticache_replication_group",
+    "aws_sqs_queue",
+    "aws_sns_topic",
+];
+
+// 値から参照先を引くために索引を作る属性
+const INDEXED_KEYS: [&str; 14] = [
+    "id",
+    "arn",
+    "invoke_arn",
+    "execution_arn",
+    "dns_name",
+    "domain_name",
+    "bucket_regional_domain_name",
+    "bucket_domain_name",
+    "url",
+    "endpoint",
+    "address",
+    "api_endpoint",
+    "identifier",
+    "cluster_identifier",
+];
+// 名前で参照されることが多く、name も索引に含めるリソース
+const NAMED: [&str; 6] = [
+    "aws_autoscaling_group",
+    "aws_dynamodb_table",
+    "aws_cloudwatch_event_bus",
+    "aws_cloudwatch_event_rule",
+    "aws_lambda_function",
+    "aws_sqs_queue",
+];
+
+// ノードにはならず、他のリソースに属するもの (リソースの種類, 所属先を示す属性, 所属先の種類)
+// 所属先のノードへの参照として扱う (例: リスナー → ロードバランサー)
+const OWNED: [(&str, &[&str], &[&str]); 11] = [
+    ("aws_lb_listener", &["load_balancer_arn"], &LOAD_BALANCERS),
+    ("aws_alb_listener", &["load_balancer_arn"], &LOAD_BALANCERS),
+    (
+        "aws_lb_listener_rule",
+        &["listener_arn"],
+        &["aws_lb_listener", "aws_alb_listener"],
+    ),
+    (
+        "aws_apigatewayv2_stage",
+        &["api_id"],
+        &["aws_apigatewayv2_api"],
+    ),
+    (
+        "aws_api_gateway_stage",
+        &["rest_api_id"],
+        &["aws_api_gateway_rest_api"],
+    ),
+    (
+        "aws_rds_cluster_instance",
+        &["cluster_identifier"],
+        &["aws_rds_cluster"],
+    ),
+    (
+        "aws_docdb_cluster_instance",
+        &["cluster_identifier"],
+        &["aws_docdb_cluster"],
+    ),
+    (
+        "aws_neptune_cluster_instance",
+        &["cluster_identifier"],
+        &["aws_neptune_cluster"],
+    ),
+    // リードレプリカは元のデータベースの readReplicas として数える
+    (
+        "aws_db_instance",
+        &["replicate_source_db"],
+        &["aws_db_instance"],
+    ),
+    (
+        "aws_cloudwatch_event_rule",
+        &["event_bus_name"],
+        &["aws_cloudwatch_event_bus"],
+    ),
+    (
+        "aws_autoscaling_attachment",
+        &["autoscaling_group_name"],
+        &["aws_autoscaling_group"],
+    ),
+];
+
+// 接続を表すリソース (リソースの種類, 接続元の属性, 接続先の属性, 接続先の種類の絞り込み, プロトコル)
+// 属性が空の場合はリソース自身のノードを指す
+type Wiring = (
+    &'static str,
+    &'static [&'static str],
+    &'static [&'static str],
+    &'static [&'static str],
+    Option<Protocol>,
+);
+const WIRINGS: [Wiring; 12] = [
+    (
+        "aws_cloudfront_distribution",
+        &[],
+        &["origin"],
+        &[],
+        Some(Protocol::Https),
+    ),
+    (
+        "aws_cloudfront_distribution",
+        &["web_acl_id"],
+        &[],
+        &[],
+        None,
+    ),
+    ("aws_route53_record", &["zone_id"], &["alias"], &[], None),
+    (
+        "aws_wafv2_web_acl_association",
+        &["web_acl_arn"],
+        &["resource_arn"],
+        &[],
+        None,
+    ),
+    (
+        "aws_apigatewayv2_integration",
+        &["api_id"],
+        &["integration_uri"],
+        &[],
+        Some(Protocol::Https),
+    ),
+    (
+        "aws_api_gateway_integration",
+        &["rest_api_id"],
+        &["uri"],
+        &[],
+        Some(Protocol::Https),
+    ),
+    (
+        "aws_lambda_event_source_mapping",
+        &["event_source_arn"],
+        &["function_name"],
+        &["aws_lambda_function"],
+        None,
+    ),
+    (
+        "aws_sns_topic_subscription",
+        &["topic_arn"],
+        &["endpoint"],
+        &[],
+        None,
+    ),
+    (
+        "aws_cloudwatch_event_target",
+        &["event_bus_name", "rule"],
+        &["arn"],
+        &[],
+        None,
+    ),
+    (
+        "aws_lambda_permission",
+        &["source_arn"],
+        &["function_name"],
+        &["aws_lambda_function"],
+        None,
+    ),
+    (
+        "aws_route53_health_check",
+        &[],
+        &["fqdn", "ip_address"],
+        &[],
+        None,
+    ),
+    (
+        "aws_s3_bucket_notification",
+        &["bucket"],
+        &["queue", "topic", "lambda_function"],
+        &[],
+        None,
+    ),
+];
+
+// ノードにならず、関係を辿るためだけに使う (取り込めなかったものとして報告しない) リソース
+const SUPPORTING: [&str; 24] = [
+    "aws_security_group",
+    "aws_security_group_rule",
+    "aws_vpc_security_group_ingress_rule",
+    "aws_vpc_security_group_egress_rule",
+    "aws_lb_target_group",
+    "aws_alb_target_group",
+    "aws_lb_target_group_attachment",
+    "aws_launch_template",
+    "aws_launch_configuration",
+    "aws_db_subnet_group",
+    "aws_elasticache_subnet_group",
+    "aws_docdb_subnet_group",
+    "aws_neptune_subnet_group",
+    "aws_internet_gateway",
+    "aws_nat_gateway",
+    "aws_eip",
+    "aws_route",
+    "aws_ecs_cluster",
+    "aws_ecs_task_definition",
+    "aws_batch_compute_environment",
+    "aws_batch_job_queue",
+    "aws_cloudwatch_event_target",
+    "aws_sqs_queue_policy",
+    "aws_sns_topic_policy",
+];
+// 同じく、名前の先頭で判定する付随リソースの系統
+const SUPPORTING_PREFIXES: [&str; 10] = [
+    "aws_iam_",
+    "aws_s3_bucket_",
+    "aws_api_gateway_",
+    "aws_apigatewayv2_",
+    "aws_route_table",
+    "aws_kms_",
+    "aws_acm_",
+    "aws_cloudfront_origin_",
+    "aws_lambda_",
+    "aws_sns_topic_",
+];
+
+struct Resource<'a> {
+    address: String,
+    // count / for_each の添字を除いたアドレス (同じ定義の複数インスタンスは1つのノードにまとめる)
+    base: String,
+    kind: &'a str,
+    name: &'a str,
+    values: &'a Value,
+}
+
+// configuration 側のリソース定義 (モジュール内の参照を解決するためのアドレスの接頭辞と参照式)
+struct Expressions<'a> {
+    prefix: String,
+    expressions: &'a Value,
+}
+
+pub fn import(
+    json: &str,
+    catalog: &ComponentCatalog,
+) -> Result<ImportResult, Box<dyn std::error::Error>> {
+    let doc: Value = serde_json::from_str(json)?;
+    let root = doc
+        .pointer("/planned_values/root_module")
+        .or_else(|| doc.pointer("/values/root_module"))
+        .ok_or(
+            "values / planned_values がありません (terraform show -json の出力を指定してください)",
+        )?;
+    let mut resources = Vec::new();
+    collect_resources(root, &mut resources);
+    let mut expressions = HashMap::new();
+    if let Some(config) = doc.pointer("/configuration/root_module") {
+        collect_expressions(config, String::new(), &mut expressions);
+    }
+    Ok(Importer::new(resources, expressions, catalog).run())
+}
+
+fn collect_resources<'a>(module: &'a Value, out: &mut Vec<Resource<'a>>) {
+    for resource in module["resources"].as_array().into_iter().flatten() {
+        if resource["mode"].as_str() == Some("data") {
+            continue;
+        }
+        let (Some(address), Some(kind), Some(name)) = (
+            resource["address"].as_str(),
+            resource["type"].as_str(),
+            resource["name"].as_str(),
+        ) else {
+            continue;
+        };
+        let local = format!("{}.{}", kind, name);
+        let base = match address.rfind(&local) {
+            Some(pos) => address[..pos + local.len()].to_string(),
+            None => address.to_string(),
+        };
+        out.push(Resource {
+            address: address.to_string(),
+            base,
+            kind,
+            name,
+            values: &resource["values"],
+        });
+    }
+    for child in module["child_modules"].as_array().into_iter().flatten() {
+        collect_resources(child, out);
+    }
+}
+
+fn collect_expressions<'a>(
+    module: &'a Value,
+    prefix: String,
+    out: &mut HashMap<String, Expressions<'a>>,
+) {
+    for resource in module["resources"].as_array().into_iter().flatten() {
+        if resource["mode"].as_str() == Some("data") {
+            continue;
+        }
+        if let Some(address) = resource["address"].as_str() {
+            out.insert(
+                format!("{}{}", prefix, address),
+                Expressions {
+                    prefix: prefix.clone(),
+                    expressions: &resource["expressions"],
+                },
+            );
+        }
+    }
+    for (name, call) in module["module_calls"].as_object().into_iter().flatten() {
+        collect_expressions(&call["module"], format!("{}module.{}.", prefix, name), out);
+    }
+}
+
+struct Importer<'a> {
+    resources: Vec<Resource<'a>>,
+    expressions: HashMap<String, Expressions<'a>>,
+    catalog: &'a ComponentCatalog,
+    // 属性値 (ID・ARN・DNS名等) → リソースの位置
+    by_value: HashMap<&'a str, usize>,
+    // リソースの位置 → そのリソースを表す (または所属先の) ノードのID
+    owners: HashMap<usize, String>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    skipped: Vec<SkippedItem>,
+}
+
+impl<'a> Importer<'a> {
+    fn new(
+        resources: Vec<Resource<'a>>,
+        expressions: HashMap<String, Expressions<'a>>,
+        catalog: &'a ComponentCatalog,
+    ) -> Self {
+        let mut by_value = HashMap::new();
+        for (i, resource) in resources.iter().enumerate() {
+            let named = NAMED.contains(&resource.kind);
+            for (key, value) in resource.values.as_object().into_iter().flatten() {
+                let indexed = INDEXED_KEYS.contains(&key.as_str())
+                    || (named && (key == "name" || key == "function_name"));
+                if let (true, Some(text)) = (indexed, value.as_str())
+                    && !text.is_empty()
+                {
+                    by_value.entry(text).or_insert(i);
+                }
+            }
+        }
+        Self {
+            resources,
+            expressions,
+            catalog,
+            by_value,
+            owners: HashMap::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> ImportResult {
+        self.networks();
+        self.components();
+        self.owned();
+        self.security_group_edges();
+        self.load_balancer_edges();
+        self.wiring_edges();
+        for (i, edge) in self.edges.iter_mut().enumerate() {
+            edge.id = Some(format!("edge-{}", i + 1));
+        }
+
+        let mut diagram = Diagram {
+            nodes: self.nodes,
+            edges: self.edges,
+        };
+        auto_layout(&mut diagram);
+        ImportResult {
+            diagram,
+            skipped: self.skipped,
+        }
+    }
+
+    // VPC とサブネット、サブネットの AZ ごとの Availability Zone グループ
+    fn networks(&mut self) {
+        for i in self.indexes_of(&["aws_vpc"]) {
+            let resource = &self.resources[i];
+            let node = new_node(
+                resource.address.clone(),
+                "VPC (Network)",
+                Some(label(resource)),
+                origin(),
+            );
+            self.owners.insert(i, node.id.clone());
+            self.nodes.push(node);
+        }
+
+        let mut zones: HashSet<String> = HashSet::new();
+        for i in self.indexes_of(&["aws_subnet"]) {
+            let vpc = self.linked(i, &["vpc_id"], &["aws_vpc"]).into_iter().next();
+            let resource = &self.resources[i];
+            let zone = resource.values["availability_zone"]
+                .as_str()
+                .or_else(|| resource.values["availability_zone_id"].as_str())
+                .unwrap_or("(AZ 未確定)")
+                .to_string();
+            let zone_id = format!("az:{}:{}", vpc.as_deref().unwrap_or("-"), zone);
+            if zones.insert(zone_id.clone()) {
+                let mut az = new_node(zone_id.clone(), "Availability Zone", Some(zone), origin());
+                az.parent_node = vpc;
+                self.nodes.push(az);
+            }
+
+            let mut node = new_node(
+                resource.address.clone(),
+                "Subnet",
+                Some(label(resource)),
+                origin(),
+            );
+            node.parent_node = Some(zone_id);
+            self.owners.insert(i, node.id.clone());
+            self.nodes.push(node);
+        }
+    }
+
+    // コンポーネントに対応するリソース (count / for_each のインスタンスは1つのノードにまとめる)
+    fn components(&mut self) {
+        let mut bases: Vec<&str> = Vec::new();
+        let mut instances: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, resource) in self.resources.iter().enumerate() {
+            if !instances.contains_key(resource.base.as_str()) {
+                bases.push(resource.base.as_str());
+            }
+            instances.entry(resource.base.as_str()).or_default().push(i);
+        }
+
+        let mut nodes = Vec::new();
+        for base in bases {
+            let members = &instances[base];
+            let first = &self.resources[members[0]];
+            // VPC・サブネットはグループとして取り込み済み
+            if self.owners.contains_key(&members[0]) {
+                continue;
+            }
+            let type_label = match self.component_type(members[0]) {
+                Some(t) if self.catalog.get(t).is_some() => t,
+                Some(_) => continue,
+                None => {
+                    if !self.is_supporting(members[0]) {
+                        self.skipped.push(SkippedItem::new(
+                            base,
+                            format!("「{}」に対応するコンポーネントがありません", first.kind),
+                        ));
+                    }
+                    continue;
+                }
+            };
+            let mut node = new_node(base.to_string(), type_label, Some(label(first)), origin());
+            node.properties = self.properties(members, type_label);
+            node.parent_node = self.placement(members);
+            nodes.push((members.clone(), node));
+        }
+        for (members, node) in nodes {
+            for i in members {
+                self.owners.insert(i, node.id.clone());
+            }
+            self.nodes.push(node);
+        }
+    }
+
+    fn component_type(&self, i: usize) -> Option<&'static str> {
+        let resource = &self.resources[i];
+        let type_label = match resource.kind {
+            "aws_instance" | "aws_autoscaling_group" | "aws_ecs_service" => {
+                compute_type(&label(resource))
+            }
+            "aws_lambda_function" => "Function (Serverless)",
+            "aws_lb" | "aws_alb" | "aws_elb" => "Load Balancer",
+            "aws_apigatewayv2_api" | "aws_api_gateway_rest_api" => "API Gateway",
+            "aws_cloudfront_distribution" => "CDN (CloudFront)",
+            "aws_route53_zone" => "DNS (Route53)",
+            "aws_wafv2_web_acl" | "aws_waf_web_acl" => "WAF (Firewall)",
+            "aws_batch_job_definition" => "Batch Job",
+            // リードレプリカは元のデータベースにまとめる
+            "aws_db_instance" if self.has(i, "replicate_source_db") => return None,
+            "aws_db_instance" | "aws_rds_cluster" => "RDBMS (SQL)",
+            "aws_dynamodb_table" => "NoSQL (KV)",
+            "aws_docdb_cluster" => "NoSQL (Doc)",
+            "aws_neptune_cluster" => "NoSQL (Graph)",
+            "aws_s3_bucket" => "Object Storage",
+            "aws_opensearch_domain" | "aws_elasticsearch_domain" => "Search Engine",
+            "aws_elasticache_cluster" | "aws_elasticache_replication_group" => "Distributed Cache",
+            "aws_sqs_queue" => "Message Queue",
+            "aws_sns_topic" if label(resource).to_lowercase().contains("alert") => "Alert Manager",
+            "aws_sns_topic" => "Pub/Sub",
+            "aws_cloudwatch_event_bus" => "Event Bus",
+            "aws_cloudwatch_log_group" => "Log Aggregator",
+            "aws_route53_health_check" => "Health Checker",
+            _ => return None,
+        };
+        Some(type_label)
+    }
+
+    fn is_supporting(&self, i: usize) -> bool {
+        let kind = self.resources[i].kind;
+        SUPPORTING.contains(&kind)
+            || SUPPORTING_PREFIXES.iter().any(|p| kind.starts_with(p))
+            || OWNED.iter().any(|(k, _, _)| *k == kind)
+            || WIRINGS.iter().any(|(k, ..)| *k == kind)
+    }
+
+    fn properties(&self, members: &[usize], type_label: &str) -> NodeProperties {
+        let i = members[0];
+        let values = self.resources[i].values;
+        let count = members.len() as u32;
+        let mut properties = NodeProperties::default();
+        match self.resources[i].kind {
+            "aws_instance" => {
+                properties.replicas = (count > 1).then_some(count);
+                properties.instance_class = text(&values["instance_type"]);
+            }
+            "aws_autoscaling_group" => {
+                if let (Some(min), Some(max)) =
+                    (values["min_size"].as_u64(), values["max_size"].as_u64())
+                {
+                    properties.autoscaling = Some(AutoScaling {
+                        min: min as u32,
+                        max: max as u32,
+                    });
+                }
+                properties.replicas = values["desired_capacity"].as_u64().map(|n| n as u32);
+                properties.instance_class = self
+                    .refs(
+                        i,
+                        &["launch_template", "launch_configuration"],
+                        &LAUNCH_TEMPLATES,
+                    )
+                    .into_iter()
+                    .find_map(|t| text(&self.resources[t].values["instance_type"]));
+            }
+            "aws_ecs_service" => {
+                properties.replicas = values["desired_count"].as_u64().map(|n| n as u32);
+            }
+            "aws_db_instance" => {
+                properties.instance_class = text(&values["instance_class"]);
+                properties.multi_az = values["multi_az"].as_bool();
+            }
+            "aws_elasticache_cluster" => {
+                properties.replicas = values["num_cache_nodes"].as_u64().map(|n| n as u32);
+                properties.instance_class = text(&values["node_type"]);
+            }
+            "aws_elasticache_replication_group" => {
+                properties.replicas = values["num_cache_clusters"].as_u64().map(|n| n as u32);
+                properties.instance_class = text(&values["node_type"]);
+                properties.multi_az = values["multi_az_enabled"].as_bool();
+            }
+            "aws_opensearch_domain" | "aws_elasticsearch_domain" => {
+                let cluster = values["cluster_config"].get(0).unwrap_or(&Value::Null);
+                properties.replicas = cluster["instance_count"].as_u64().map(|n| n as u32);
+                properties.instance_class = text(&cluster["instance_type"]);
+                properties.multi_az = cluster["zone_awareness_enabled"].as_bool();
+            }
+            _ => {}
+        }
+        // 複数の AZ のサブネットに配置されていれば Multi-AZ とみなす
+        if allows_property(self.catalog, type_label, "multiAz")
+            && properties.multi_az.is_none()
+            && self.zone_count(members) > 1
+        {
+            properties.multi_az = Some(true);
+        }
+        properties
+    }
+
+    // 配置先のサブネット (サブネットグループ経由を含む)
+    fn subnets(&self, members: &[usize]) -> Vec<String> {
+        let mut subnets = Vec::new();
+        for &i in members {
+            let mut found = self.linked(i, &SUBNET_KEYS, &["aws_subnet"]);
+            for group in self.refs(i, &SUBNET_GROUP_KEYS, &SUBNET_GROUPS) {
+                found.extend(self.linked(group, &["subnet_ids"], &["aws_subnet"]));
+            }
+            for subnet in found {
+                if !subnets.contains(&subnet) {
+                    subnets.push(subnet);
+                }
+            }
+        }
+        subnets
+    }
+
+    fn zone_count(&self, members: &[usize]) -> usize {
+        let zones: HashSet<Option<String>> = self
+            .subnets(members)
+            .iter()
+            .map(|s| self.parent_of(s))
+            .collect();
+        zones.len()
+    }
+
+    // 配置先のサブネットすべてを含む、最も内側のグループ
+    fn placement(&self, members: &[usize]) -> Option<String> {
+        let subnets = self.subnets(members);
+        let chains: Vec<Vec<String>> = subnets.iter().map(|s| self.chain(s)).collect();
+        let (first, rest) = chains.split_first()?;
+        first
+            .iter()
+            .find(|group| rest.iter().all(|c| c.contains(group)))
+            .cloned()
+    }
+
+    // id 自身と、その祖先のグループ (内側から順に)
+    fn chain(&self, id: &str) -> Vec<String> {
+        let mut chain = vec![id.to_string()];
+        while let Some(parent) = chain.last().and_then(|c| self.parent_of(c)) {
+            if chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+        }
+        chain
+    }
+
+    fn parent_of(&self, id: &str) -> Option<String> {
+        self.nodes
+            .iter()
+            .find(|n| n.id == id)
+            .and_then(|n| n.parent_node.clone())
+    }
+
+    // ノードにならないリソースを、所属先のノードに対応付ける
+    fn owned(&mut self) {
+        for (kind, keys, owners) in OWNED {
+            for i in self.indexes_of(&[kind]) {
+                if self.owners.contains_key(&i) {
+                    continue;
+                }
+                let Some(owner) = self.linked(i, keys, owners).into_iter().next() else {
+                    continue;
+                };
+                let Some(node) = self.nodes.iter_mut().find(|n| n.id == owner) else {
+                    continue;
+                };
+                match kind {
+                    "aws_db_instance" => {
+                        let replicas = node.properties.read_replicas.unwrap_or(0);
+                        node.properties.read_replicas = Some(replicas + 1);
+                    }
+                    // クラスターのインスタンスは、1台目をライター、残りをリーダーとして数える
+                    "aws_rds_cluster_instance"
+                    | "aws_docdb_cluster_instance"
+                    | "aws_neptune_cluster_instance" => {
+                        let properties = &mut node.properties;
+                        if properties.instance_class.is_none() {
+                            properties.instance_class =
+                                text(&self.resources[i].values["instance_class"]);
+                            properties.read_replicas = Some(0);
+                        } else {
+                            let replicas = properties.read_replicas.unwrap_or(0) + 1;
+                            properties.read_replicas = Some(replicas);
+                            properties.multi_az = Some(true);
+                        }
+                    }
+                    _ => {}
+                }
+                self.owners.insert(i, owner);
+            }
+        }
+        for node in &mut self.nodes {
+            if node.properties.read_replicas == Some(0) {
+                node.properties.read_replicas = None;
+            }
+        }
+    }
+
+    // セキュリティグループで許可された通信 (許可元のメンバー → 許可先のメンバー)
+    fn security_group_edges(&mut self) {
+        let mut members: HashMap<usize, Vec<String>> = HashMap::new();
+        let owners: BTreeMap<&usize, &String> = self.owners.iter().collect();
+        for (&i, owner) in owners {
+            let mut groups = self.refs(i, &SECURITY_GROUP_KEYS, &SECURITY_GROUPS);
+            for template in self.refs(
+                i,
+                &["launch_template", "launch_configuration"],
+                &LAUNCH_TEMPLATES,
+            ) {
+                groups.extend(self.refs(template, &SECURITY_GROUP_KEYS, &SECURITY_GROUPS));
+            }
+            for group in groups {
+                let list = members.entry(group).or_default();
+                if !list.contains(owner) {
+                    list.push(owner.clone());
+                }
+            }
+        }
+
+        // (許可元, 許可先, ポート)
+        let mut rules: Vec<(usize, usize, Option<i64>)> = Vec::new();
+        for (i, resource) in self.resources.iter().enumerate() {
+            match resource.kind {
+                "aws_security_group" => {
+                    let blocks = resource.values["ingress"].as_array();
+                    let config = self.expressions(i);
+                    for (n, block) in blocks.into_iter().flatten().enumerate() {
+                        let mut sources = self.lookup_values(block, &["security_groups"]);
+                        if let Some(c) = config
+                            && let Some(e) = c.expressions["ingress"].get(n)
+                        {
+                            sources.extend(self.lookup_expressions(
+                                &c.prefix,
+                                e,
+                                &["security_groups"],
+                            ));
+                        }
+                        for source in sources {
+                            if self.resources[source].kind == "aws_security_group" {
+                                rules.push((source, i, block["from_port"].as_i64()));
+                            }
+                        }
+                    }
+                }
+                "aws_security_group_rule"
+                    if resource.values["type"].as_str() == Some("ingress") =>
+                {
+                    let sources = self.refs(i, &["source_security_group_id"], &SECURITY_GROUPS);
+                    for target in self.refs(i, &["security_group_id"], &SECURITY_GROUPS) {
+                        for &source in &sources {
+                            rules.push((source, target, resource.values["from_port"].as_i64()));
+                        }
+                    }
+                }
+                "aws_vpc_security_group_ingress_rule" => {
+                    let sources = self.refs(i, &["referenced_security_group_id"], &SECURITY_GROUPS);
+                    for target in self.refs(i, &["security_group_id"], &SECURITY_GROUPS) {
+                        for &source in &sources {
+                            rules.push((source, target, resource.values["from_port"].as_i64()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (source, target, port) in rules {
+            let protocol = port.and_then(port_protocol);
+            for from in members.get(&source).into_iter().flatten() {
+                for to in members.get(&target).into_iter().flatten() {
+                    add_edge(&mut self.edges, from, to, protocol);
+                }
+            }
+        }
+    }
+
+    // ロードバランサー → (リスナー → ターゲットグループ →) ターゲット
+    fn load_balancer_edges(&mut self) {
+        let mut balancers: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        let mut targets: HashMap<usize, Vec<String>> = HashMap::new();
+        for (i, resource) in self.resources.iter().enumerate() {
+            let (front, back) = match resource.kind {
+                "aws_lb_listener" | "aws_alb_listener" => (true, false),
+                "aws_lb_listener_rule" => (true, false),
+                "aws_autoscaling_group" | "aws_ecs_service" | "aws_autoscaling_attachment" => {
+                    (false, true)
+                }
+                "aws_lb_target_group_attachment" => (false, true),
+                _ => continue,
+            };
+            let groups = self.refs(
+                i,
+                &[
+                    "default_action",
+                    "action",
+                    "target_group_arns",
+                    "load_balancer",
+                    "lb_target_group_arn",
+                    "target_group_arn",
+                ],
+                &TARGET_GROUPS,
+            );
+            let owner = match resource.kind {
+                "aws_lb_target_group_attachment" => {
+                    self.linked(i, &["target_id"], &[]).into_iter().next()
+                }
+                _ => self.owners.get(&i).cloned(),
+            };
+            let Some(owner) = owner else {
+                continue;
+            };
+            for group in groups {
+                if front {
+                    balancers.entry(group).or_default().push(owner.clone());
+                }
+                if back {
+                    targets.entry(group).or_default().push(owner.clone());
+                }
+            }
+        }
+
+        for (group, fronts) in balancers {
+            let values = self.resources[group].values;
+            let protocol = match values["protocol"].as_str() {
+                Some("HTTPS") => Some(Protocol::Https),
+                Some("HTTP") => Some(Protocol::Http),
+                Some("TCP") => Some(Protocol::Tcp),
+                _ => values["port"].as_i64().and_then(port_protocol),
+            };
+            for from in &fronts {
+                for to in targets.get(&group).into_iter().flatten() {
+                    add_edge(&mut self.edges, from, to, protocol);
+                }
+            }
+        }
+    }
+
+    fn wiring_edges(&mut self) {
+        let mut found = Vec::new();
+        for (kind, from_keys, to_keys, to_kinds, protocol) in WIRINGS {
+            for i in self.indexes_of(&[kind]) {
+                let side = |keys: &[&str], kinds: &[&str]| match keys {
+                    [] => self.owners.get(&i).cloned().into_iter().collect(),
+                    _ => self.linked(i, keys, kinds),
+                };
+                for from in side(from_keys, &[]) {
+                    for to in side(to_keys, to_kinds) {
+                        found.push((from.clone(), to, protocol));
+                    }
+                }
+            }
+        }
+        // Lambda の環境変数で名前や URL を渡しているデータストア等
+        for i in self.indexes_of(&["aws_lambda_function"]) {
+            if let Some(owner) = self.owners.get(&i) {
+                for to in self.linked(i, &["environment"], &BACKING_SERVICES) {
+                    found.push((owner.clone(), to, None));
+                }
+            }
+        }
+        for (from, to, protocol) in found {
+            add_edge(&mut self.edges, &from, &to, protocol);
+        }
+    }
+
+    fn indexes_of(&self, kinds: &[&str]) -> Vec<usize> {
+        (0..self.resources.len())
+            .filter(|&i| kinds.contains(&self.resources[i].kind))
+            .collect()
+    }
+
+    fn expressions(&self, i: usize) -> Option<&Expressions<'a>> {
+        self.expressions.get(&self.resources[i].base)
+    }
+
+    // 値または参照式として属性が与えられているか
+    fn has(&self, i: usize, key: &str) -> bool {
+        let value = &self.resources[i].values[key];
+        !(value.is_null() || value.as_str() == Some(""))
+            || self
+                .expressions(i)
+                .is_some_and(|e| !e.expressions[key].is_null())
+    }
+
+    // 属性 keys (入れ子のブロック内を含む) が参照するリソース (kinds が空なら種類を問わない)
+    fn refs(&self, i: usize, keys: &[&str], kinds: &[&str]) -> Vec<usize> {
+        let mut found = self.lookup_values(self.resources[i].values, keys);
+        if let Some(e) = self.expressions(i) {
+            found.extend(self.lookup_expressions(&e.prefix, e.expressions, keys));
+        }
+        let mut refs = Vec::new();
+        for r in found {
+            let matches = kinds.is_empty() || kinds.contains(&self.resources[r].kind);
+            if r != i && matches && !refs.contains(&r) {
+                refs.push(r);
+            }
+        }
+        refs
+    }
+
+    // refs の参照先を表すノードのID
+    fn linked(&self, i: usize, keys: &[&str], kinds: &[&str]) -> Vec<String> {
+        let mut linked = Vec::new();
+        for r in self.refs(i, keys, kinds) {
+            if let Some(owner) = self.owners.get(&r)
+                && !linked.contains(owner)
+            {
+                linked.push(owner.clone());
+            }
+        }
+        linked
+    }
+
+    fn lookup_values(&self, values: &Value, keys: &[&str]) -> Vec<usize> {
+        let mut texts = Vec::new();
+        strings_under(values, keys, false, &mut texts);
+        texts.into_iter().filter_map(|t| self.lookup(t)).collect()
+    }
+
+    // ID・ARN 等から元のリソースを引く (URL やパス付きの ARN は順に切り詰めて探す)
+    fn lookup(&self, text: &str) -> Option<usize> {
+        if let Some(&i) = self.by_value.get(text) {
+            return Some(i);
+        }
+        if let Some((_, rest)) = text.split_once("://") {
+            let host = rest.split('/').next().unwrap_or(rest);
+            if let Some(&i) = self.by_value.get(host) {
+                return Some(i);
+            }
+        }
+        let mut current = text;
+        while let Some((head, _)) = current.rsplit_once('/') {
+            if let Some(&i) = self.by_value.get(head) {
+                return Some(i);
+            }
+            current = head;
+        }
+        None
+    }
+
+    // 参照式 (例: "aws_subnet.private[0].id") が指すリソース
+    // Terraform は具体的な参照から順に並べる ("aws_subnet.private[0].id", "aws_subnet.private")
+    // ため、一覧ごとに最初に一致した参照だけを使う
+    fn lookup_expressions(&self, prefix: &str, expressions: &Value, keys: &[&str]) -> Vec<usize> {
+        let mut lists = Vec::new();
+        reference_lists(expressions, keys, false, &mut lists);
+        let mut found = Vec::new();
+        for list in lists {
+            for reference in list.iter().filter_map(Value::as_str) {
+                let reference = format!("{}{}", prefix, reference);
+                let matched: Vec<usize> = (0..self.resources.len())
+                    .filter(|&r| {
+                        let resource = &self.resources[r];
+                        refers_to(&reference, &resource.address)
+                            || refers_to(&reference, &resource.base)
+                    })
+                    .collect();
+                if !matched.is_empty() {
+                    found.extend(matched);
+                    break;
+                }
+            }
+        }
+        found
+    }
+}
+
+fn refers_to(reference: &str, address: &str) -> bool {
+    reference
+        .strip_prefix(address)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('['))
+}
+
+// 属性 keys の下にある文字列をすべて集める
+fn strings_under<'v>(value: &'v Value, keys: &[&str], matched: bool, out: &mut Vec<&'v str>) {
+    match value {
+        Value::String(text) if matched => out.push(text),
+        Value::Array(items) => {
+            for item in items {
+                strings_under(item, keys, matched, out);
+            }
+        }
+        Value::Object(entries) => {
+            for (key, child) in entries {
+                strings_under(child, keys, matched || keys.contains(&key.as_str()), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// 属性 keys の下にある参照式の一覧 ("references") をすべて集める
+fn reference_lists<'v>(
+    value: &'v Value,
+    keys: &[&str],
+    matched: bool,
+    out: &mut Vec<&'v Vec<Value>>,
+) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                reference_lists(item, keys, matched, out);
+            }
+        }
+        Value::Object(entries) => {
+            for (key, child) in entries {
+                match child {
+                    Value::Array(list) if matched && key == "references" => out.push(list),
+                    _ => reference_lists(child, keys, matched || keys.contains(&key.as_str()), out),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Name タグ、name 等の属性、リソース名の順に表示名を選ぶ
+fn label(resource: &Resource) -> String {
+    let values = resource.values;
+    [
+        &values["tags"]["Name"],
+        &values["name"],
+        &values["bucket"],
+        &values["function_name"],
+        &values["identifier"],
+        &values["cluster_identifier"],
+    ]
+    .into_iter()
+    .find_map(text)
+    .unwrap_or_else(|| {
+        // 添字付きのインスタンスは添字も含める (例: "app[1]")
+        let local = format!("{}.", resource.kind);
+        match resource.address.rfind(&local) {
+            Some(pos) => resource.address[pos + local.len()..].to_string(),
+            None => resource.name.to_string(),
+        }
+    })
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().filter(|t| !t.is_empty()).map(str::to_string)
+}
+
+fn origin() -> Position {
+    Position { x: 0.0, y: 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+
+    // VPC・サブネット・セキュリティグループ・ALB・Auto Scaling・RDS からなる `terraform show -json` の出力
+    const STATE: &str = include_str!("fixtures/terraform_show.json");
+
+    #[test]
+    fn imports_state_with_network_and_security_groups() {
+        let result = import(STATE, &test_catalog()).unwrap();
+        assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+        let diagram = &result.diagram;
+        let node = |id: &str| diagram.node(id).unwrap_or_else(|| panic!("{}", id));
+
+        // グループ: VPC → AZ → サブネット
+        assert_eq!(node("aws_vpc.main").type_label, "VPC (Network)");
+        let zone = "az:aws_vpc.main:ap-northeast-1a";
+        assert_eq!(node(zone).parent_node.as_deref(), Some("aws_vpc.main"));
+        assert_eq!(
+            node("aws_subnet.public[0]").parent_node.as_deref(),
+            Some(zone)
+        );
+        assert_eq!(node("aws_subnet.private[1]").type_label, "Subnet");
+
+        // 複数 AZ のサブネットにまたがるものは VPC に置かれる
+        let lb = node("aws_lb.web");
+        assert_eq!(lb.type_label, "Load Balancer");
+        assert_eq!(lb.parent_node.as_deref(), Some("aws_vpc.main"));
+        let app = node("aws_autoscaling_group.app");
+        assert_eq!(app.type_label, "App Server");
+        assert_eq!(app.parent_node.as_deref(), Some("aws_vpc.main"));
+        assert_eq!(app.properties.replicas, Some(2));
+        assert_eq!(app.properties.instance_class.as_deref(), Some("t3.small"));
+        assert_eq!(
+            app.properties.autoscaling.map(|a| (a.min, a.max)),
+            Some((2, 4))
+        );
+        let db = node("aws_db_instance.main");
+        assert_eq!(db.type_label, "RDBMS (SQL)");
+        assert_eq!(db.parent_node.as_deref(), Some("aws_vpc.main"));
+        assert_eq!(db.properties.multi_az, Some(true));
+
+        // ノードにならない付随リソースは取り込まれない
+        assert!(diagram.node("aws_lb_target_group.app").is_none());
+        assert!(diagram.node("aws_security_group.app").is_none());
+
+        let edges: Vec<(&str, &str, Option<Protocol>)> = diagram
+            .edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str(), e.protocol))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                (
+                    "aws_lb.web",
+                    "aws_autoscaling_group.app",
+                    Some(Protocol::Http)
+                ),
+                (
+                    "aws_autoscaling_group.app",
+                    "aws_db_instance.main",
+                    Some(Protocol::Sql)
+                ),
+            ]
+        );
+    }
+}
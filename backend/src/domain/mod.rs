@@ -1 +1,6 @@
+pub mod analysis;
+pub mod export;
+pub mod import;
 pub mod model;
+pub mod project;
+pub mod repository;
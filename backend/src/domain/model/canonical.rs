@@ -0,0 +1,335 @@
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// 評価ペイロードの正規化
+// 座標やノードIDの振り方など、評価結果に影響しない差分を取り除き、
+// 同じ設計であれば同じハッシュになるようにする
+
+// 正規化の対象外とするノードのフィールド (表示上の情報)
+const IGNORED_NODE_FIELDS: [&str; 6] = ["id", "position", "style", "width", "height", "selected"];
+
+pub fn canonicalize_design(payload: &Value) -> Value {
+    let nodes = payload
+        .get("nodes")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let edges = payload
+        .get("edges")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let node_id = |n: &Value| {
+        n.get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let index_of: HashMap<String, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (node_id(n), i))
+        .collect();
+    let parent_of: Vec<Option<usize>> = nodes
+        .iter()
+        .map(|n| {
+            n.get("parentNode")
+                .and_then(|p| p.as_str())
+                .and_then(|p| index_of.get(p).copied())
+        })
+        .collect();
+    // 接続ごとの (接続元, 接続先, ID と両端を除いた属性)
+    let links: Vec<(usize, usize, String)> = edges
+        .iter()
+        .filter_map(|e| {
+            let source = *index_of.get(e.get("source")?.as_str()?)?;
+            let target = *index_of.get(e.get("target")?.as_str()?)?;
+            let attrs: Map<String, Value> = e
+                .as_object()?
+                .iter()
+                .filter(|(k, v)| !["id", "source", "target"].contains(&k.as_str()) && !v.is_null())
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            Some((source, target, Value::Object(attrs).to_string()))
+        })
+        .collect();
+
+    // IDに依存しないノードの並び順: 自身の内容から始め、親・子・接続先・接続元の順位で
+    // 同順位のノードを分けていく。それでも残った同順位 (対称な位置にあるノード) は
+    // 1つずつ区別して分け直すため、入力の並び順やIDに結果が左右されない
+    let own: Vec<String> = nodes
+        .iter()
+        .map(|n| {
+            let mut own = strip_fields(n);
+            if let Some(obj) = own.as_object_mut() {
+                obj.remove("parentNode");
+            }
+            own.to_string()
+        })
+        .collect();
+    let mut ranks = rank(&own);
+    loop {
+        ranks = refine(ranks, &parent_of, &links);
+        let Some(tied) = first_tie(&ranks) else {
+            break;
+        };
+        let split: Vec<String> = ranks
+            .iter()
+            .enumerate()
+            .map(|(i, r)| format!("{:08}|{}", r, u8::from(i != tied)))
+            .collect();
+        ranks = rank(&split);
+    }
+
+    let mut keyed: Vec<(usize, Value)> = ranks.iter().copied().zip(nodes.iter().cloned()).collect();
+    keyed.sort_by_key(|(r, _)| *r);
+
+    let id_map: HashMap<String, String> = keyed
+        .iter()
+        .enumerate()
+        .map(|(i, (_, n))| (node_id(n), format!("n{}", i)))
+        .collect();
+
+    let canonical_nodes: Vec<Value> = keyed
+        .iter()
+        .map(|(_, n)| {
+            let mut obj = match strip_fields(n) {
+                Value::Object(obj) => obj,
+                _ => Map::new(),
+            };
+            obj.insert("id".to_string(), json!(id_map[&node_id(n)]));
+            // 親グループのIDも正規化後のIDに置き換える
+            if let Some(parent) = obj.get("parentNode").and_then(|v| v.as_str()) {
+                let mapped = id_map.get(parent).cloned().unwrap_or_default();
+                obj.insert("parentNode".to_string(), json!(mapped));
+            }
+            Value::Object(obj)
+        })
+        .collect();
+
+    // 接続は ID を除いた属性 (プロトコル・通信方式など) ごと正規化する
+    let mut canonical_edges: Vec<Value> = edges
+        .iter()
+        .filter_map(|e| {
+            let source = id_map.get(e.get("source")?.as_str()?)?;
+            let target = id_map.get(e.get("target")?.as_str()?)?;
+            let mut obj: Map<String, Value> = e
+                .as_object()?
+                .iter()
+                .filter(|(k, v)| k.as_str() != "id" && !v.is_null())
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            obj.insert("source".to_string(), json!(source));
+            obj.insert("target".to_string(), json!(target));
+            Some(Value::Object(obj))
+        })
+        .collect();
+    canonical_edges.sort_by_key(|e| e.to_string());
+    canonical_edges.dedup();
+
+    json!({
+        "scenario": payload.get("scenario").cloned().unwrap_or(Value::Null),
+        "nodes": canonical_nodes,
+        "edges": canonical_edges,
+    })
+}
+
+// 正規化済みの設計・プロンプトのバージョン・審査に使うモデルから、キャッシュキーとなるハッシュを作る
+pub fn design_hash(payload: &Value, prompt_version: &str, model: &str) -> String {
+    let canonical = canonicalize_design(payload);
+    // serde_json の Map はキー順に並ぶため、文字列化した結果は安定している
+    let mut hasher = Sha256::new();
+    hasher.update(canonical.to_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(prompt_version.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(model.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+fn strip_fields(node: &Value) -> Value {
+    match node {
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .filter(|(k, v)| !IGNORED_NODE_FIELDS.contains(&k.as_str()) && !v.is_null())
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+// 文字列キーを辞書順の順位に置き換える (同じキーは同じ順位)
+fn rank(keys: &[String]) -> Vec<usize> {
+    let mut sorted: Vec<&String> = keys.iter().collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+    keys.iter()
+        .map(|k| sorted.binary_search(&k).unwrap_or_default())
+        .collect()
+}
+
+// 親・子・接続相手の順位を加えて、区別できるノードがなくなるまで順位を細かくする
+fn refine(
+    mut ranks: Vec<usize>,
+    parent_of: &[Option<usize>],
+    links: &[(usize, usize, String)],
+) -> Vec<usize> {
+    loop {
+        let keys: Vec<String> = (0..ranks.len())
+            .map(|i| {
+                let parent = parent_of[i]
+                    .map(|p| ranks[p].to_string())
+                    .unwrap_or_default();
+                let mut children: Vec<usize> = (0..ranks.len())
+                    .filter(|&c| parent_of[c] == Some(i))
+                    .map(|c| ranks[c])
+                    .collect();
+                let mut outgoing: Vec<String> = links
+                    .iter()
+                    .filter(|(s, _, _)| *s == i)
+                    .map(|(_, t, attrs)| format!("{}>{}", attrs, ranks[*t]))
+                    .collect();
+                let mut incoming: Vec<String> = links
+                    .iter()
+                    .filter(|(_, t, _)| *t == i)
+                    .map(|(s, _, attrs)| format!("{}<{}", attrs, ranks[*s]))
+                    .collect();
+                children.sort_unstable();
+                outgoing.sort_unstable();
+                incoming.sort_unstable();
+                format!(
+                    "{:08}|{}|{:?}|{}|{}",
+                    ranks[i],
+                    parent,
+                    children,
+                    outgoing.join(","),
+                    incoming.join(",")
+                )
+            })
+            .collect();
+        let refined = rank(&keys);
+        if distinct(&refined) == distinct(&ranks) {
+            return refined;
+        }
+        ranks = refined;
+    }
+}
+
+fn distinct(ranks: &[usize]) -> usize {
+    ranks.iter().collect::<std::collections::HashSet<_>>().len()
+}
+
+// 最も小さい同順位グループの、最初のノード
+fn first_tie(ranks: &[usize]) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for &r in ranks {
+        *counts.entry(r).or_default() += 1;
+    }
+    let tied = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(r, _)| r)
+        .min()?;
+    ranks.iter().position(|&r| r == tied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, node_type: &str, x: i64) -> Value {
+        json!({ "id": id, "type": node_type, "position": { "x": x, "y": 0 } })
+    }
+
+    fn edge(id: &str, source: &str, target: &str) -> Value {
+        json!({ "id": id, "source": source, "target": target, "protocol": "HTTPS" })
+    }
+
+    fn design(nodes: Vec<Value>, edges: Vec<Value>) -> Value {
+        json!({ "scenario": { "id": "sns_app" }, "nodes": nodes, "edges": edges })
+    }
+
+    // App Server 2台がそれぞれ別の Database につながる、同じ型のノードが並ぶ設計
+    fn paired(ids: [&str; 5], node_order: [usize; 5], x: i64) -> Value {
+        let nodes = [
+            node(ids[0], "Load Balancer", x),
+            node(ids[1], "App Server", x + 1),
+            node(ids[2], "App Server", x + 2),
+            node(ids[3], "Database", x + 3),
+            node(ids[4], "Database", x + 4),
+        ];
+        design(
+            node_order.iter().map(|&i| nodes[i].clone()).collect(),
+            vec![
+                edge("e1", ids[0], ids[1]),
+                edge("e2", ids[0], ids[2]),
+                edge("e3", ids[1], ids[3]),
+                edge("e4", ids[2], ids[4]),
+            ],
+        )
+    }
+
+    #[test]
+    fn hash_ignores_order_ids_and_layout() {
+        let base = design_hash(
+            &paired(["lb", "a", "b", "x", "y"], [0, 1, 2, 3, 4], 0),
+            "v1",
+            "m",
+        );
+        let variants = [
+            paired(["lb", "a", "b", "x", "y"], [4, 3, 2, 1, 0], 0),
+            paired(["lb", "a", "b", "x", "y"], [0, 1, 2, 4, 3], 0),
+            paired(["lb", "a", "b", "x", "y"], [2, 0, 4, 1, 3], 500),
+            paired(["1", "2", "3", "4", "5"], [0, 1, 2, 3, 4], 0),
+            paired(["q", "w", "e", "r", "t"], [3, 1, 4, 0, 2], 90),
+        ];
+        for variant in variants {
+            assert_eq!(design_hash(&variant, "v1", "m"), base, "{}", variant);
+        }
+
+        let mut reversed = paired(["lb", "a", "b", "x", "y"], [0, 1, 2, 3, 4], 0);
+        reversed["edges"].as_array_mut().unwrap().reverse();
+        assert_eq!(design_hash(&reversed, "v1", "m"), base);
+    }
+
+    #[test]
+    fn hash_distinguishes_wiring_prompt_and_model() {
+        let payload = paired(["lb", "a", "b", "x", "y"], [0, 1, 2, 3, 4], 0);
+        let base = design_hash(&payload, "v1", "m");
+
+        // 同じ型のノード・同じ本数の接続でも、つなぎ方が違えば別の設計
+        let mut rewired = payload.clone();
+        rewired["edges"][3] = edge("e4", "a", "y");
+        assert_ne!(design_hash(&rewired, "v1", "m"), base);
+
+        let mut relabeled = payload.clone();
+        relabeled["edges"][2]["protocol"] = json!("SQL");
+        assert_ne!(design_hash(&relabeled, "v1", "m"), base);
+
+        assert_ne!(design_hash(&payload, "v2", "m"), base);
+        assert_ne!(design_hash(&payload, "v1", "gemini-2.5-pro"), base);
+    }
+
+    #[test]
+    fn parent_groups_are_renamed_with_their_children() {
+        let grouped = |vpc: &str, app: &str| {
+            let mut child = node(app, "App Server", 0);
+            child["parentNode"] = json!(vpc);
+            design(vec![child, node(vpc, "VPC", 0)], vec![])
+        };
+        let canonical = canonicalize_design(&grouped("vpc-1", "app-1"));
+        let nodes = canonical["nodes"].as_array().unwrap();
+        let vpc = nodes.iter().find(|n| n["type"] == "VPC").unwrap();
+        let app = nodes.iter().find(|n| n["type"] == "App Server").unwrap();
+        assert_eq!(app["parentNode"], vpc["id"]);
+        assert!(nodes.iter().all(|n| n.get("position").is_none()));
+        assert_eq!(canonical, canonicalize_design(&grouped("g", "n")));
+    }
+}
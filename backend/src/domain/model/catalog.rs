@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::diagram::Diagram;
+
+// architecture_defs.json をそのまま表す定義 (フロントエンドと共有)
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArchitectureDefs {
+    pub categories: Vec<CategoryDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryDef {
+    pub id: String,
+    pub label: String,
+    pub color: String,
+    pub bg_color: String,
+    pub items: Vec<ItemDef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDef {
+    #[serde(rename = "type")] // JSONの "type" フィールドをマッピング
+    pub type_name: String,
+    pub label: String,
+    #[serde(default)]
+    pub semantics: ComponentSemantics,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Hosting {
+    // クラウド事業者が冗長性・運用を担うマネージドサービス
+    Managed,
+    // 利用者自身がインスタンスを運用するもの
+    SelfHosted,
+    // 利用者の端末側で動くもの
+    Client,
+    // VPC等の論理的な区画 (実体を持たない)
+    #[default]
+    Logical,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentSemantics {
+    #[serde(default)]
+    pub stateful: bool,
+    #[serde(default)]
+    pub entry_point: bool,
+    #[serde(default)]
+    pub group: bool,
+    #[serde(default)]
+    pub scales_horizontally: bool,
+    // 単一インスタンスを単一障害点とみなすか (未指定なら hosting と水平スケールから判断する)
+    // 監視系のように、止まっても利用者のリクエストが失敗しないものは false にする
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redundancy_required: Option<bool>,
+    // 単体での既定SLA (%)
+    #[serde(default)]
+    pub sla: Option<f64>,
+    #[serde(default)]
+    pub hosting: Hosting,
+    // 接続してよい相手 (カテゴリIDまたはコンポーネントの type)
+    #[serde(default)]
+    pub connects_to: Vec<String>,
+    // ノードに設定できるプロパティ (NodeProperties の項目名)
+    #[serde(default)]
+    pub properties: Vec<String>,
+    // 1台あたりの処理能力 (台数の見積もりに使う。水平スケールするマネージドサービスは持たない)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<CapacityProfile>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacityProfile {
+    pub rps_per_instance: u32,
+}
+
+// --- 型付きのコンポーネント登録簿 ---
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentDef {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub label: String,
+    pub category: String,
+    pub category_label: String,
+    pub color: String,
+    pub bg_color: String,
+    #[serde(flatten)]
+    pub semantics: ComponentSemantics,
+}
+
+impl ComponentDef {
+    // 単一インスタンスでは冗長性が担保されず、単一障害点になりうるか
+    pub fn needs_redundancy(&self) -> bool {
+        if let Some(required) = self.semantics.redundancy_required {
+            return required;
+        }
+        match self.semantics.hosting {
+            Hosting::SelfHosted => true,
+            Hosting::Managed => !self.semantics.scales_horizontally,
+            Hosting::Client | Hosting::Logical => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComponentCatalog {
+    components: BTreeMap<String, ComponentDef>,
+    order: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_label: String,
+}
+
+impl ComponentCatalog {
+    pub fn from_defs(defs: ArchitectureDefs) -> Self {
+        let mut components = BTreeMap::new();
+        let mut order = Vec::new();
+        for category in defs.categories {
+            for item in category.items {
+                order.push(item.type_name.clone());
+                components.insert(
+                    item.type_name.clone(),
+                    ComponentDef {
+                        type_name: item.type_name,
+                        label: item.label,
+                        category: category.id.clone(),
+                        category_label: category.label.clone(),
+                        color: category.color.clone(),
+                        bg_color: category.bg_color.clone(),
+                        semantics: item.semantics,
+                    },
+                );
+            }
+        }
+        Self { components, order }
+    }
+
+    pub fn from_json(json_str: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::from_defs(serde_json::from_str(json_str)?))
+    }
+
+    pub fn get(&self, type_name: &str) -> Option<&ComponentDef> {
+        self.components.get(type_name)
+    }
+
+    // 定義ファイル上の順序で全コンポーネントを返す
+    pub fn components(&self) -> impl Iterator<Item = &ComponentDef> {
+        self.order.iter().filter_map(|t| self.components.get(t))
+    }
+
+    // source から target への接続が定義上許可されているか
+    pub fn can_connect(&self, source: &str, target: &str) -> bool {
+        let (Some(source), Some(target)) = (self.get(source), self.get(target)) else {
+            return false;
+        };
+        source
+            .semantics
+            .connects_to
+            .iter()
+            .any(|allowed| *allowed == target.type_name || *allowed == target.category)
+    }
+
+    // 許可されていない接続に対し、間に挟むべきコンポーネントの経路を探す (最大 max_hops 個)
+    // preferred に含まれる型 (設計図に既にあるもの等) を優先して経由させる
+    pub fn suggest_path(
+        &self,
+        source: &str,
+        target: &str,
+        max_hops: usize,
+        preferred: &[&str],
+    ) -> Option<Vec<String>> {
+        let mut candidates: Vec<&ComponentDef> = self.components().collect();
+        candidates.sort_by_key(|c| !preferred.contains(&c.type_name.as_str()));
+
+        let mut frontier = vec![vec![source.to_string()]];
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for path in &frontier {
+                let last = path.last().map(|s| s.as_str()).unwrap_or(source);
+                for candidate in &candidates {
+                    let name = candidate.type_name.as_str();
+                    if candidate.semantics.group
+                        || candidate.semantics.hosting == Hosting::Client
+                        || path.iter().any(|p| p == name)
+                        || !self.can_connect(last, name)
+                    {
+                        continue;
+                    }
+                    let mut extended = path.clone();
+                    extended.push(name.to_string());
+                    if self.can_connect(name, target) {
+                        // 先頭の source は除き、間に挟むものだけを返す
+                        return Some(extended.into_iter().skip(1).collect());
+                    }
+                    next.push(extended);
+                }
+            }
+            frontier = next;
+        }
+        None
+    }
+
+    // 設計図のうち、カタログに存在しない型のノード
+    pub fn unknown_nodes(&self, diagram: &Diagram) -> Vec<UnknownNode> {
+        diagram
+            .nodes
+            .iter()
+            .filter(|n| self.get(&n.type_label).is_none())
+            .map(|n| UnknownNode {
+                id: n.id.clone(),
+                type_label: n.type_label.clone(),
+            })
+            .collect()
+    }
+}
+
+// テスト用: フロントエンドと同じ architecture_defs.json から作ったカタログ
+#[cfg(test)]
+pub fn test_catalog() -> ComponentCatalog {
+    ComponentCatalog::from_json(include_str!(
+        "../../../../frontend/src/constants/architecture_defs.json"
+    ))
+    .expect("architecture_defs.json should parse")
+}
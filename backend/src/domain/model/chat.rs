@@ -1,6 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::diagram::Diagram;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChatLog {
     pub role: String,
     pub content: String,
@@ -11,4 +14,17 @@ pub struct ChatRequest {
     pub scenario_id: String,
     pub messages: Vec<ChatLog>,
     pub partner_role: Option<String>,
+    // カスタムシナリオの難易度 (small / medium / large)
+    #[serde(default)]
+    pub difficulty: Option<String>,
+    // 現在の設計図 (指定された場合、クライアントが設計内容にも言及する)
+    #[serde(default)]
+    pub diagram: Option<Diagram>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerReply {
+    pub reply: String,
+    // このターンで新たに開示された裏要件のID
+    pub disclosed: Vec<String>,
 }
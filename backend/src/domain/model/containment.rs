@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use super::diagram::{Diagram, Node};
+
+// グループノード (VPC / AZ / Subnet / Security Group) による包含関係
+// 画面上の座標ではなく、フロントエンドが送る parentNode から組み立てる
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    Vpc,
+    AvailabilityZone,
+    Subnet,
+    SecurityGroup,
+}
+
+impl GroupKind {
+    pub fn from_type(type_label: &str) -> Option<Self> {
+        match type_label {
+            "VPC (Network)" => Some(GroupKind::Vpc),
+            "Availability Zone" => Some(GroupKind::AvailabilityZone),
+            "Subnet" => Some(GroupKind::Subnet),
+            "Security Group" => Some(GroupKind::SecurityGroup),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupKind::Vpc => "VPC",
+            GroupKind::AvailabilityZone => "Availability Zone",
+            GroupKind::Subnet => "Subnet",
+            GroupKind::SecurityGroup => "Security Group",
+        }
+    }
+
+    // 直接の親として許されるグループ (None はトップレベルに置くこと)
+    // Subnet は AZ の中、AZ は VPC の中。Security Group は複数AZにまたがれるため VPC 直下も可
+    pub fn allows_parent(&self, parent: Option<GroupKind>) -> bool {
+        match self {
+            GroupKind::Vpc => parent.is_none(),
+            GroupKind::AvailabilityZone => parent == Some(GroupKind::Vpc),
+            GroupKind::Subnet => parent == Some(GroupKind::AvailabilityZone),
+            GroupKind::SecurityGroup => matches!(
+                parent,
+                Some(GroupKind::Vpc | GroupKind::AvailabilityZone | GroupKind::Subnet)
+            ),
+        }
+    }
+
+    // 規則上の本来の親 (エラーメッセージ用)
+    pub fn expected_parent(&self) -> &'static str {
+        match self {
+            GroupKind::Vpc => "トップレベル",
+            GroupKind::AvailabilityZone => "VPC",
+            GroupKind::Subnet => "Availability Zone",
+            GroupKind::SecurityGroup => "VPC / Availability Zone / Subnet",
+        }
+    }
+}
+
+pub struct ContainmentTree<'a> {
+    diagram: &'a Diagram,
+}
+
+impl<'a> ContainmentTree<'a> {
+    pub fn new(diagram: &'a Diagram) -> Self {
+        Self { diagram }
+    }
+
+    // 直接の親ノード (存在しないIDを指している場合は None)
+    pub fn parent(&self, id: &str) -> Option<&'a Node> {
+        let parent_id = self.diagram.node(id)?.parent_node.as_deref()?;
+        self.diagram.node(parent_id)
+    }
+
+    // 近い順の祖先 (親の循環があってもそこで打ち切る)
+    pub fn ancestors(&self, id: &str) -> Vec<&'a Node> {
+        let mut ancestors = Vec::new();
+        let mut visited = HashSet::from([id.to_string()]);
+        let mut current = id.to_string();
+        while let Some(parent) = self.parent(&current) {
+            if !visited.insert(parent.id.clone()) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent.id.clone();
+        }
+        ancestors
+    }
+
+    // 親をたどると自分自身に戻ってくるか
+    pub fn is_in_cycle(&self, id: &str) -> bool {
+        let mut visited = HashSet::new();
+        let mut current = id.to_string();
+        while let Some(parent) = self.parent(&current) {
+            if parent.id == id {
+                return true;
+            }
+            if !visited.insert(parent.id.clone()) {
+                return false;
+            }
+            current = parent.id.clone();
+        }
+        false
+    }
+
+    // 指定した種類のグループのうち、最も内側で自身を含むもの
+    pub fn enclosing(&self, id: &str, kind: GroupKind) -> Option<&'a Node> {
+        self.ancestors(id)
+            .into_iter()
+            .find(|n| GroupKind::from_type(&n.type_label) == Some(kind))
+    }
+
+    pub fn zone_of(&self, id: &str) -> Option<&'a Node> {
+        self.enclosing(id, GroupKind::AvailabilityZone)
+    }
+
+    // 同じ種類のノード (同じ層のレプリカ) が配置されているAZのID
+    pub fn zones_for_type(&self, type_label: &str) -> BTreeSet<&'a str> {
+        self.diagram
+            .nodes
+            .iter()
+            .filter(|n| n.type_label == type_label)
+            .filter_map(|n| self.zone_of(&n.id))
+            .map(|z| z.id.as_str())
+            .collect()
+    }
+
+    // 層 (コンポーネントの種類) ごとの配置AZの表示名 (どのAZにも属さないノードは除く)
+    pub fn zone_placement(&self) -> BTreeMap<&'a str, Vec<String>> {
+        let mut placement: BTreeMap<&'a str, BTreeSet<String>> = BTreeMap::new();
+        for node in &self.diagram.nodes {
+            if GroupKind::from_type(&node.type_label).is_some() {
+                continue;
+            }
+            if let Some(zone) = self.zone_of(&node.id) {
+                placement
+                    .entry(node.type_label.as_str())
+                    .or_default()
+                    .insert(zone.display_name());
+            }
+        }
+        placement
+            .into_iter()
+            .map(|(t, zones)| (t, zones.into_iter().collect()))
+            .collect()
+    }
+
+    // 外側から並べた所属グループの表記 (例: "VPC (Network) > Availability Zone「1a」")
+    pub fn path(&self, id: &str) -> String {
+        let mut ancestors = self.ancestors(id);
+        ancestors.reverse();
+        ancestors
+            .iter()
+            .map(|n| n.display_name())
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // (ID, 型, ラベル, 親)
+    fn diagram(nodes: &[(&str, &str, &str, Option<&str>)]) -> Diagram {
+        let nodes: Vec<serde_json::Value> = nodes
+            .iter()
+            .map(|(id, node_type, label, parent)| {
+                json!({
+                    "id": id, "type": node_type, "label": label,
+                    "parentNode": parent, "position": { "x": 0, "y": 0 }
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({ "nodes": nodes, "edges": [] })).unwrap()
+    }
+
+    fn multi_az() -> Diagram {
+        diagram(&[
+            ("vpc", "VPC (Network)", "main", None),
+            ("az1", "Availability Zone", "1a", Some("vpc")),
+            ("az2", "Availability Zone", "1c", Some("vpc")),
+            ("sn1", "Subnet", "private-1a", Some("az1")),
+            ("app1", "App Server", "App Server", Some("sn1")),
+            ("app2", "App Server", "App Server", Some("az2")),
+            ("db", "RDBMS (SQL)", "RDBMS (SQL)", Some("sn1")),
+            ("cdn", "CDN (CloudFront)", "CDN (CloudFront)", None),
+        ])
+    }
+
+    #[test]
+    fn groups_are_resolved_from_parent_node() {
+        let diagram = multi_az();
+        let tree = ContainmentTree::new(&diagram);
+
+        let ancestors: Vec<&str> = tree
+            .ancestors("app1")
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect();
+        assert_eq!(ancestors, vec!["sn1", "az1", "vpc"]);
+        assert_eq!(tree.enclosing("app1", GroupKind::Vpc).unwrap().id, "vpc");
+        assert_eq!(tree.zone_of("app2").unwrap().id, "az2");
+        assert!(tree.enclosing("app2", GroupKind::Subnet).is_none());
+        assert!(tree.zone_of("cdn").is_none());
+        assert_eq!(
+            tree.path("app1"),
+            "VPC (Network)「main」 > Availability Zone「1a」 > Subnet「private-1a」"
+        );
+        assert_eq!(tree.path("cdn"), "");
+    }
+
+    #[test]
+    fn zones_are_collected_per_component_type() {
+        let diagram = multi_az();
+        let tree = ContainmentTree::new(&diagram);
+
+        assert_eq!(
+            tree.zones_for_type("App Server")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["az1", "az2"]
+        );
+        assert_eq!(tree.zones_for_type("RDBMS (SQL)").len(), 1);
+        let placement = tree.zone_placement();
+        assert_eq!(
+            placement["App Server"],
+            vec!["Availability Zone「1a」", "Availability Zone「1c」"]
+        );
+        // グループ自身と、どのAZにも属さないノードは含めない
+        assert_eq!(
+            placement.keys().copied().collect::<Vec<_>>(),
+            vec!["App Server", "RDBMS (SQL)"]
+        );
+    }
+
+    #[test]
+    fn broken_parents_do_not_loop() {
+        let diagram = diagram(&[
+            ("a", "Subnet", "a", Some("b")),
+            ("b", "Availability Zone", "b", Some("a")),
+            ("c", "App Server", "c", Some("a")),
+            ("d", "App Server", "d", Some("missing")),
+        ]);
+        let tree = ContainmentTree::new(&diagram);
+
+        assert!(tree.is_in_cycle("a"));
+        // 循環の中にはいないが、祖先が循環している
+        assert!(!tree.is_in_cycle("c"));
+        let ancestors: Vec<&str> = tree.ancestors("c").iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ancestors, vec!["a", "b"]);
+        assert!(tree.parent("d").is_none());
+        assert!(tree.ancestors("d").is_empty());
+    }
+
+    #[test]
+    fn nesting_rules_follow_the_network_hierarchy() {
+        use GroupKind::*;
+        assert!(Vpc.allows_parent(None));
+        assert!(!Vpc.allows_parent(Some(Vpc)));
+        assert!(AvailabilityZone.allows_parent(Some(Vpc)));
+        assert!(!AvailabilityZone.allows_parent(None));
+        assert!(Subnet.allows_parent(Some(AvailabilityZone)));
+        assert!(!Subnet.allows_parent(Some(Vpc)));
+        for parent in [Vpc, AvailabilityZone, Subnet] {
+            assert!(SecurityGroup.allows_parent(Some(parent)));
+        }
+        assert!(!SecurityGroup.allows_parent(None));
+    }
+}
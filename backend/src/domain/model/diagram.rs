@@ -1,29 +1,468 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::containment::ContainmentTree;
 
 // プロジェクトの一部としても、APIの受け渡し用としても使える「図」の定義
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Diagram {
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Node {
     pub id: String,
     #[serde(rename = "type")]
     pub type_label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub position: Position,
+    // 描画サイズ (React Flow の width / height。グループの大きさ等、外部形式との変換に使う)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    // 所属するグループノード (VPC / AZ / Subnet / Security Group) のID
+    #[serde(
+        rename = "parentNode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub parent_node: Option<String>,
+    // 台数や冗長化などの設定 (許可される項目はコンポーネントの種類ごとに異なる)
+    #[serde(default, skip_serializing_if = "NodeProperties::is_empty")]
+    pub properties: NodeProperties,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<u32>,
+    // インスタンスサイズ (例: "t3.medium", "db.r6g.large")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_class: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoscaling: Option<AutoScaling>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_az: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_replicas: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_seconds: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AutoScaling {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl NodeProperties {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // 設定されている項目名 (architecture_defs.json の properties と同じ名前)
+    pub fn keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if self.replicas.is_some() {
+            keys.push("replicas");
+        }
+        if self.instance_class.is_some() {
+            keys.push("instanceClass");
+        }
+        if self.autoscaling.is_some() {
+            keys.push("autoscaling");
+        }
+        if self.multi_az.is_some() {
+            keys.push("multiAz");
+        }
+        if self.read_replicas.is_some() {
+            keys.push("readReplicas");
+        }
+        if self.cache_ttl_seconds.is_some() {
+            keys.push("cacheTtlSeconds");
+        }
+        keys
+    }
+
+    // 要約用の表記 (例: "×3, autoscaling 2-10, Multi-AZ")
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(replicas) = self.replicas {
+            parts.push(format!("×{}", replicas));
+        }
+        if let Some(class) = &self.instance_class {
+            parts.push(class.clone());
+        }
+        if let Some(scaling) = self.autoscaling {
+            parts.push(format!("autoscaling {}-{}", scaling.min, scaling.max));
+        }
+        if self.multi_az == Some(true) {
+            parts.push("Multi-AZ".to_string());
+        }
+        if let Some(read_replicas) = self.read_replicas {
+            parts.push(format!("read replica ×{}", read_replicas));
+        }
+        if let Some(ttl) = self.cache_ttl_seconds {
+            parts.push(format!("TTL {}s", ttl));
+        }
+        parts.join(", ")
+    }
+}
+
+impl Node {
+    // このノードが表す常時稼働のインスタンス数 (箱1つで複数台を表現できる)
+    pub fn instance_count(&self) -> u32 {
+        let replicas = self.properties.replicas.unwrap_or(1);
+        let min = self.properties.autoscaling.map(|a| a.min).unwrap_or(1);
+        replicas.max(min).max(1)
+    }
+
+    // 複数台構成、または Multi-AZ のスタンバイにより冗長化されているか
+    pub fn is_redundant(&self) -> bool {
+        self.instance_count() >= 2 || self.properties.multi_az == Some(true)
+    }
+
+    // プロンプト等で使う表示名 (ラベルが型名と異なる場合のみ併記)
+    pub fn display_name(&self) -> String {
+        match self.label.as_deref() {
+            Some(label) if !label.is_empty() && label != self.type_label => {
+                format!("{}「{}」", self.type_label, label)
+            }
+            _ => self.type_label.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Edge {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub source: String,
     pub target: String,
+    // 通信プロトコル (HTTPS, SQL, gRPC 等)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Protocol>,
+    // 同期/非同期/ストリーム (未指定の場合は接続先から推定する)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<CommunicationStyle>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bidirectional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+// 大文字・小文字を区別せずに読み取り、一覧にない値は誤りとして扱う (Deserialize は下で実装)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum Protocol {
+    #[serde(rename = "HTTPS")]
+    Https,
+    #[serde(rename = "HTTP")]
+    Http,
+    #[serde(rename = "gRPC")]
+    Grpc,
+    #[serde(rename = "WebSocket")]
+    WebSocket,
+    #[serde(rename = "SQL")]
+    Sql,
+    #[serde(rename = "TCP")]
+    Tcp,
+    #[serde(rename = "AMQP")]
+    Amqp,
+    #[serde(rename = "DNS")]
+    Dns,
+    #[serde(rename = "Other")]
+    Other,
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 9] = [
+        Protocol::Https,
+        Protocol::Http,
+        Protocol::Grpc,
+        Protocol::WebSocket,
+        Protocol::Sql,
+        Protocol::Tcp,
+        Protocol::Amqp,
+        Protocol::Dns,
+        Protocol::Other,
+    ];
+
+    pub fn parse(text: &str) -> Option<Protocol> {
+        let text = text.trim();
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str().eq_ignore_ascii_case(text))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Https => "HTTPS",
+            Protocol::Http => "HTTP",
+            Protocol::Grpc => "gRPC",
+            Protocol::WebSocket => "WebSocket",
+            Protocol::Sql => "SQL",
+            Protocol::Tcp => "TCP",
+            Protocol::Amqp => "AMQP",
+            Protocol::Dns => "DNS",
+            Protocol::Other => "Other",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Protocol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Protocol::parse(&text).ok_or_else(|| {
+            let expected: Vec<&str> = Protocol::ALL.iter().map(|p| p.as_str()).collect();
+            serde::de::Error::custom(format!(
+                "unknown protocol `{}`, expected one of {}",
+                text,
+                expected.join(", ")
+            ))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunicationStyle {
+    Sync,
+    Async,
+    Stream,
+}
+
+impl CommunicationStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommunicationStyle::Sync => "sync",
+            CommunicationStyle::Async => "async",
+            CommunicationStyle::Stream => "stream",
+        }
+    }
+}
+
+// メッセージを仲介し、送信側と受信側を非同期に切り離すコンポーネント
+pub const MESSAGE_BROKERS: [&str; 3] = ["Message Queue", "Pub/Sub", "Event Bus"];
+
+impl Edge {
+    // 明示された通信方式、なければプロトコルや接続先から推定した通信方式
+    pub fn effective_style(&self, diagram: &Diagram) -> CommunicationStyle {
+        if let Some(style) = self.style {
+            return style;
+        }
+        if self.protocol == Some(Protocol::Amqp) {
+            return CommunicationStyle::Async;
+        }
+        if self.protocol == Some(Protocol::WebSocket) {
+            return CommunicationStyle::Stream;
+        }
+        let via_broker = [&self.source, &self.target].iter().any(|id| {
+            diagram
+                .node(id)
+                .is_some_and(|n| MESSAGE_BROKERS.contains(&n.type_label.as_str()))
+        });
+        if via_broker {
+            CommunicationStyle::Async
+        } else {
+            CommunicationStyle::Sync
+        }
+    }
+
+    // 要約用の注記 (例: "HTTPS, async")
+    pub fn annotation(&self, diagram: &Diagram) -> String {
+        let mut parts = Vec::new();
+        if let Some(label) = self.label.as_deref().filter(|l| !l.is_empty()) {
+            parts.push(label.to_string());
+        }
+        if let Some(protocol) = self.protocol {
+            parts.push(protocol.as_str().to_string());
+        }
+        let style = self.effective_style(diagram);
+        if style != CommunicationStyle::Sync {
+            parts.push(style.as_str().to_string());
+        }
+        if self.bidirectional {
+            parts.push("双方向".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+impl Diagram {
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    // 生のJSONではなく、LLMが読みやすいコンパクトなトポロジー表現に要約する
+    pub fn summarize(&self) -> String {
+        if self.nodes.is_empty() {
+            return "(設計図にはまだ何も配置されていません)\n".to_string();
+        }
+
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for node in &self.nodes {
+            *counts.entry(node.type_label.as_str()).or_insert(0) += 1;
+        }
+        let inventory: Vec<String> = counts
+            .iter()
+            .map(|(t, c)| format!("{} ×{}", t, c))
+            .collect();
+
+        let tree = ContainmentTree::new(self);
+        let mut summary = format!("構成要素: {}\n接続:\n", inventory.join(", "));
+        for node in &self.nodes {
+            let targets: Vec<String> = self
+                .edges
+                .iter()
+                .filter(|e| e.source == node.id)
+                .filter(|e| self.node(&e.target).is_some())
+                .map(|e| match e.annotation(self) {
+                    a if a.is_empty() => e.target.clone(),
+                    a => format!("{} ({})", e.target, a),
+                })
+                .collect();
+            let has_incoming = self.edges.iter().any(|e| e.target == node.id);
+
+            let name = match node.properties.describe() {
+                p if p.is_empty() => node.display_name(),
+                p => format!("{} ({})", node.display_name(), p),
+            };
+            let line = if !targets.is_empty() {
+                format!("- {} [{}] → {}", name, node.id, targets.join(", "))
+            } else if has_incoming {
+                format!("- {} [{}] (終端)", name, node.id)
+            } else {
+                format!("- {} [{}] (どこにも接続されていない)", name, node.id)
+            };
+            summary.push_str(&line);
+            let placement = tree.path(&node.id);
+            if !placement.is_empty() {
+                summary.push_str(&format!(" @ {}", placement));
+            }
+            if let Some(desc) = node.description.as_deref().filter(|d| !d.is_empty()) {
+                summary.push_str(&format!(" ※{}", desc));
+            }
+            summary.push('\n');
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(protocol: &str) -> Result<Edge, serde_json::Error> {
+        serde_json::from_value(serde_json::json!({
+            "source": "a", "target": "b", "protocol": protocol
+        }))
+    }
+
+    #[test]
+    fn protocols_are_read_case_insensitively() {
+        assert_eq!(edge("https").unwrap().protocol, Some(Protocol::Https));
+        assert_eq!(edge("HTTPs").unwrap().protocol, Some(Protocol::Https));
+        assert_eq!(edge("grpc").unwrap().protocol, Some(Protocol::Grpc));
+        assert_eq!(
+            edge("websocket").unwrap().protocol,
+            Some(Protocol::WebSocket)
+        );
+        assert_eq!(edge("Other").unwrap().protocol, Some(Protocol::Other));
+        // 書き出しは正規の表記に揃う
+        let json = serde_json::to_value(edge("grpc").unwrap()).unwrap();
+        assert_eq!(json["protocol"], "gRPC");
+    }
+
+    fn node(properties: serde_json::Value) -> Node {
+        serde_json::from_value(serde_json::json!({
+            "id": "app", "type": "App Server", "position": { "x": 0, "y": 0 },
+            "properties": properties
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn instance_count_takes_the_larger_of_replicas_and_autoscaling_min() {
+        // (プロパティ, 台数, 冗長化されているか)
+        let cases = [
+            (serde_json::json!({}), 1, false),
+            (serde_json::json!({ "replicas": 3 }), 3, true),
+            (
+                serde_json::json!({ "autoscaling": { "min": 2, "max": 6 } }),
+                2,
+                true,
+            ),
+            (
+                serde_json::json!({ "replicas": 1, "autoscaling": { "min": 4, "max": 6 } }),
+                4,
+                true,
+            ),
+            (serde_json::json!({ "multiAz": true }), 1, true),
+            (serde_json::json!({ "multiAz": false }), 1, false),
+        ];
+        for (properties, count, redundant) in cases {
+            let node = node(properties.clone());
+            assert_eq!(node.instance_count(), count, "{}", properties);
+            assert_eq!(node.is_redundant(), redundant, "{}", properties);
+        }
+    }
+
+    #[test]
+    fn properties_round_trip_with_catalog_names() {
+        let properties = serde_json::json!({
+            "replicas": 2, "instanceClass": "t3.medium",
+            "autoscaling": { "min": 2, "max": 8 }, "multiAz": true,
+            "readReplicas": 1, "cacheTtlSeconds": 300
+        });
+        let node = node(properties.clone());
+        assert_eq!(
+            node.properties.keys(),
+            vec![
+                "replicas",
+                "instanceClass",
+                "autoscaling",
+                "multiAz",
+                "readReplicas",
+                "cacheTtlSeconds"
+            ]
+        );
+        assert_eq!(
+            node.properties.describe(),
+            "×2, t3.medium, autoscaling 2-8, Multi-AZ, read replica ×1, TTL 300s"
+        );
+        assert_eq!(
+            serde_json::to_value(&node).unwrap()["properties"],
+            properties
+        );
+
+        // 未設定のプロパティは書き出さない
+        let empty = self::node(serde_json::json!({}));
+        assert!(empty.properties.keys().is_empty());
+        assert!(
+            serde_json::to_value(&empty)
+                .unwrap()
+                .get("properties")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn unknown_protocols_are_rejected() {
+        let err = edge("htps").unwrap_err().to_string();
+        assert!(err.contains("unknown protocol `htps`"), "{}", err);
+    }
 }
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// ヒアリングで「裏要件」をどこまで明かしてよいかを決める開示ポリシー
+// プロンプトの文章で隠させるのではなく、バックエンド側で開示可能な事実だけを渡す
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DisclosurePolicy {
+    // 何を聞かれても明かさない
+    Never,
+    // その話題について直接質問されたら明かす
+    WhenAskedDirectly,
+    // 最初の質問の後、指定回数だけ食い下がられたら明かす
+    AfterFollowUps { count: u32 },
+    // 正確な値は明かさず、幅 (range) のみを伝える
+    OnlyRange,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HiddenFact {
+    pub id: String,
+    // 話題の名前 (例: "予算")
+    pub topic: String,
+    // ユーザーの質問がこの話題に触れているかを判定するキーワード
+    pub keywords: Vec<String>,
+    // 正確な値
+    pub value: String,
+    // OnlyRange の場合に伝える幅
+    pub range: Option<String>,
+    pub policy: DisclosurePolicy,
+    // ペルソナごとのポリシー上書き (キーは partner_role)
+    #[serde(default)]
+    pub role_policies: BTreeMap<String, DisclosurePolicy>,
+}
+
+impl HiddenFact {
+    pub fn policy_for(&self, role: &str) -> &DisclosurePolicy {
+        self.role_policies.get(role).unwrap_or(&self.policy)
+    }
+
+    pub fn is_mentioned_in(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|k| text.contains(&k.to_lowercase()))
+    }
+}
+
+// 開示された事実の内容 (プロンプトに注入する単位)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Disclosure {
+    Exact(String),
+    Range(String),
+}
+
+// セッション内でどの事実が何回聞かれ、どこまで開示されたかの記録
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DisclosureState {
+    pub ask_counts: BTreeMap<String, u32>,
+    pub revealed: BTreeSet<String>,
+    pub range_revealed: BTreeSet<String>,
+}
+
+impl DisclosureState {
+    // ユーザーの1ターン分の発言を反映し、このターンで新たに開示可能になった事実のIDを返す
+    pub fn observe(&mut self, facts: &[HiddenFact], role: &str, message: &str) -> Vec<String> {
+        let mut newly_revealed = Vec::new();
+        for fact in facts {
+            if !fact.is_mentioned_in(message) {
+                continue;
+            }
+            let count = self.ask_counts.entry(fact.id.clone()).or_insert(0);
+            *count += 1;
+            let asked = *count;
+
+            match fact.policy_for(role) {
+                DisclosurePolicy::Never => {}
+                DisclosurePolicy::WhenAskedDirectly => {
+                    if self.revealed.insert(fact.id.clone()) {
+                        newly_revealed.push(fact.id.clone());
+                    }
+                }
+                DisclosurePolicy::AfterFollowUps { count } => {
+                    // 最初の質問 + count 回の食い下がりで開示
+                    if asked > *count && self.revealed.insert(fact.id.clone()) {
+                        newly_revealed.push(fact.id.clone());
+                    }
+                }
+                DisclosurePolicy::OnlyRange => {
+                    if fact.range.is_some() && self.range_revealed.insert(fact.id.clone()) {
+                        newly_revealed.push(fact.id.clone());
+                    }
+                }
+            }
+        }
+        newly_revealed
+    }
+
+    // 現時点でプロンプトに含めてよい事実の一覧
+    pub fn eligible<'a>(
+        &self,
+        facts: &'a [HiddenFact],
+        role: &str,
+    ) -> Vec<(&'a HiddenFact, Disclosure)> {
+        facts
+            .iter()
+            .filter_map(|fact| {
+                if matches!(fact.policy_for(role), DisclosurePolicy::Never) {
+                    return None;
+                }
+                if self.revealed.contains(&fact.id) {
+                    Some((fact, Disclosure::Exact(fact.value.clone())))
+                } else if self.range_revealed.contains(&fact.id) {
+                    fact.range
+                        .as_ref()
+                        .map(|r| (fact, Disclosure::Range(r.clone())))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // 開示可能な事実と、まだ伏せるべき話題をプロンプト用の文章にまとめる
+    pub fn prompt_section(&self, facts: &[HiddenFact], role: &str) -> String {
+        let eligible = self.eligible(facts, role);
+        let mut section = String::from("【開示してよい情報】\n");
+        if eligible.is_empty() {
+            section.push_str("- (まだありません)\n");
+        }
+        for (fact, disclosure) in &eligible {
+            match disclosure {
+                Disclosure::Exact(v) => section.push_str(&format!("- {}: {}\n", fact.topic, v)),
+                Disclosure::Range(r) => section.push_str(&format!(
+                    "- {}: {} (正確な値は知らない体で、この幅でのみ答える)\n",
+                    fact.topic, r
+                )),
+            }
+        }
+
+        let withheld: Vec<&str> = facts
+            .iter()
+            .filter(|f| !eligible.iter().any(|(e, _)| e.id == f.id))
+            .map(|f| f.topic.as_str())
+            .collect();
+        if !withheld.is_empty() {
+            section.push_str("\n【まだ答えられない話題】\n");
+            for topic in withheld {
+                section.push_str(&format!("- {}\n", topic));
+            }
+            section.push_str(
+                "上記の話題について聞かれても、具体的な数値や事実は答えず、あいまいに返してください。\n",
+            );
+        }
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(policy: DisclosurePolicy) -> HiddenFact {
+        HiddenFact {
+            id: "budget".to_string(),
+            topic: "予算".to_string(),
+            keywords: vec!["予算".to_string(), "Cost".to_string()],
+            value: "月額3万円".to_string(),
+            range: Some("月額数万円".to_string()),
+            policy,
+            role_policies: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn after_follow_ups_reveals_once_the_count_is_exceeded() {
+        let facts = [budget(DisclosurePolicy::AfterFollowUps { count: 2 })];
+        let mut state = DisclosureState::default();
+        // 話題に触れない発言は数えない
+        assert!(
+            state
+                .observe(&facts, "cto", "いつまでに必要ですか")
+                .is_empty()
+        );
+        assert!(state.observe(&facts, "cto", "予算は？").is_empty());
+        assert!(
+            state
+                .observe(&facts, "cto", "予算をもう少し詳しく")
+                .is_empty()
+        );
+        assert!(state.eligible(&facts, "cto").is_empty());
+
+        assert_eq!(
+            state.observe(&facts, "cto", "COST だけでも"),
+            vec!["budget"]
+        );
+        assert_eq!(state.ask_counts["budget"], 3);
+        assert_eq!(
+            state.eligible(&facts, "cto")[0].1,
+            Disclosure::Exact("月額3万円".to_string())
+        );
+        // 開示済みの事実は再び「新たに開示」されない
+        assert!(state.observe(&facts, "cto", "予算は？").is_empty());
+    }
+
+    #[test]
+    fn only_range_never_exposes_the_exact_value() {
+        let facts = [budget(DisclosurePolicy::OnlyRange)];
+        let mut state = DisclosureState::default();
+        assert_eq!(state.observe(&facts, "cto", "予算は？"), vec!["budget"]);
+        assert!(state.observe(&facts, "cto", "正確な予算は？").is_empty());
+        assert_eq!(
+            state.eligible(&facts, "cto")[0].1,
+            Disclosure::Range("月額数万円".to_string())
+        );
+        let prompt = state.prompt_section(&facts, "cto");
+        assert!(prompt.contains("月額数万円"));
+        assert!(!prompt.contains("月額3万円"));
+
+        // 幅を持たない事実は開示しない
+        let mut facts = facts;
+        facts[0].range = None;
+        let mut state = DisclosureState::default();
+        assert!(state.observe(&facts, "cto", "予算は？").is_empty());
+        assert!(state.eligible(&facts, "cto").is_empty());
+    }
+
+    #[test]
+    fn role_policy_overrides_the_base_policy() {
+        let mut fact = budget(DisclosurePolicy::WhenAskedDirectly);
+        fact.role_policies
+            .insert("cfo".to_string(), DisclosurePolicy::Never);
+        let facts = [fact];
+        let mut state = DisclosureState::default();
+        assert!(state.observe(&facts, "cfo", "予算は？").is_empty());
+        assert!(state.eligible(&facts, "cfo").is_empty());
+        assert_eq!(state.observe(&facts, "cto", "予算は？"), vec!["budget"]);
+        // 同じ状態でも、Never のロールには開示しない
+        assert!(state.eligible(&facts, "cfo").is_empty());
+        assert_eq!(state.eligible(&facts, "cto").len(), 1);
+    }
+}
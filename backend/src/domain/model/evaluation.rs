@@ -0,0 +1,313 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::chat::ChatLog;
+use super::diagram::Diagram;
+
+// system_prompt.txt の Output_Format に対応する評価結果
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationResult {
+    pub total_score: u32,
+    pub details: DetailedScores,
+    #[serde(default)]
+    pub feedback: String,
+    #[serde(default)]
+    pub improvement: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedScores {
+    pub availability: u32,
+    pub scalability: u32,
+    pub security: u32,
+    pub maintainability: u32,
+    pub cost_efficiency: u32,
+    pub feasibility: u32,
+}
+
+// 評価結果について審査員に質問するためのリクエスト
+#[derive(Debug, Deserialize)]
+pub struct ExplainRequest {
+    pub scenario: Value,
+    #[serde(flatten)]
+    pub diagram: Diagram,
+    pub evaluation: EvaluationResult,
+    pub messages: Vec<ChatLog>,
+}
+
+impl DetailedScores {
+    pub const DIMENSIONS: [&'static str; 6] = [
+        "availability",
+        "scalability",
+        "security",
+        "maintainability",
+        "costEfficiency",
+        "feasibility",
+    ];
+
+    pub fn get(&self, dimension: &str) -> u32 {
+        match dimension {
+            "availability" => self.availability,
+            "scalability" => self.scalability,
+            "security" => self.security,
+            "maintainability" => self.maintainability,
+            "costEfficiency" => self.cost_efficiency,
+            "feasibility" => self.feasibility,
+            _ => 0,
+        }
+    }
+
+    pub fn from_fn(mut f: impl FnMut(&'static str) -> u32) -> Self {
+        Self {
+            availability: f("availability"),
+            scalability: f("scalability"),
+            security: f("security"),
+            maintainability: f("maintainability"),
+            cost_efficiency: f("costEfficiency"),
+            feasibility: f("feasibility"),
+        }
+    }
+}
+
+// --- 複数サンプル評価 ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnsembleConfig {
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+    // サンプルごとに循環して割り当てる温度・モデル (空なら既定値)
+    #[serde(default)]
+    pub temperatures: Vec<f32>,
+    #[serde(default)]
+    pub models: Vec<String>,
+    // 最大値と最小値の差がこれを超えた観点を「合意度が低い」とみなす
+    #[serde(default = "default_agreement_threshold")]
+    pub agreement_threshold: u32,
+}
+
+fn default_samples() -> usize {
+    3
+}
+
+fn default_agreement_threshold() -> u32 {
+    20
+}
+
+// 評価APIと同じペイロードに ensemble 設定を加えたリクエスト
+#[derive(Debug, Deserialize)]
+pub struct EnsembleRequest {
+    pub ensemble: EnsembleConfig,
+    #[serde(flatten)]
+    pub design: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreStats {
+    pub median: u32,
+    pub min: u32,
+    pub max: u32,
+    // 最大値 - 最小値
+    pub spread: u32,
+    pub std_dev: f64,
+    pub scores: Vec<u32>,
+}
+
+impl ScoreStats {
+    pub fn from_scores(mut scores: Vec<u32>) -> Self {
+        scores.sort_unstable();
+        let n = scores.len();
+        let median = match n {
+            0 => 0,
+            _ if n % 2 == 1 => scores[n / 2],
+            _ => (scores[n / 2 - 1] + scores[n / 2]).div_ceil(2),
+        };
+        let min = scores.first().copied().unwrap_or(0);
+        let max = scores.last().copied().unwrap_or(0);
+        let mean = scores.iter().map(|&s| s as f64).sum::<f64>() / n.max(1) as f64;
+        let variance = scores
+            .iter()
+            .map(|&s| (s as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n.max(1) as f64;
+        Self {
+            median,
+            min,
+            max,
+            spread: max - min,
+            std_dev: (variance.sqrt() * 10.0).round() / 10.0,
+            scores,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnsembleReport {
+    pub sample_count: usize,
+    pub failed_samples: usize,
+    pub total_score: ScoreStats,
+    pub dimensions: BTreeMap<&'static str, ScoreStats>,
+    pub low_agreement: Vec<&'static str>,
+}
+
+// 集計後の評価結果
+// 既存の EvaluationResult と同じ形に中央値を入れ、集計情報を ensemble に添える
+#[derive(Debug, Clone, Serialize)]
+pub struct EnsembleEvaluation {
+    #[serde(flatten)]
+    pub result: EvaluationResult,
+    pub ensemble: EnsembleReport,
+    pub samples: Vec<EvaluationResult>,
+}
+
+impl EnsembleEvaluation {
+    pub fn aggregate(
+        samples: Vec<EvaluationResult>,
+        failed_samples: usize,
+        agreement_threshold: u32,
+    ) -> Self {
+        let total_score = ScoreStats::from_scores(samples.iter().map(|s| s.total_score).collect());
+
+        let mut dimensions = BTreeMap::new();
+        for name in DetailedScores::DIMENSIONS {
+            let scores = samples.iter().map(|s| s.details.get(name)).collect();
+            dimensions.insert(name, ScoreStats::from_scores(scores));
+        }
+        let low_agreement = dimensions
+            .iter()
+            .filter(|(_, stats)| stats.spread > agreement_threshold)
+            .map(|(name, _)| *name)
+            .collect();
+
+        // フィードバック文は、合計点が中央値に最も近いサンプルのものを代表として採用する
+        let representative = samples
+            .iter()
+            .min_by_key(|s| s.total_score.abs_diff(total_score.median))
+            .cloned()
+            .expect("aggregate requires at least one sample");
+
+        let result = EvaluationResult {
+            total_score: total_score.median,
+            details: DetailedScores::from_fn(|name| dimensions[name].median),
+            feedback: representative.feedback,
+            improvement: representative.improvement,
+        };
+
+        Self {
+            result,
+            ensemble: EnsembleReport {
+                sample_count: samples.len(),
+                failed_samples,
+                total_score,
+                dimensions,
+                low_agreement,
+            },
+            samples,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(total: u32, availability: u32, feedback: &str) -> EvaluationResult {
+        EvaluationResult {
+            total_score: total,
+            details: DetailedScores {
+                availability,
+                scalability: 60,
+                security: 70,
+                maintainability: 50,
+                cost_efficiency: 80,
+                feasibility: 65,
+            },
+            feedback: feedback.to_string(),
+            improvement: format!("{}の改善点", feedback),
+        }
+    }
+
+    #[test]
+    fn score_stats_use_the_middle_values() {
+        // (入力, 中央値, 最小, 最大, 標準偏差)
+        let cases: [(Vec<u32>, u32, u32, u32, f64); 5] = [
+            (vec![], 0, 0, 0, 0.0),
+            (vec![70], 70, 70, 70, 0.0),
+            (vec![80, 60, 70], 70, 60, 80, 8.2),
+            // 偶数個は中央2つの平均を切り上げる
+            (vec![61, 70, 80, 60], 66, 60, 80, 8.1),
+            (vec![50, 50], 50, 50, 50, 0.0),
+        ];
+        for (scores, median, min, max, std_dev) in cases {
+            let stats = ScoreStats::from_scores(scores.clone());
+            assert_eq!(stats.median, median, "{:?}", scores);
+            assert_eq!((stats.min, stats.max), (min, max), "{:?}", scores);
+            assert_eq!(stats.spread, max - min, "{:?}", scores);
+            assert_eq!(stats.std_dev, std_dev, "{:?}", scores);
+            assert!(stats.scores.is_sorted());
+        }
+    }
+
+    #[test]
+    fn aggregate_takes_medians_and_flags_disagreement() {
+        let samples = vec![
+            sample(60, 30, "低め"),
+            sample(72, 80, "中央"),
+            sample(90, 55, "高め"),
+        ];
+        let ensemble = EnsembleEvaluation::aggregate(samples, 1, 20);
+
+        assert_eq!(ensemble.result.total_score, 72);
+        assert_eq!(ensemble.result.details.availability, 55);
+        assert_eq!(ensemble.result.details.security, 70);
+        // 代表のフィードバックは合計点が中央値に最も近いサンプルのもの
+        assert_eq!(ensemble.result.feedback, "中央");
+        assert_eq!(ensemble.result.improvement, "中央の改善点");
+
+        assert_eq!(ensemble.ensemble.sample_count, 3);
+        assert_eq!(ensemble.ensemble.failed_samples, 1);
+        assert_eq!(ensemble.ensemble.total_score.spread, 30);
+        // 差が閾値を超えたのは availability (50) だけ
+        assert_eq!(ensemble.ensemble.low_agreement, vec!["availability"]);
+        assert_eq!(ensemble.samples.len(), 3);
+    }
+
+    #[test]
+    fn spread_equal_to_the_threshold_is_still_agreement() {
+        let samples = vec![sample(70, 40, "a"), sample(70, 60, "b")];
+        let ensemble = EnsembleEvaluation::aggregate(samples, 0, 20);
+        assert!(ensemble.ensemble.low_agreement.is_empty());
+        assert_eq!(ensemble.result.details.availability, 50);
+        // 同点なら先に来たサンプルを代表にする
+        assert_eq!(ensemble.result.feedback, "a");
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one sample")]
+    fn aggregate_rejects_an_empty_ensemble() {
+        EnsembleEvaluation::aggregate(Vec::new(), 3, 20);
+    }
+
+    #[test]
+    fn ensemble_request_applies_defaults_and_keeps_the_design() {
+        let req: EnsembleRequest = serde_json::from_value(serde_json::json!({
+            "ensemble": {},
+            "scenario": { "id": "sns_app" },
+            "nodes": [],
+            "edges": []
+        }))
+        .unwrap();
+        assert_eq!(req.ensemble.samples, 3);
+        assert_eq!(req.ensemble.agreement_threshold, 20);
+        assert!(req.ensemble.temperatures.is_empty() && req.ensemble.models.is_empty());
+        assert!(req.design.contains_key("scenario"));
+        assert!(!req.design.contains_key("ensemble"));
+    }
+}
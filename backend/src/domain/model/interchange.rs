@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use super::diagram::Diagram;
+
+// 外部ツールの形式 (draw.io 等) から設計図を取り込む際の受け渡し用の型
+
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    // 取り込むファイルの中身 (テキスト)
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    pub diagram: Diagram,
+    // 対応するコンポーネントが見つからず、取り込まなかった要素
+    pub skipped: Vec<SkippedItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedItem {
+    // 元ファイル上のID (リソースのアドレス等)
+    pub source_id: String,
+    pub reason: String,
+}
+
+impl SkippedItem {
+    pub fn new(source_id: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            source_id: source_id.into(),
+            reason: reason.into(),
+        }
+    }
+}
@@ -1,3 +1,14 @@
+pub mod canonical;
+pub mod catalog;
 pub mod chat;
+pub mod containment;
 pub mod diagram;
+pub mod disclosure;
+pub mod evaluation;
+pub mod interchange;
+pub mod project;
+pub mod requirements;
+pub mod scenario;
+pub mod session;
+pub mod template;
 pub mod url_shorten;
@@ -0,0 +1,54 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::chat::ChatLog;
+use super::diagram::Diagram;
+use super::evaluation::EvaluationResult;
+
+// プロジェクトの保存ファイル (現行の形式)
+// 形式を変える場合は FORMAT_VERSION を上げ、domain::project に1つ前の形式からの移行を追加する
+
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFile {
+    // 保存ファイルの形式のバージョン (v1 のファイルにはこの項目がない)
+    pub format_version: u32,
+    // 保存するたびに上がるプロジェクトの版数 ("1.0", "2.0", ...)
+    pub version: String,
+    // 保存日時 (ISO 8601)
+    #[serde(default)]
+    pub timestamp: String,
+    pub project_id: Uuid,
+    // カスタムシナリオも含め、フロントエンドのシナリオ定義をそのまま保持する
+    pub scenario: Value,
+    #[serde(default)]
+    pub memo: String,
+    pub diagram: Diagram,
+    // 設計の意味に関わらない表示上の設定 (ノードIDごと)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub appearance: BTreeMap<String, NodeAppearance>,
+    #[serde(default)]
+    pub chat_history: Vec<ChatLog>,
+    #[serde(default)]
+    pub evaluation: Option<EvaluationResult>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeAppearance {
+    // ノードの色 (CSS のカラーコード)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl ProjectFile {
+    // 現行形式の JSON Schema (エディタでの補完や外部ツールでの検証用)
+    pub fn json_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(ProjectFile)).unwrap_or_default()
+    }
+}
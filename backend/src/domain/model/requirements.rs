@@ -0,0 +1,522 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// シナリオの要件を型付きの値で表したもの
+// プロンプトにも機械的な判定にも、この値を使う
+// プリセットシナリオ等の文章形式 (users / traffic / availability / budget) からも読み取れる
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Requirements {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dau: Option<u64>,
+    // ピーク時の秒間リクエスト数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_volume_gb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetRange>,
+    // 可用性の目標 (%, 例: 99.9)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability_target: Option<f64>,
+    // 許容できるデータ損失 (RPO) と復旧時間 (RTO)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpo_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rto_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub regions: Vec<String>,
+    // 準拠すべき規制・基準 (例: "pii", "pci-dss")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compliance: Vec<String>,
+    // 数値にできない要望・制約 (例: "夜間メンテナンス可")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+// 月額予算 (円)。上限がない場合は無制限
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_jpy: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_jpy: Option<u64>,
+}
+
+// 要件から導いた段階 (機械的な判定の基準)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementLevels {
+    pub scale: Level,
+    pub availability: Level,
+    pub budget: Level,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Ja,
+    En,
+}
+
+// ピーク時のリクエスト数が不明な場合に、DAUから推定するための係数
+const REQUESTS_PER_USER_PER_DAY: f64 = 20.0;
+const PEAK_FACTOR: f64 = 3.0;
+
+// 文章で「安く」「性能優先」とだけ書かれた予算を、月額に置き換える目安
+const LOW_BUDGET_JPY: u64 = 50_000;
+const HIGH_BUDGET_JPY: u64 = 1_000_000;
+
+const MULTIPLIERS: [(&str, f64); 5] = [
+    ("億", 1e8),
+    ("万", 1e4),
+    ("千", 1e3),
+    ("million", 1e6),
+    ("k", 1e3),
+];
+const RPS_UNITS: [&str; 5] = ["rps", "qps", "req/s", "requests/s", "requests per second"];
+const USER_UNITS: [&str; 6] = ["dau", "ユーザー", "人", "名", "users", "user"];
+
+impl Requirements {
+    // 型付きの値を優先し、足りない項目は文章形式の項目から読み取る
+    pub fn from_value(value: &Value) -> Self {
+        let typed: Requirements = serde_json::from_value(value.clone()).unwrap_or_default();
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let legacy = Self::from_text(
+            text("users"),
+            text("traffic"),
+            text("availability"),
+            text("budget"),
+        );
+        typed.or(legacy)
+    }
+
+    // 未設定の項目を other の値で補う
+    fn or(self, other: Self) -> Self {
+        let or_vec = |a: Vec<String>, b: Vec<String>| if a.is_empty() { b } else { a };
+        Self {
+            dau: self.dau.or(other.dau),
+            peak_rps: self.peak_rps.or(other.peak_rps),
+            data_volume_gb: self.data_volume_gb.or(other.data_volume_gb),
+            budget: self.budget.or(other.budget),
+            availability_target: self.availability_target.or(other.availability_target),
+            rpo_minutes: self.rpo_minutes.or(other.rpo_minutes),
+            rto_minutes: self.rto_minutes.or(other.rto_minutes),
+            regions: or_vec(self.regions, other.regions),
+            compliance: or_vec(self.compliance, other.compliance),
+            notes: or_vec(self.notes, other.notes),
+        }
+    }
+
+    pub fn from_text(users: &str, traffic: &str, availability: &str, budget: &str) -> Self {
+        let load = format!("{} {}", users, traffic);
+        let (dau, peak_rps) = parse_load(&load);
+        let all = format!("{} {} {} {}", users, traffic, availability, budget).to_lowercase();
+
+        let mut regions = Vec::new();
+        if contains_any(&all, &["グローバル", "global", "世界"]) {
+            regions.push("global".to_string());
+        }
+        let mut compliance = Vec::new();
+        if contains_any(&all, &["個人情報", "pii"]) {
+            compliance.push("pii".to_string());
+        }
+        if contains_any(&all, &["pci", "クレジットカード"]) {
+            compliance.push("pci-dss".to_string());
+        }
+        if contains_any(&all, &["gdpr"]) {
+            compliance.push("gdpr".to_string());
+        }
+
+        Self {
+            dau,
+            peak_rps,
+            budget: parse_budget(budget),
+            availability_target: parse_availability(availability),
+            regions,
+            compliance,
+            notes: [traffic]
+                .iter()
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_string())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    // ピーク時の秒間リクエスト数と、それがDAUからの推定値かどうか
+    pub fn effective_peak_rps(&self) -> Option<(u32, bool)> {
+        if let Some(rps) = self.peak_rps {
+            return Some((rps, false));
+        }
+        let dau = self.dau? as f64;
+        let rps = (dau * REQUESTS_PER_USER_PER_DAY / 86_400.0 * PEAK_FACTOR).ceil();
+        Some((rps.max(1.0) as u32, true))
+    }
+
+    pub fn levels(&self) -> RequirementLevels {
+        let dau = self.dau.unwrap_or(0);
+        let rps = self.effective_peak_rps().map(|(rps, _)| rps).unwrap_or(0);
+        let scale = if dau >= 1_000_000 || rps >= 5_000 {
+            Level::Critical
+        } else if dau >= 10_000 || rps >= 100 {
+            Level::High
+        } else if self.dau.is_some_and(|d| d <= 1_000) {
+            Level::Low
+        } else {
+            Level::Medium
+        };
+
+        let availability = match self.availability_target {
+            Some(t) if t >= 99.99 => Level::Critical,
+            Some(t) if t >= 99.9 => Level::High,
+            Some(t) if t < 99.5 => Level::Low,
+            _ => Level::Medium,
+        };
+
+        let budget = match self.budget {
+            Some(BudgetRange {
+                max_jpy: Some(max), ..
+            }) if max <= 2 * LOW_BUDGET_JPY => Level::Low,
+            Some(BudgetRange { max_jpy: None, .. }) => Level::High,
+            _ => Level::Medium,
+        };
+
+        RequirementLevels {
+            scale,
+            availability,
+            budget,
+        }
+    }
+
+    // 画面やプロンプト向けの表示 (1行1項目)
+    pub fn render(&self, locale: Locale) -> String {
+        let ja = locale == Locale::Ja;
+        let mut lines = Vec::new();
+        let mut line = |ja_label: &str, en_label: &str, value: String| {
+            lines.push(format!(
+                "{}: {}",
+                if ja { ja_label } else { en_label },
+                value
+            ));
+        };
+
+        if let Some(dau) = self.dau {
+            let value = match locale {
+                Locale::Ja => format!("{}DAU", count(dau, locale)),
+                Locale::En => format!("{} DAU", count(dau, locale)),
+            };
+            line("利用者数", "Users", value);
+        }
+        if let Some((rps, estimated)) = self.effective_peak_rps() {
+            let value = match (ja, estimated) {
+                (true, false) => format!("秒間{}リクエスト", count(rps as u64, locale)),
+                (true, true) => format!(
+                    "秒間約{}リクエスト (DAUからの推定)",
+                    count(rps as u64, locale)
+                ),
+                (false, false) => format!("{} req/s", count(rps as u64, locale)),
+                (false, true) => {
+                    format!("~{} req/s (estimated from DAU)", count(rps as u64, locale))
+                }
+            };
+            line("ピーク時の負荷", "Peak load", value);
+        }
+        if let Some(gb) = self.data_volume_gb {
+            line(
+                "データ量",
+                "Data volume",
+                format!("{}GB", count(gb, locale)),
+            );
+        }
+        if let Some(budget) = self.budget {
+            let value = match (budget.min_jpy, budget.max_jpy, ja) {
+                (Some(min), Some(max), true) => {
+                    format!("月額{}〜{}", yen(min, locale), yen(max, locale))
+                }
+                (None, Some(max), true) => format!("月額{}以内", yen(max, locale)),
+                (Some(min), None, true) => format!("月額{}以上 (上限なし)", yen(min, locale)),
+                (None, None, true) => "無制限".to_string(),
+                (Some(min), Some(max), false) => {
+                    format!("{} - {} / month", yen(min, locale), yen(max, locale))
+                }
+                (None, Some(max), false) => format!("up to {} / month", yen(max, locale)),
+                (Some(min), None, false) => {
+                    format!("{}+ / month (no upper limit)", yen(min, locale))
+                }
+                (None, None, false) => "unlimited".to_string(),
+            };
+            line("予算", "Budget", value);
+        }
+        if let Some(target) = self.availability_target {
+            line(
+                "可用性の目標",
+                "Availability target",
+                format!("{}%", target),
+            );
+        }
+        if let Some(rpo) = self.rpo_minutes {
+            line("RPO (許容データ損失)", "RPO", minutes(rpo, locale));
+        }
+        if let Some(rto) = self.rto_minutes {
+            line("RTO (目標復旧時間)", "RTO", minutes(rto, locale));
+        }
+        if !self.regions.is_empty() {
+            line("リージョン", "Regions", self.regions.join(", "));
+        }
+        if !self.compliance.is_empty() {
+            line("コンプライアンス", "Compliance", self.compliance.join(", "));
+        }
+        for note in &self.notes {
+            line("備考", "Notes", note.clone());
+        }
+        lines.join("\n")
+    }
+}
+
+fn contains_any(text: &str, needles: &[&str]) -> bool {
+    needles.iter().any(|n| text.contains(n))
+}
+
+// 文章中の数値 (単位つき) と、その直後の語
+fn numbers(text: &str) -> Vec<(f64, String, String)> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut digits = String::new();
+        while i < chars.len() && (chars[i].is_ascii_digit() || matches!(chars[i], ',' | '.')) {
+            if chars[i] != ',' {
+                digits.push(chars[i]);
+            }
+            i += 1;
+        }
+        let Ok(mut value) = digits.trim_end_matches('.').parse::<f64>() else {
+            continue;
+        };
+
+        let rest: String = chars[i..].iter().collect();
+        let mut after = rest.trim_start();
+        for (suffix, multiplier) in MULTIPLIERS {
+            if let Some(stripped) = after.strip_prefix(suffix) {
+                value *= multiplier;
+                after = stripped.trim_start();
+                break;
+            }
+        }
+        let before: String = chars[start.saturating_sub(4)..start].iter().collect();
+        found.push((value, before, after.to_string()));
+    }
+    found
+}
+
+// "10万DAU, ピーク時秒間100リクエスト" のような文章から DAU とピーク時RPSを読み取る
+fn parse_load(text: &str) -> (Option<u64>, Option<u32>) {
+    let mut dau: Option<u64> = None;
+    let mut rps = None;
+    for (value, before, after) in numbers(text) {
+        if before.contains("秒間") || RPS_UNITS.iter().any(|u| after.starts_with(u)) {
+            rps = Some(value as u32);
+        } else if USER_UNITS.iter().any(|u| after.starts_with(u)) {
+            // "50〜100人" のような幅は上限を採る
+            dau = Some(dau.unwrap_or(0).max(value as u64));
+        }
+    }
+    (dau, rps)
+}
+
+// "月額50万円〜100万円" "月額5,000円以内" "無制限" のような文章から月額予算を読み取る
+fn parse_budget(text: &str) -> Option<BudgetRange> {
+    let lower = text.to_lowercase();
+    let amounts: Vec<u64> = numbers(&lower)
+        .into_iter()
+        .filter(|(_, _, after)| after.starts_with('円'))
+        .map(|(value, _, _)| value as u64)
+        .collect();
+    match amounts.as_slice() {
+        [min, max, ..] => Some(BudgetRange {
+            min_jpy: Some(*min),
+            max_jpy: Some(*max),
+        }),
+        [amount] if contains_any(&lower, &["以上", "から"]) => Some(BudgetRange {
+            min_jpy: Some(*amount),
+            max_jpy: None,
+        }),
+        [amount] => Some(BudgetRange {
+            min_jpy: None,
+            max_jpy: Some(*amount),
+        }),
+        [] if contains_any(&lower, &["無制限", "unlimited"]) => Some(BudgetRange::default()),
+        [] if contains_any(&lower, &["low", "安く"]) => Some(BudgetRange {
+            min_jpy: None,
+            max_jpy: Some(LOW_BUDGET_JPY),
+        }),
+        [] if contains_any(&lower, &["high"]) => Some(BudgetRange {
+            min_jpy: Some(HIGH_BUDGET_JPY),
+            max_jpy: None,
+        }),
+        [] => None,
+    }
+}
+
+// "99.95%" のような数値、なければ "Critical (24/7)" 等の段階から可用性の目標を読み取る
+fn parse_availability(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    let explicit = numbers(&lower)
+        .into_iter()
+        .find(|(_, _, after)| after.starts_with('%'))
+        .map(|(value, _, _)| value);
+    if explicit.is_some() {
+        return explicit;
+    }
+    if contains_any(&lower, &["critical", "24/7", "止まってはいけない"]) {
+        Some(99.99)
+    } else if contains_any(&lower, &["high", "multi-az"]) {
+        Some(99.9)
+    } else if contains_any(&lower, &["best effort", "moderate", "停止可", "downtime"]) {
+        Some(99.0)
+    } else {
+        None
+    }
+}
+
+// 件数の表記 (日本語は万単位)
+fn count(n: u64, locale: Locale) -> String {
+    if locale == Locale::Ja && n >= 10_000 && n.is_multiple_of(10_000) {
+        return format!("{}万", n / 10_000);
+    }
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+fn yen(n: u64, locale: Locale) -> String {
+    match locale {
+        Locale::Ja => format!("{}円", count(n, locale)),
+        Locale::En => format!("JPY {}", count(n, locale)),
+    }
+}
+
+fn minutes(m: u32, locale: Locale) -> String {
+    match (locale, m >= 60 && m.is_multiple_of(60)) {
+        (Locale::Ja, true) => format!("{}時間", m / 60),
+        (Locale::Ja, false) => format!("{}分", m),
+        (Locale::En, true) => format!("{} h", m / 60),
+        (Locale::En, false) => format!("{} min", m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // frontend/src/scenarios.ts のプリセットシナリオ
+    #[test]
+    fn parses_internal_tool_scenario() {
+        let req = Requirements::from_text(
+            "50 users (Internal)",
+            "Very Low (Peak at 9:00 AM only)",
+            "Moderate (Can allow short downtimes at night)",
+            "Low (Avoid over-engineering)",
+        );
+        assert_eq!(req.dau, Some(50));
+        assert_eq!(req.peak_rps, None);
+        assert_eq!(req.availability_target, Some(99.0));
+        assert_eq!(
+            req.budget,
+            Some(BudgetRange {
+                min_jpy: None,
+                max_jpy: Some(LOW_BUDGET_JPY)
+            })
+        );
+        assert!(req.regions.is_empty());
+        assert!(req.compliance.is_empty());
+
+        let levels = req.levels();
+        assert_eq!(levels.scale, Level::Low);
+        assert_eq!(levels.availability, Level::Low);
+        assert_eq!(levels.budget, Level::Low);
+    }
+
+    #[test]
+    fn parses_sns_app_scenario() {
+        let req = Requirements::from_text(
+            "1 Million DAU (Global)",
+            "High (Read heavy, Write heavy)",
+            "Critical (24/7 uptime required)",
+            "High (Performance is priority)",
+        );
+        assert_eq!(req.dau, Some(1_000_000));
+        assert_eq!(req.availability_target, Some(99.99));
+        assert_eq!(req.regions, vec!["global"]);
+        assert_eq!(
+            req.budget,
+            Some(BudgetRange {
+                min_jpy: Some(HIGH_BUDGET_JPY),
+                max_jpy: None
+            })
+        );
+        let levels = req.levels();
+        assert_eq!(levels.scale, Level::Critical);
+        assert_eq!(levels.availability, Level::Critical);
+        assert_eq!(levels.budget, Level::High);
+    }
+
+    #[test]
+    fn parses_japanese_amounts_and_ranges() {
+        let req = Requirements::from_text(
+            "10万DAU, ピーク時秒間100リクエスト",
+            "急激なアクセス増に耐えられるスケーラビリティが必須",
+            "High (Multi-AZ推奨)",
+            "月額50万円〜100万円",
+        );
+        assert_eq!(req.dau, Some(100_000));
+        assert_eq!(req.peak_rps, Some(100));
+        assert_eq!(req.availability_target, Some(99.9));
+        assert_eq!(
+            req.budget,
+            Some(BudgetRange {
+                min_jpy: Some(500_000),
+                max_jpy: Some(1_000_000)
+            })
+        );
+        // 文章にない項目は推測しない
+        assert_eq!(req.rpo_minutes, None);
+        assert_eq!(req.rto_minutes, None);
+        assert_eq!(req.data_volume_gb, None);
+        assert!(req.compliance.is_empty());
+    }
+
+    #[test]
+    fn typed_values_take_precedence_over_text() {
+        let req = Requirements::from_value(&serde_json::json!({
+            "users": "50 users",
+            "dau": 2000,
+            "availabilityTarget": 99.95
+        }));
+        assert_eq!(req.dau, Some(2000));
+        assert_eq!(req.availability_target, Some(99.95));
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use super::disclosure::{DisclosurePolicy, HiddenFact};
+
+// プリセットシナリオにおけるクライアント像と裏要件の定義
+
+pub struct ScenarioProfile {
+    // クライアントの立場・性格 (裏要件は含めない)
+    pub persona: &'static str,
+    pub hidden_facts: Vec<HiddenFact>,
+}
+
+// 裏要件を持つプリセットシナリオのID ("custom" はフロントエンドの設定を使う)
+pub const PRESET_SCENARIOS: [&str; 2] = ["internal_tool", "sns_app"];
+
+impl ScenarioProfile {
+    pub fn is_known(scenario_id: &str) -> bool {
+        scenario_id == "custom" || PRESET_SCENARIOS.contains(&scenario_id)
+    }
+
+    pub fn for_id(scenario_id: &str) -> Self {
+        match scenario_id {
+            "internal_tool" => Self {
+                persona: "あなたは「社内勤怠管理ツール」の発注担当者（総務部）です。\nITには詳しくありません。",
+                hidden_facts: vec![
+                    fact(
+                        "users",
+                        "利用者数",
+                        &["ユーザー数", "利用者数", "人数", "何人", "社員数", "dau"],
+                        "社員50人",
+                        Some("数十人程度"),
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[("ceo", DisclosurePolicy::OnlyRange)],
+                    ),
+                    fact(
+                        "peak",
+                        "アクセスの時間帯",
+                        &[
+                            "ピーク",
+                            "時間帯",
+                            "同時アクセス",
+                            "同時接続",
+                            "アクセス数",
+                            "トラフィック",
+                            "集中",
+                        ],
+                        "朝9時に社員50人が一斉にアクセスするが、それ以外は誰も使わない",
+                        None,
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[("ceo", DisclosurePolicy::AfterFollowUps { count: 1 })],
+                    ),
+                    fact(
+                        "budget",
+                        "予算",
+                        &["予算", "コスト", "費用", "金額", "月額", "値段"],
+                        "予算はとにかく安く済ませたい（月額数千円程度）",
+                        Some("月額1万円以下"),
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[("ceo", DisclosurePolicy::AfterFollowUps { count: 2 })],
+                    ),
+                    fact(
+                        "durability",
+                        "データ消失と停止の許容度",
+                        &[
+                            "止ま",
+                            "停止",
+                            "ダウン",
+                            "可用性",
+                            "消え",
+                            "バックアップ",
+                            "データ消失",
+                            "障害",
+                        ],
+                        "データは消えると困るが、数分止まるくらいなら許容できる",
+                        None,
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[],
+                    ),
+                    // 社内の事情であり、どのペルソナも明かさない (予算の低さから推し量る前提)
+                    fact(
+                        "approval",
+                        "決裁の事情",
+                        &["決裁", "稟議", "承認", "上長"],
+                        "月額1万円を超えると役員会の承認が必要になり、導入が半年遅れる",
+                        None,
+                        DisclosurePolicy::Never,
+                        &[],
+                    ),
+                ],
+            },
+            "sns_app" => Self {
+                persona: "あなたは「次世代SNSアプリ」のスタートアップCEOです。\n野心的で、急成長を想定しています。",
+                hidden_facts: vec![
+                    fact(
+                        "region",
+                        "利用地域",
+                        &["世界", "海外", "地域", "グローバル", "国内", "リージョン"],
+                        "世界中からアクセスがある想定",
+                        None,
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[],
+                    ),
+                    fact(
+                        "users",
+                        "利用者数",
+                        &[
+                            "ユーザー数",
+                            "利用者数",
+                            "人数",
+                            "何人",
+                            "dau",
+                            "mau",
+                            "ユーザー規模",
+                        ],
+                        "100万DAU",
+                        Some("数十万〜数百万DAU"),
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[("ceo", DisclosurePolicy::OnlyRange)],
+                    ),
+                    fact(
+                        "performance",
+                        "性能要件",
+                        &["速度", "レスポンス", "サクサク", "遅", "性能", "レイテンシ"],
+                        "とにかく「サクサク動く」ことが最重要",
+                        None,
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[],
+                    ),
+                    fact(
+                        "availability",
+                        "可用性",
+                        &["止ま", "停止", "ダウン", "可用性", "稼働", "メンテナンス"],
+                        "24時間365日止まってはいけない",
+                        None,
+                        DisclosurePolicy::WhenAskedDirectly,
+                        &[("ceo", DisclosurePolicy::AfterFollowUps { count: 1 })],
+                    ),
+                    // 投資家向けの強気な姿勢と矛盾するため、どのペルソナも明かさない
+                    fact(
+                        "runway",
+                        "資金繰り",
+                        &["資金", "調達", "ランウェイ", "投資家", "キャッシュ"],
+                        "次の資金調達まで手元資金は半年分しかなく、初期費用は抑えたい",
+                        None,
+                        DisclosurePolicy::Never,
+                        &[],
+                    ),
+                ],
+            },
+            _ => Self {
+                persona: "あなたは一般的なシステムの顧客です。",
+                hidden_facts: Vec::new(),
+            },
+        }
+    }
+}
+
+fn fact(
+    id: &str,
+    topic: &str,
+    keywords: &[&str],
+    value: &str,
+    range: Option<&str>,
+    policy: DisclosurePolicy,
+    role_policies: &[(&str, DisclosurePolicy)],
+) -> HiddenFact {
+    HiddenFact {
+        id: id.to_string(),
+        topic: topic.to_string(),
+        keywords: keywords.iter().map(|k| k.to_string()).collect(),
+        value: value.to_string(),
+        range: range.map(|r| r.to_string()),
+        policy,
+        role_policies: role_policies
+            .iter()
+            .map(|(role, policy)| (role.to_string(), policy.clone()))
+            .collect::<BTreeMap<_, _>>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::disclosure::DisclosureState;
+
+    #[test]
+    fn generic_questions_do_not_disclose_facts() {
+        let profile = ScenarioProfile::for_id("internal_tool");
+        let mut state = DisclosureState::default();
+        let disclosed = state.observe(
+            &profile.hidden_facts,
+            "cto",
+            "いつまでに必要ですか？データは何を扱いますか？1円単位で計算しますか？",
+        );
+        assert!(disclosed.is_empty(), "{:?}", disclosed);
+    }
+
+    #[test]
+    fn direct_questions_follow_the_fact_policy() {
+        let profile = ScenarioProfile::for_id("internal_tool");
+        let mut state = DisclosureState::default();
+        assert_eq!(
+            state.observe(&profile.hidden_facts, "cto", "利用者数は何人ですか？"),
+            vec!["users"]
+        );
+
+        // CEO は利用者数を幅でしか答えない (ロールごとの上書き)
+        let mut state = DisclosureState::default();
+        state.observe(&profile.hidden_facts, "ceo", "利用者数は何人ですか？");
+        assert!(state.revealed.is_empty());
+        assert!(state.range_revealed.contains("users"));
+    }
+
+    #[test]
+    fn confidential_facts_are_never_disclosed() {
+        for scenario_id in PRESET_SCENARIOS {
+            let profile = ScenarioProfile::for_id(scenario_id);
+            let confidential: Vec<&HiddenFact> = profile
+                .hidden_facts
+                .iter()
+                .filter(|f| f.policy == DisclosurePolicy::Never && f.role_policies.is_empty())
+                .collect();
+            assert!(!confidential.is_empty(), "{}", scenario_id);
+
+            for role in ["ceo", "cto", "cfo"] {
+                let mut state = DisclosureState::default();
+                for fact in &confidential {
+                    let question = format!("{}について教えてください", fact.keywords[0]);
+                    for _ in 0..3 {
+                        assert!(
+                            state
+                                .observe(&profile.hidden_facts, role, &question)
+                                .is_empty()
+                        );
+                    }
+                }
+                let prompt = state.prompt_section(&profile.hidden_facts, role);
+                for fact in &confidential {
+                    assert!(!prompt.contains(&fact.value), "{}", prompt);
+                }
+            }
+        }
+    }
+}
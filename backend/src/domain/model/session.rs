@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use super::chat::ChatLog;
+use super::diagram::Diagram;
+use super::disclosure::DisclosureState;
+use super::scenario::ScenarioProfile;
+
+// ヒアリング相手として選べる役割
+pub const PARTNER_ROLES: [&str; 3] = ["ceo", "cto", "cfo"];
+
+// サーバー側で保持するヒアリングセッション
+// 再開・監査・講師によるレビューのため、会話履歴と開示状態をまとめて永続化する
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSession {
+    pub id: Uuid,
+    pub scenario_id: String,
+    pub partner_role: String,
+    // カスタムシナリオの難易度 (要件の決定に使う)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    pub messages: Vec<ChatLog>,
+    pub disclosure: DisclosureState,
+    // UNIX時刻 (秒)
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl ChatSession {
+    pub fn new(
+        scenario_id: String,
+        partner_role: String,
+        difficulty: Option<String>,
+        system_prompt: Option<String>,
+    ) -> Self {
+        let now = now_secs();
+        let messages = system_prompt
+            .map(|content| {
+                vec![ChatLog {
+                    role: "system".to_string(),
+                    content,
+                }]
+            })
+            .unwrap_or_default();
+        Self {
+            id: Uuid::new_v4(),
+            scenario_id,
+            partner_role,
+            difficulty,
+            messages,
+            disclosure: DisclosureState::default(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn push(&mut self, role: &str, content: String) {
+        self.messages.push(ChatLog {
+            role: role.to_string(),
+            content,
+        });
+        self.updated_at = now_secs();
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSessionRequest {
+    pub scenario_id: String,
+    pub partner_role: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<String>,
+    // カスタムシナリオ用のクライアント設定 (フロントエンドの system メッセージに相当)
+    pub system_prompt: Option<String>,
+}
+
+impl CreateSessionRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if !ScenarioProfile::is_known(&self.scenario_id) {
+            return Err(format!("Unknown scenario_id: {}", self.scenario_id));
+        }
+        if let Some(role) = &self.partner_role
+            && !PARTNER_ROLES.contains(&role.as_str())
+        {
+            return Err(format!(
+                "Unknown partner_role: {} (expected one of {})",
+                role,
+                PARTNER_ROLES.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionMessageRequest {
+    pub content: String,
+    #[serde(default)]
+    pub diagram: Option<Diagram>,
+}
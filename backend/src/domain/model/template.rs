@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::diagram::{Diagram, Position};
+
+// よくある構成パターンをまとめて配置するためのテンプレート
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchitectureTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub difficulty: TemplateDifficulty,
+    // グループ内のノードの座標はグループからの相対位置 (React Flow と同じ)
+    pub diagram: Diagram,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateDifficulty {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemplateFile {
+    templates: Vec<ArchitectureTemplate>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TemplateLibrary {
+    templates: Vec<ArchitectureTemplate>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TemplateQuery {
+    pub tag: Option<String>,
+    pub difficulty: Option<TemplateDifficulty>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct InstantiateRequest {
+    // テンプレートを置く位置 (キャンバス上の左上)
+    #[serde(default)]
+    pub position: Option<Position>,
+}
+
+impl TemplateLibrary {
+    pub fn from_json(json_str: &str) -> Result<Self, serde_json::Error> {
+        let file: TemplateFile = serde_json::from_str(json_str)?;
+        Ok(Self {
+            templates: file.templates,
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ArchitectureTemplate> {
+        self.templates.iter().find(|t| t.id == id)
+    }
+
+    pub fn templates(&self) -> impl Iterator<Item = &ArchitectureTemplate> {
+        self.templates.iter()
+    }
+
+    pub fn search(&self, query: &TemplateQuery) -> Vec<&ArchitectureTemplate> {
+        self.templates
+            .iter()
+            .filter(|t| query.tag.as_ref().is_none_or(|tag| t.tags.contains(tag)))
+            .filter(|t| query.difficulty.is_none_or(|d| t.difficulty == d))
+            .collect()
+    }
+}
+
+impl ArchitectureTemplate {
+    // キャンバス上の既存ノードと衝突しないよう、ノードと接続のIDを振り直した設計図を返す
+    // グループ内のノードは相対座標のため、トップレベルのノードだけを origin の分ずらす
+    pub fn instantiate(&self, origin: &Position) -> Diagram {
+        let suffix = Uuid::new_v4().simple().to_string();
+        let suffix = &suffix[..8];
+        let ids: HashMap<&str, String> = self
+            .diagram
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), format!("{}_{}", n.id, suffix)))
+            .collect();
+        let fresh = |id: &str| ids.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+        let mut diagram = self.diagram.clone();
+        for node in &mut diagram.nodes {
+            node.id = fresh(&node.id);
+            match &node.parent_node {
+                Some(parent) => node.parent_node = Some(fresh(parent)),
+                None => {
+                    node.position.x += origin.x;
+                    node.position.y += origin.y;
+                }
+            }
+        }
+        for (i, edge) in diagram.edges.iter_mut().enumerate() {
+            edge.source = fresh(&edge.source);
+            edge.target = fresh(&edge.target);
+            edge.id = Some(format!("e_{}_{}", suffix, i));
+        }
+        diagram
+    }
+}
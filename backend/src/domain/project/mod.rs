@@ -0,0 +1,163 @@
+mod v1;
+
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::domain::model::project::{FORMAT_VERSION, ProjectFile};
+
+// 保存ファイルを読み込み、現行の形式 (FORMAT_VERSION) まで1版ずつ移行する
+// 旧ビルドのフロントエンドは JSON を Base64 で包んで保存していたため、どちらの形でも受け付ける
+
+// format_version が n のファイルを n + 1 に上げる移行 (MIGRATIONS[n - 1])
+// 新しい形式に移せずに捨てた項目は、第2引数に説明を追加する
+type Migration = fn(Value, &mut Vec<String>) -> Result<Value, Box<dyn std::error::Error>>;
+
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize - 1] = [v1::upgrade];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedProject {
+    pub project: ProjectFile,
+    // 読み込んだファイルの形式のバージョン (現行なら FORMAT_VERSION と同じ)
+    pub migrated_from: u32,
+    // 移行の際に捨てた項目の説明
+    pub dropped: Vec<String>,
+}
+
+pub fn load(content: &str) -> Result<LoadedProject, Box<dyn std::error::Error>> {
+    let mut doc = decode(content)?;
+    let migrated_from = format_version(&doc)?;
+    if migrated_from > FORMAT_VERSION {
+        return Err(format!(
+            "形式のバージョン {} は、このサーバーが対応する {} より新しいため読み込めません",
+            migrated_from, FORMAT_VERSION
+        )
+        .into());
+    }
+    let mut dropped = Vec::new();
+    for migration in &MIGRATIONS[migrated_from as usize - 1..] {
+        doc = migration(doc, &mut dropped)?;
+    }
+    Ok(LoadedProject {
+        project: serde_json::from_value(doc)?,
+        migrated_from,
+        dropped,
+    })
+}
+
+// 平文の JSON か、UTF-8 の JSON を Base64 で包んだもの
+fn decode(content: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let trimmed = content.trim();
+    if trimmed.starts_with('{') {
+        return Ok(serde_json::from_str(trimmed)?);
+    }
+    let compact: String = trimmed.split_whitespace().collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(compact)
+        .map_err(|_| "JSON でも Base64 でもありません")?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+// formatVersion を持たないファイルは v1 (version はプロジェクトの版数であり、形式とは無関係)
+fn format_version(doc: &Value) -> Result<u32, Box<dyn std::error::Error>> {
+    if !doc.is_object() {
+        return Err("プロジェクトファイルの構造が不正です".into());
+    }
+    match doc.get("formatVersion") {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| format!("formatVersion が不正です: {}", v).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // フロントエンドの「保存」で書き出された v1 のファイル (Base64 で包む前の JSON)
+    const V1_FILE: &str = include_str!("fixtures/v1_frontend.json");
+
+    fn v1_with_evaluation(evaluation: Value) -> String {
+        let mut doc: Value = serde_json::from_str(V1_FILE).unwrap();
+        doc["evaluation"] = evaluation;
+        doc.to_string()
+    }
+
+    #[test]
+    fn migrates_base64_v1_file_from_frontend() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(V1_FILE);
+        let loaded = load(&encoded).unwrap();
+        assert_eq!(loaded.migrated_from, 1);
+        assert!(loaded.dropped.is_empty(), "{:?}", loaded.dropped);
+
+        let project = &loaded.project;
+        assert_eq!(project.format_version, FORMAT_VERSION);
+        assert_eq!(project.version, "2.0");
+        assert_eq!(
+            project.project_id.to_string(),
+            "5b8f2d5e-6c1a-4f0e-9d3b-2a7c4e1f8b90"
+        );
+        assert_eq!(project.chat_history.len(), 2);
+
+        let diagram = &project.diagram;
+        let vpc = diagram.node("dndnode_1").unwrap();
+        assert_eq!(vpc.type_label, "VPC (Network)");
+        assert_eq!((vpc.width, vpc.height), (Some(520.0), Some(280.0)));
+        let app = diagram.node("dndnode_3").unwrap();
+        assert_eq!(app.type_label, "App Server");
+        assert_eq!(app.label.as_deref(), Some("API サーバー"));
+        assert_eq!(app.description.as_deref(), Some("投稿と通知の API"));
+        assert_eq!(app.parent_node.as_deref(), Some("dndnode_1"));
+        // 型名と同じラベルは持たない
+        assert_eq!(diagram.node("dndnode_4").unwrap().label, None);
+        assert_eq!(diagram.edges.len(), 3);
+        assert_eq!(
+            project.appearance["dndnode_3"].color.as_deref(),
+            Some("#f97316")
+        );
+        assert_eq!(project.evaluation.as_ref().unwrap().total_score, 68);
+
+        // 書き出した v2 はそのまま読み込める
+        let exported = serde_json::to_string(project).unwrap();
+        let reloaded = load(&exported).unwrap();
+        assert_eq!(reloaded.migrated_from, FORMAT_VERSION);
+        assert_eq!(reloaded.project.diagram.nodes.len(), 5);
+    }
+
+    #[test]
+    fn legacy_score_becomes_total_score() {
+        let evaluation = json!({
+            "score": 55,
+            "details": {
+                "availability": 50, "scalability": 55, "security": 60,
+                "maintainability": 55, "costEfficiency": 50, "feasibility": 60
+            },
+            "feedback": "",
+            "improvement": ""
+        });
+        let loaded = load(&v1_with_evaluation(evaluation)).unwrap();
+        assert!(loaded.dropped.is_empty());
+        assert_eq!(loaded.project.evaluation.unwrap().total_score, 55);
+    }
+
+    #[test]
+    fn unreadable_evaluation_is_reported_as_dropped() {
+        let evaluation = json!({ "error": "Gemini API Error", "status": "error" });
+        let loaded = load(&v1_with_evaluation(evaluation)).unwrap();
+        assert!(loaded.project.evaluation.is_none());
+        assert_eq!(loaded.dropped.len(), 1);
+        assert!(
+            loaded.dropped[0].contains("評価結果"),
+            "{:?}",
+            loaded.dropped
+        );
+
+        let loaded = load(&v1_with_evaluation(Value::Null)).unwrap();
+        assert!(loaded.dropped.is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use uuid::Uuid;
+
+use crate::domain::model::chat::ChatLog;
+use crate::domain::model::diagram::Position;
+use crate::domain::model::evaluation::EvaluationResult;
+
+// v1: フロントエンドの ProjectSaveData をそのまま保存した形式
+// ノードは React Flow の形 (type は "custom" / "group"、コンポーネントの種類は data.originalType) で、
+// 色やグループの大きさは data.customColor と style に入っている
+// v2 ではノードを設計図 (Diagram) の形に揃え、色は appearance に分ける
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectV1 {
+    #[serde(default = "first_version")]
+    version: String,
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    project_id: String,
+    #[serde(default)]
+    scenario: Value,
+    #[serde(default)]
+    memo: String,
+    diagram: DiagramV1,
+    #[serde(default)]
+    chat_history: Vec<ChatLog>,
+    #[serde(default)]
+    evaluation: Option<Value>,
+}
+
+fn first_version() -> String {
+    "1.0".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct DiagramV1 {
+    nodes: Vec<NodeV1>,
+    edges: Vec<EdgeV1>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeV1 {
+    id: String,
+    // React Flow のノード種別
+    #[serde(rename = "type", default)]
+    kind: Option<String>,
+    position: Position,
+    #[serde(default)]
+    data: NodeDataV1,
+    #[serde(default)]
+    style: Map<String, Value>,
+    #[serde(default)]
+    width: Option<f64>,
+    #[serde(default)]
+    height: Option<f64>,
+    #[serde(rename = "parentNode", default)]
+    parent_node: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeDataV1 {
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    original_type: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    custom_color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EdgeV1 {
+    #[serde(default)]
+    id: Option<String>,
+    source: String,
+    target: String,
+}
+
+pub fn upgrade(doc: Value, dropped: &mut Vec<String>) -> Result<Value, Box<dyn std::error::Error>> {
+    let old: ProjectV1 = serde_json::from_value(doc)?;
+
+    let mut nodes = Vec::new();
+    let mut appearance = Map::new();
+    for node in old.diagram.nodes {
+        let NodeDataV1 {
+            label,
+            original_type,
+            description,
+            custom_color,
+        } = node.data;
+        // originalType を持たないノードは、ラベルか React Flow の種別を型名とみなす
+        let type_label = original_type
+            .or_else(|| label.clone())
+            .or(node.kind)
+            .unwrap_or_else(|| "Unknown".to_string());
+        let mut converted = json!({
+            "id": node.id,
+            "type": type_label,
+            "position": node.position,
+        });
+        if let Some(label) = label.filter(|l| !l.is_empty() && *l != type_label) {
+            converted["label"] = json!(label);
+        }
+        if let Some(description) = description.filter(|d| !d.is_empty()) {
+            converted["description"] = json!(description);
+        }
+        if let Some(width) = node.width.or_else(|| css_length(node.style.get("width"))) {
+            converted["width"] = json!(width);
+        }
+        if let Some(height) = node.height.or_else(|| css_length(node.style.get("height"))) {
+            converted["height"] = json!(height);
+        }
+        if let Some(parent) = node.parent_node {
+            converted["parentNode"] = json!(parent);
+        }
+        if let Some(color) = custom_color.filter(|c| !c.is_empty()) {
+            appearance.insert(node.id.clone(), json!({ "color": color }));
+        }
+        nodes.push(converted);
+    }
+    let edges: Vec<Value> = old
+        .diagram
+        .edges
+        .into_iter()
+        .map(|e| match e.id {
+            Some(id) => json!({ "id": id, "source": e.source, "target": e.target }),
+            None => json!({ "source": e.source, "target": e.target }),
+        })
+        .collect();
+
+    let evaluation = old.evaluation.and_then(|e| evaluation(e, dropped));
+    // 古いビルドには UUID 以外のIDもあり得るため、その場合は新しいIDを振る
+    let project_id = Uuid::parse_str(&old.project_id).unwrap_or_else(|_| Uuid::new_v4());
+
+    Ok(json!({
+        "formatVersion": 2,
+        "version": old.version,
+        "timestamp": old.timestamp,
+        "projectId": project_id,
+        "scenario": old.scenario,
+        "memo": old.memo,
+        "diagram": { "nodes": nodes, "edges": edges },
+        "appearance": appearance,
+        "chatHistory": old.chat_history,
+        "evaluation": evaluation,
+    }))
+}
+
+// 古いビルドは合計点を score に入れていたため、totalScore がなければ score を使う
+// 評価に失敗した際のエラー応答 (スコアの内訳がないもの) も保存されていたため、
+// 評価結果として読めないものは捨て、その旨を dropped に残す
+fn evaluation(mut value: Value, dropped: &mut Vec<String>) -> Option<EvaluationResult> {
+    if value.is_null() {
+        return None;
+    }
+    if let Some(fields) = value.as_object_mut()
+        && !fields.contains_key("totalScore")
+        && let Some(score) = fields.get("score").cloned()
+    {
+        fields.insert("totalScore".to_string(), score);
+    }
+    match serde_json::from_value::<EvaluationResult>(value) {
+        Ok(evaluation) => Some(evaluation),
+        Err(e) => {
+            dropped.push(format!("評価結果を読み取れないため破棄しました: {}", e));
+            None
+        }
+    }
+}
+
+// style の width / height (数値、または "300px" のような文字列)
+fn css_length(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().trim_end_matches("px").parse().ok(),
+        _ => None,
+    }
+}
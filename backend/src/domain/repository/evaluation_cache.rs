@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::domain::model::evaluation::EvaluationResult;
+
+// 正規化した設計のハッシュをキーに評価結果を保持するキャッシュ
+#[async_trait]
+pub trait EvaluationCache: Send + Sync {
+    async fn get(&self, key: &str) -> Option<EvaluationResult>;
+    async fn put(&self, key: &str, result: &EvaluationResult);
+}
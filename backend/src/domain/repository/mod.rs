@@ -0,0 +1,3 @@
+pub mod evaluation_cache;
+pub mod project;
+pub mod session;
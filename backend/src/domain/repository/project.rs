@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::model::project::ProjectFile;
+
+#[async_trait]
+pub trait ProjectRepository: Send + Sync {
+    async fn find(&self, id: Uuid) -> Result<Option<ProjectFile>, Box<dyn std::error::Error>>;
+    // 同じIDのプロジェクトが既にある場合は保存せず、false を返す
+    async fn create(&self, project: &ProjectFile) -> Result<bool, Box<dyn std::error::Error>>;
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::model::session::ChatSession;
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    // 読み込み→更新→保存の間、同じセッションへの他の書き込みを待たせる
+    async fn lock(&self, id: Uuid) -> SessionLock;
+    async fn find(&self, id: Uuid) -> Result<Option<ChatSession>, Box<dyn std::error::Error>>;
+    async fn save(&self, session: &ChatSession) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+// セッションのロック (破棄すると解放される)
+// ロックの仕組みは実装ごとに異なるため、中身は隠しておく
+pub struct SessionLock {
+    _guard: Box<dyn Send>,
+}
+
+impl SessionLock {
+    pub fn new(guard: impl Send + 'static) -> Self {
+        Self {
+            _guard: Box::new(guard),
+        }
+    }
+}
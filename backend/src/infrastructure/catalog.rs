@@ -0,0 +1,21 @@
+use std::sync::OnceLock;
+
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::infrastructure::gemini::client::get_architecture_defs_json;
+
+static CATALOG: OnceLock<ComponentCatalog> = OnceLock::new();
+
+// architecture_defs.json を読み込んだコンポーネント登録簿 (初回のみ読み込む)
+// プロンプト生成と設計図の解析で同じ定義を共有する
+pub fn component_catalog() -> &'static ComponentCatalog {
+    CATALOG.get_or_init(|| {
+        let json_str = match get_architecture_defs_json() {
+            Ok(json_str) => json_str,
+            Err(e) => {
+                eprintln!("Error loading configuration: {}", e);
+                std::process::exit(1);
+            }
+        };
+        ComponentCatalog::from_json(&json_str).expect("Failed to parse architecture_defs.json")
+    })
+}
@@ -7,30 +7,37 @@ use serde_json::Value;
 use std::env;
 use std::fs;
 use std::time::Duration;
+use tokio::task::JoinSet;
 use tokio::time::sleep;
 
-use crate::domain::model::chat::ChatRequest;
-
-#[derive(Deserialize)]
-struct ArchitectureDefs {
-    categories: Vec<Category>,
-}
-
-#[derive(Deserialize)]
-struct Category {
-    items: Vec<Item>,
-}
-
-#[derive(Deserialize)]
-struct Item {
-    #[serde(rename = "type")] // JSONの "type" フィールドをマッピング
-    type_name: String,
-}
+use crate::domain::analysis::capacity::{self, CapacityPlan};
+use crate::domain::analysis::constraints::{self, ConstraintFinding};
+use crate::domain::analysis::exposure;
+use crate::domain::analysis::failure_domains::failure_domains;
+use crate::domain::model::canonical::content_hash;
+use crate::domain::model::catalog::Hosting;
+use crate::domain::model::chat::{ChatLog, ChatRequest, CustomerReply};
+use crate::domain::model::containment::ContainmentTree;
+use crate::domain::model::diagram::Diagram;
+use crate::domain::model::disclosure::DisclosureState;
+use crate::domain::model::evaluation::{
+    EnsembleConfig, EnsembleEvaluation, EvaluationResult, ExplainRequest,
+};
+use crate::domain::model::requirements::{Locale, Requirements};
+use crate::domain::model::scenario::ScenarioProfile;
+use crate::infrastructure::catalog::component_catalog;
 
 // --- Gemini APIのリクエスト形式 (構造体定義) ---
 #[derive(Serialize)]
 struct GeminiRequest {
     contents: Vec<Content>,
+    #[serde(rename = "generationConfig", skip_serializing_if = "Option::is_none")]
+    generation_config: Option<GenerationConfig>,
+}
+
+#[derive(Serialize)]
+struct GenerationConfig {
+    temperature: f32,
 }
 
 #[derive(Serialize)]
@@ -69,27 +76,45 @@ fn build_system_prompt() -> String {
     // 1. プロンプトテンプレートを読み込む (コンパイル時に埋め込み)
     let template = include_str!("system_prompt.txt");
 
-    match get_architecture_defs_json() {
-        Ok(json_str) => {
-            let defs: ArchitectureDefs =
-                serde_json::from_str(&json_str).expect("Failed to parse architecture_defs.json");
-
-            let mut components = String::new();
-            for category in defs.categories {
-                for item in category.items {
-                    // YAMLのリスト形式 "- Name" に整形
-                    components.push_str(&format!("    - \"{}\"\n", item.type_name));
-                }
-            }
-
-            // テンプレート内のプレースホルダーを置換
-            template.replace("{{AVAILABLE_COMPONENTS}}", &components)
+    let mut components = String::new();
+    for component in component_catalog().components() {
+        // YAMLのリスト形式 "- Name" に、評価の手がかりとなる性質を添えて整形
+        let semantics = &component.semantics;
+        let mut traits = vec![component.category_label.clone()];
+        if semantics.group {
+            traits.push("contains other nodes".to_string());
+        }
+        if semantics.entry_point {
+            traits.push("entry point".to_string());
         }
-        Err(e) => {
-            eprintln!("Error loading configuration: {}", e);
-            std::process::exit(1);
+        if semantics.stateful {
+            traits.push("stateful".to_string());
         }
+        if semantics.scales_horizontally {
+            traits.push("scales horizontally".to_string());
+        }
+        match semantics.hosting {
+            Hosting::Managed => traits.push("managed".to_string()),
+            Hosting::SelfHosted => traits.push("self-hosted".to_string()),
+            Hosting::Client | Hosting::Logical => {}
+        }
+        if let Some(sla) = semantics.sla {
+            traits.push(format!("SLA {}%", sla));
+        }
+        components.push_str(&format!(
+            "    - \"{}\" ({})\n",
+            component.type_name,
+            traits.join(", ")
+        ));
     }
+
+    // テンプレート内のプレースホルダーを置換
+    template.replace("{{AVAILABLE_COMPONENTS}}", &components)
+}
+
+// 評価プロンプトのバージョン (テンプレートやコンポーネント定義が変わると値が変わる)
+pub fn prompt_version() -> String {
+    content_hash(&build_system_prompt())
 }
 
 // アーキテクチャ定義ファイル読み込み
@@ -107,33 +132,47 @@ pub fn get_architecture_defs_json() -> Result<String, Box<dyn std::error::Error>
     Ok(json_str)
 }
 
-fn get_difficulty_specs(difficulty: &str) -> serde_json::Value {
-    match difficulty {
-        "small" => serde_json::json!({
-            "users": "50〜100人程度",
-            "traffic": "運用コストをかけられないため、メンテナンスフリーな構成を好む",
-            "budget": "月額5,000円以内 (可能な限り安く)",
-            "availability": "Best Effort (夜間停止可)"
-        }),
-        "medium" => serde_json::json!({
-             "users": "10万DAU, ピーク時秒間100リクエスト",
-             "traffic": "急激なアクセス増に耐えられるスケーラビリティが必須",
-             "budget": "月額50万円〜100万円",
-             "availability": "High (Multi-AZ推奨)"
-        }),
-        "large" => serde_json::json!({
-             "users": "1000万ユーザー, グローバル展開",
-             "traffic": "単一障害点(SPOF)の完全排除と、データロス発生時の法的リスク回避",
-             "budget": "無制限（可用性とレイテンシが最優先）",
-             "availability": "Critical (24/7)"
-        }),
-        _ => serde_json::json!({ // デフォルト
-             "users": "10万DAU",
-             "traffic": "Standard",
-             "budget": "Standard",
-             "availability": "High"
-        }),
-    }
+// カスタムシナリオの真の要件 (難易度ごと)
+// 文章で決めた要件から型付きの値を読み取る。文章にない項目 (RPO/RTO 等) は推測せず未設定のままにする
+pub fn custom_requirements(difficulty: &str) -> Requirements {
+    let (users, traffic, budget, availability) = match difficulty {
+        "small" => (
+            "50〜100人程度",
+            "運用コストをかけられないため、メンテナンスフリーな構成を好む",
+            "月額5,000円以内 (可能な限り安く)",
+            "Best Effort (夜間停止可)",
+        ),
+        "medium" => (
+            "10万DAU, ピーク時秒間100リクエスト",
+            "急激なアクセス増に耐えられるスケーラビリティが必須",
+            "月額50万円〜100万円",
+            "High (Multi-AZ推奨)",
+        ),
+        "large" => (
+            "1000万ユーザー, グローバル展開",
+            "単一障害点(SPOF)の完全排除と、データロス発生時の法的リスク回避",
+            "無制限（可用性とレイテンシが最優先）",
+            "Critical (24/7)",
+        ),
+        // デフォルト
+        _ => ("10万DAU", "", "", "High"),
+    };
+    Requirements::from_text(users, traffic, availability, budget)
+}
+
+pub fn default_model_name() -> String {
+    env::var("AI_MODEL_NAME").unwrap_or_else(|_| "gemini-2.5-flash".to_string())
+}
+
+fn gemini_url(model_name: &str) -> String {
+    let api_key = env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY must be set");
+    let base_url = env::var("AI_API_BASE_URL")
+        .unwrap_or_else(|_| "https://generativelanguage.googleapis.com".to_string());
+
+    format!(
+        "{}/v1beta/models/{}:generateContent?key={}",
+        base_url, model_name, api_key
+    )
 }
 
 async fn send_with_retry(
@@ -212,29 +251,22 @@ async fn send_with_retry(
     }
 }
 
-// --- 評価関数 ---
-pub async fn evaluate_with_gemini(json_data: &Value) -> Result<String, Box<dyn std::error::Error>> {
-    let api_key = env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY must be set");
-    let base_url = env::var("AI_API_BASE_URL")
-        .unwrap_or_else(|_| "https://generativelanguage.googleapis.com".to_string());
-
-    let model_name = env::var("AI_MODEL_NAME").unwrap_or_else(|_| "gemini-2.5-flash".to_string());
-
-    let url = format!(
-        "{}/v1beta/models/{}:generateContent?key={}",
-        base_url, model_name, api_key
-    );
-
+// requirements を型付きの値に揃える
+// カスタムシナリオの場合は、難易度に応じた真の要件で置き換える
+fn resolve_scenario(json_data: &Value) -> Value {
     let mut final_json = json_data.clone();
 
-    if let Some(scenario) = final_json.get_mut("scenario") {
+    if let Some(scenario) = final_json
+        .get_mut("scenario")
+        .and_then(|s| s.as_object_mut())
+    {
         // カスタムフラグのチェック
         let is_custom = scenario
             .get("isCustom")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        if is_custom {
+        let requirements = if is_custom {
             // 難易度の取得
             let difficulty = scenario
                 .get("difficulty")
@@ -246,15 +278,100 @@ pub async fn evaluate_with_gemini(json_data: &Value) -> Result<String, Box<dyn s
                 difficulty
             );
 
-            // 正解スペックの取得
-            let specs = get_difficulty_specs(difficulty);
-
-            // requirementsフィールドの上書き
             // フロントエンドでは "AI決定" 等のダミーが入っているため、ここで真の値をセットする
-            if let Some(reqs) = scenario.get_mut("requirements") {
-                *reqs = specs;
-            }
-        }
+            custom_requirements(difficulty)
+        } else {
+            Requirements::from_value(scenario.get("requirements").unwrap_or(&Value::Null))
+        };
+
+        scenario.insert(
+            "requirementLevels".to_string(),
+            serde_json::json!(requirements.levels()),
+        );
+        scenario.insert(
+            "requirementsDisplay".to_string(),
+            Value::String(requirements.render(Locale::En)),
+        );
+        scenario.insert("requirements".to_string(), serde_json::json!(requirements));
+    }
+    final_json
+}
+
+// 設計データから、スコアの上限となる制約違反を機械的に検出する
+// 設計図はハンドラーで解析済みのもの (解析できない設計は評価前に 400 で返す)
+pub fn machine_findings(json_data: &Value, diagram: &Diagram) -> Vec<ConstraintFinding> {
+    findings_for(&resolve_scenario(json_data), diagram)
+}
+
+fn findings_for(resolved: &Value, diagram: &Diagram) -> Vec<ConstraintFinding> {
+    let mut findings = constraints::check(
+        diagram,
+        component_catalog(),
+        &requirements_of(resolved).levels(),
+    );
+    if let Some(plan) = plan_for(resolved, diagram) {
+        findings.extend(capacity::findings(&plan));
+    }
+    findings
+}
+
+fn requirements_of(resolved: &Value) -> Requirements {
+    let requirements = resolved
+        .get("scenario")
+        .and_then(|s| s.get("requirements"))
+        .unwrap_or(&Value::Null);
+    Requirements::from_value(requirements)
+}
+
+// シナリオの負荷から各層の必要台数を見積もり、設計図の台数と比較する
+pub fn capacity_plan(json_data: &Value, diagram: &Diagram) -> Option<CapacityPlan> {
+    plan_for(&resolve_scenario(json_data), diagram)
+}
+
+fn plan_for(resolved: &Value, diagram: &Diagram) -> Option<CapacityPlan> {
+    capacity::plan(diagram, component_catalog(), &requirements_of(resolved))
+}
+
+// --- 評価関数 ---
+pub async fn evaluate_with_gemini(
+    json_data: &Value,
+    diagram: &Diagram,
+) -> Result<String, Box<dyn std::error::Error>> {
+    evaluate_with_sampling(json_data, diagram, None, None).await
+}
+
+// モデルと温度を指定して審査員を1回呼び出す (未指定の場合は既定値)
+async fn evaluate_with_sampling(
+    json_data: &Value,
+    diagram: &Diagram,
+    model: Option<&str>,
+    temperature: Option<f32>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = match model {
+        Some(model) => gemini_url(model),
+        None => gemini_url(&default_model_name()),
+    };
+
+    let mut final_json = resolve_scenario(json_data);
+
+    // 機械的に判定した制約を、採点の前提として渡す
+    final_json["machine_findings"] = serde_json::json!(findings_for(&final_json, diagram));
+    if let Some(plan) = plan_for(&final_json, diagram) {
+        final_json["capacity_plan"] = serde_json::json!(plan);
+    }
+    // 同期通信でつながった障害ドメイン (非同期接続で分断される)
+    final_json["failure_domains"] =
+        serde_json::json!(failure_domains(diagram, component_catalog()));
+    // インターネットからの到達性と露出 (security 観点の根拠)
+    final_json["exposure"] = serde_json::json!(exposure::analyze(diagram, component_catalog()));
+    // グループの包含関係から求めた、層ごとの配置AZ
+    let placement = ContainmentTree::new(diagram).zone_placement();
+    if !placement.is_empty() {
+        final_json["zone_placement"] = serde_json::json!(placement);
+    }
+    let unknown = component_catalog().unknown_nodes(diagram);
+    if !unknown.is_empty() {
+        final_json["unknown_components"] = serde_json::json!(unknown);
     }
 
     // プロンプトの作成
@@ -267,31 +384,176 @@ pub async fn evaluate_with_gemini(json_data: &Value) -> Result<String, Box<dyn s
         contents: vec![Content {
             parts: vec![Part { text: prompt }],
         }],
+        generation_config: temperature.map(|temperature| GenerationConfig { temperature }),
     };
 
     let client = Client::new();
     send_with_retry(&client, &url, &request_body).await
 }
 
-pub async fn chat_with_customer(req: &ChatRequest) -> Result<String, Box<dyn std::error::Error>> {
-    let api_key = env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY must be set");
-    let base_url = env::var("AI_API_BASE_URL")
-        .unwrap_or_else(|_| "https://generativelanguage.googleapis.com".to_string());
+// --- 複数サンプルによる評価 ---
+// 独立した審査員呼び出しを並列に行い、観点ごとのスコアを集計する
+pub async fn evaluate_ensemble(
+    json_data: &Value,
+    diagram: &Diagram,
+    config: &EnsembleConfig,
+) -> Result<EnsembleEvaluation, Box<dyn std::error::Error>> {
+    let samples = config.samples.clamp(1, MAX_ENSEMBLE_SAMPLES);
+    let mut tasks = JoinSet::new();
+
+    for i in 0..samples {
+        let json_data = json_data.clone();
+        let diagram = diagram.clone();
+        let model = pick(&config.models, i).cloned();
+        let temperature = pick(&config.temperatures, i).copied();
+        tasks.spawn(async move {
+            let text = evaluate_with_sampling(&json_data, &diagram, model.as_deref(), temperature)
+                .await
+                .map_err(|e| e.to_string())?;
+            serde_json::from_str::<EvaluationResult>(&strip_code_fence(&text))
+                .map_err(|e| format!("評価結果の解析に失敗しました: {}", e))
+        });
+    }
 
-    let model_name = env::var("AI_MODEL_NAME").unwrap_or_else(|_| "gemini-2.5-flash".to_string());
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(Ok(result)) => results.push(result),
+            Ok(Err(e)) => errors.push(e),
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
 
-    let url = format!(
-        "{}/v1beta/models/{}:generateContent?key={}",
-        base_url, model_name, api_key
-    );
+    if results.is_empty() {
+        return Err(errors
+            .pop()
+            .unwrap_or_else(|| "評価に失敗しました。".to_string())
+            .into());
+    }
+    for e in &errors {
+        eprintln!("Ensemble sample failed: {}", e);
+    }
+
+    Ok(EnsembleEvaluation::aggregate(
+        results,
+        errors.len(),
+        config.agreement_threshold,
+    ))
+}
+
+const MAX_ENSEMBLE_SAMPLES: usize = 5;
+
+// リストが指定されていれば、サンプル番号に応じて循環的に割り当てる
+fn pick<T>(list: &[T], i: usize) -> Option<&T> {
+    if list.is_empty() {
+        None
+    } else {
+        list.get(i % list.len())
+    }
+}
+
+// LLMの応答に含まれる ```json ... ``` の囲みを取り除く
+pub fn strip_code_fence(text: &str) -> String {
+    text.replace("```json", "")
+        .replace("```", "")
+        .trim()
+        .to_string()
+}
+
+// --- 評価結果への質問 (審査員との対話) ---
+pub async fn explain_evaluation(
+    req: &ExplainRequest,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = gemini_url(&default_model_name());
+    let request_body = GeminiRequest {
+        contents: vec![Content {
+            parts: vec![Part {
+                text: explain_prompt(req)?,
+            }],
+        }],
+        generation_config: None,
+    };
+
+    let client = Client::new();
+    send_with_retry(&client, &url, &request_body).await
+}
+
+// シナリオ・設計・評価結果と会話履歴から、審査員として答えさせるプロンプトを組み立てる
+fn explain_prompt(req: &ExplainRequest) -> Result<String, Box<dyn std::error::Error>> {
+    // 審査時と同じ型付きの要件を、日本語の表示とともに渡す
+    let resolved = resolve_scenario(&serde_json::json!({ "scenario": req.scenario }));
+    let mut scenario = resolved["scenario"].clone();
+    if let Some(obj) = scenario.as_object_mut() {
+        obj.insert(
+            "requirementsDisplay".to_string(),
+            Value::String(requirements_of(&resolved).render(Locale::Ja)),
+        );
+    }
+
+    let template = include_str!("explain_prompt.txt");
+    let mut prompt = template
+        .replace("{{SCENARIO}}", &serde_json::to_string_pretty(&scenario)?)
+        .replace("{{DESIGN}}", &req.diagram.summarize())
+        .replace(
+            "{{EVALUATION}}",
+            &serde_json::to_string_pretty(&req.evaluation)?,
+        );
+
+    prompt.push_str("\n\n--- 会話履歴 ---\n");
+    for msg in req.messages.iter().filter(|m| m.role != "system") {
+        let speaker = if msg.role == "user" { "User" } else { "Judge" };
+        prompt.push_str(&format!("{}: {}\n", speaker, msg.content));
+    }
+    prompt.push_str("Judge: ");
+    Ok(prompt)
+}
+
+pub async fn chat_with_customer(
+    req: &ChatRequest,
+) -> Result<CustomerReply, Box<dyn std::error::Error>> {
+    let role = req.partner_role.as_deref().unwrap_or("ceo");
+
+    // ステートレスなチャットでは、会話履歴を先頭から再生して開示状態を復元する
+    let profile = ScenarioProfile::for_id(&req.scenario_id);
+    let mut state = DisclosureState::default();
+    // 最新のユーザー発言で新たに開示可能になった裏要件
+    let mut disclosed = Vec::new();
+    for msg in req.messages.iter().filter(|m| m.role == "user") {
+        disclosed = state.observe(&profile.hidden_facts, role, &msg.content);
+    }
+
+    let requirements = req.difficulty.as_deref().map(custom_requirements);
+    let reply = reply_as_customer(
+        &req.scenario_id,
+        role,
+        &req.messages,
+        &state,
+        req.diagram.as_ref(),
+        requirements.as_ref(),
+    )
+    .await?;
+    Ok(CustomerReply { reply, disclosed })
+}
+
+// 開示状態を踏まえて、クライアント役としての次の発言を生成する
+pub async fn reply_as_customer(
+    scenario_id: &str,
+    role: &str,
+    messages: &[ChatLog],
+    state: &DisclosureState,
+    diagram: Option<&Diagram>,
+    requirements: Option<&Requirements>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = gemini_url(&default_model_name());
 
     // 1. ベースとなるシステム指示の取得
     let mut system_instruction = String::new();
     let mut chat_history_start_index = 0;
 
-    if req.scenario_id == "custom" {
+    if scenario_id == "custom" {
         // カスタムの場合: フロントエンドからの system メッセージを採用
-        if let Some(first_msg) = req.messages.first() {
+        if let Some(first_msg) = messages.first() {
             if first_msg.role == "system" {
                 system_instruction = first_msg.content.clone();
                 chat_history_start_index = 1;
@@ -300,46 +562,40 @@ pub async fn chat_with_customer(req: &ChatRequest) -> Result<String, Box<dyn std
         if system_instruction.is_empty() {
             system_instruction = "あなたはシステムアーキテクチャのクライアントです。".to_string();
         }
+        // 数値の要件は審査員と同じ型付きの値を渡す
+        if let Some(requirements) = requirements {
+            system_instruction.push_str(&format!(
+                "\n\n【あなたが把握している要件 (Hidden_Context)】\n{}\n関連する話題について質問された場合にのみ、この値で答えてください。",
+                requirements.render(Locale::Ja)
+            ));
+        }
     } else {
-        let hidden_context = match req.scenario_id.as_str() {
-            "internal_tool" => {
-                "
-                あなたは「社内勤怠管理ツール」の発注担当者（総務部）です。
-                ITには詳しくありません。
-                【裏要件】
-                - 予算はとにかく安く済ませたい。
-                - 朝9時に社員50人が一斉にアクセスするが、それ以外は誰も使わない。
-                - データは消えると困るが、数分止まるくらいなら許容できる。
-            "
-            }
-            "sns_app" => {
-                "
-                あなたは「次世代SNSアプリ」のスタートアップCEOです。
-                野心的で、急成長を想定しています。
-                【裏要件】
-                - 世界中からアクセスがある想定。
-                - とにかく「サクサク動く」ことが最重要。
-                - 24時間365日止まってはいけない。
-            "
-            }
-            _ => "あなたは一般的なシステムの顧客です。",
-        };
-
+        // 裏要件は開示ポリシーに従い、開示可能になったものだけをプロンプトに含める
+        let profile = ScenarioProfile::for_id(scenario_id);
         system_instruction = format!(
             r#"
             {}
+            {}
             ユーザー（システムアーキテクト）からの質問に対して、上記の立場・要件に基づいて回答してください。
             回答は短潔に、かつ自然な会話口調で行ってください。
             "#,
-            hidden_context
+            profile.persona,
+            state.prompt_section(&profile.hidden_facts, role)
         );
     }
 
-    // パートナー役割の取得とプロンプト結合
-    let role = req.partner_role.as_deref().unwrap_or("ceo");
+    // パートナー役割に応じたプロンプトの結合
     let partner_instruction = get_partner_instruction(role);
 
-    let final_system_instruction = format!("{}\n\n{}", system_instruction, partner_instruction);
+    let mut final_system_instruction = format!("{}\n\n{}", system_instruction, partner_instruction);
+
+    // 設計図が添付されている場合は、要約したトポロジーを参照させる
+    if let Some(diagram) = diagram {
+        final_system_instruction.push_str(&format!(
+            "\n\n【アーキテクトが現在作成中の設計図】\n{}設計について聞かれた場合や、あなたの立場から気になる構成がある場合は、角括弧内のIDではなくコンポーネント名を挙げて具体的にコメントしてください。\n",
+            diagram.summarize()
+        ));
+    }
 
     let mut full_prompt = String::new();
     // ★修正箇所: system_instruction ではなく final_system_instruction を使用する
@@ -354,7 +610,7 @@ pub async fn chat_with_customer(req: &ChatRequest) -> Result<String, Box<dyn std
     full_prompt.push_str("\n\n--- 会話履歴 ---\n");
 
     // 会話履歴の構築
-    for (i, msg) in req.messages.iter().enumerate() {
+    for (i, msg) in messages.iter().enumerate() {
         if i < chat_history_start_index {
             continue;
         }
@@ -375,6 +631,7 @@ pub async fn chat_with_customer(req: &ChatRequest) -> Result<String, Box<dyn std
         contents: vec![Content {
             parts: vec![Part { text: full_prompt }],
         }],
+        generation_config: None,
     };
 
     let client = Client::new();
@@ -403,16 +660,99 @@ fn get_partner_instruction(role: &str) -> &'static str {
             "\n【重要：あなたの役割 - 非技術系オーナー (CEO)】\n",
             "あなたは技術に詳しくないビジネスオーナーです。夢やビジョンを語りますが、具体的な要件（数値）はあいまいで、気分で変わることがあります。\n",
             "\n",
-            "【最重要ルール：Hidden_Context情報の隠蔽】\n",
-            "あなたはシステム設定（Hidden_Context）として「正解の数値（ユーザー数や予算）」を知っていますが、**絶対にそれをそのまま答えないでください。**\n",
-            "前述の `Behavior_Rules` に「聞かれたら答える」とあっても、CEOの場合は**「曖昧にはぐらかす」**ことを優先してください。\n",
+            "【最重要ルール：情報の出し方】\n",
+            "「開示してよい情報」に含まれていない数値や事実は、あなた自身もまだ把握していないものとして扱ってください。\n",
             "\n",
             "【回答ガイドライン】\n",
-            "1. ユーザーから「ユーザー数は？」「予算は？」と聞かれても、まずは「うーん、世界中でバズるくらい！」「安く済ませてよ」などと**感覚的な言葉**で返してください。\n",
-            "2. 具体的な数字（例: 100万人、50万円）は使わず、「桁違いの規模」「お小遣い程度」のように言い換えてください。\n",
-            "3. ユーザーが困って「具体的なサーバーのスペックを決めるために必要なんです」などと食い下がってきた場合のみ、「まあ、強いて言うなら...」と渋々、少しだけヒントを出してください。\n",
+            "1. 開示してよい情報であっても、まずは「世界中でバズるくらい！」「安く済ませてよ」などの**感覚的な言葉**を添えて話してください。\n",
+            "2. 「まだ答えられない話題」について聞かれたら、「うーん、そこはまだ決めてないんだよね」などとはぐらかしてください。\n",
+            "3. 幅だけを伝えるよう指示された情報は、その幅以上に具体的な数字を作らないでください。\n",
             "4. 専門用語を使われても「よく分からないけど、実現できるの？」「なんかカッコいい感じで頼むよ」と返してください。"
         ),
         _ => "\n【役割】一般的なクライアントとして振る舞ってください。",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_requirements_only_contain_stated_values() {
+        let large = custom_requirements("large");
+        assert_eq!(large.dau, Some(10_000_000));
+        assert_eq!(large.availability_target, Some(99.99));
+        assert_eq!(large.regions, vec!["global"]);
+        assert!(large.compliance.is_empty());
+        assert_eq!(large.rto_minutes, None);
+        assert_eq!(large.data_volume_gb, None);
+
+        let medium = custom_requirements("medium");
+        assert!(medium.regions.is_empty());
+        assert!(medium.compliance.is_empty());
+
+        let small = custom_requirements("small");
+        assert_eq!(small.dau, Some(100));
+        assert_eq!(small.rpo_minutes, None);
+        assert_eq!(small.budget.and_then(|b| b.max_jpy), Some(5_000));
+    }
+
+    fn explain_request(messages: Value) -> ExplainRequest {
+        serde_json::from_value(serde_json::json!({
+            "scenario": {
+                "id": "sns_app",
+                "title": "次世代SNSアプリ",
+                "requirements": { "users": "100万DAU", "availability": "99.99%" }
+            },
+            "nodes": [
+                { "id": "lb", "type": "Load Balancer", "position": { "x": 0, "y": 0 } },
+                { "id": "app", "type": "App Server", "label": "投稿API", "position": { "x": 0, "y": 100 } }
+            ],
+            "edges": [{ "source": "lb", "target": "app" }],
+            "evaluation": {
+                "totalScore": 62,
+                "details": {
+                    "availability": 40, "scalability": 70, "security": 65,
+                    "maintainability": 70, "costEfficiency": 60, "feasibility": 65
+                },
+                "feedback": "単一AZ構成です。",
+                "improvement": "Multi-AZ にしてください。"
+            },
+            "messages": messages
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn explain_prompt_includes_design_evaluation_and_history() {
+        let req = explain_request(serde_json::json!([
+            { "role": "system", "content": "内部向けの指示" },
+            { "role": "user", "content": "なぜ可用性が40点なのですか？" },
+            { "role": "model", "content": "単一AZだからです。" },
+            { "role": "user", "content": "どうすれば上がりますか？" }
+        ]));
+        let prompt = explain_prompt(&req).unwrap();
+
+        assert!(!prompt.contains("{{"), "{}", prompt);
+        // 型付きの要件を日本語で表示したもの
+        assert!(prompt.contains("requirementsDisplay"));
+        assert!(prompt.contains("投稿API"));
+        assert!(prompt.contains("\"totalScore\": 62"));
+        // system の発言は履歴に含めず、話者を付けて並べる
+        assert!(!prompt.contains("内部向けの指示"));
+        let history = prompt.split("--- 会話履歴 ---").nth(1).unwrap();
+        assert_eq!(
+            history.trim(),
+            "User: なぜ可用性が40点なのですか？\nJudge: 単一AZだからです。\nUser: どうすれば上がりますか？\nJudge:"
+        );
+    }
+
+    #[test]
+    fn explain_request_reads_the_flattened_diagram() {
+        let req = explain_request(serde_json::json!([]));
+        assert_eq!(req.diagram.nodes.len(), 2);
+        assert_eq!(req.diagram.edges.len(), 1);
+        assert_eq!(req.evaluation.details.availability, 40);
+        assert!(explain_prompt(&req).unwrap().ends_with("Judge: "));
+    }
+}
@@ -1 +1,4 @@
+pub mod catalog;
 pub mod gemini;
+pub mod persistence;
+pub mod templates;
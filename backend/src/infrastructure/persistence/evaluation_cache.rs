@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+use crate::domain::model::evaluation::EvaluationResult;
+use crate::domain::repository::evaluation_cache::EvaluationCache;
+
+// メモリ上のLRUキャッシュ
+// ディレクトリが指定されている場合は1件1ファイルのJSONにも書き出し、再起動後も利用する
+pub struct LruEvaluationCache {
+    entries: Mutex<LruCache<String, EvaluationResult>>,
+    dir: Option<PathBuf>,
+}
+
+impl LruEvaluationCache {
+    pub fn new(capacity: usize, dir: Option<PathBuf>) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            dir,
+        }
+    }
+
+    fn path_for(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", key)))
+    }
+}
+
+#[async_trait]
+impl EvaluationCache for LruEvaluationCache {
+    async fn get(&self, key: &str) -> Option<EvaluationResult> {
+        if let Some(result) = self.entries.lock().await.get(key) {
+            return Some(result.clone());
+        }
+
+        let json_str = fs::read_to_string(self.path_for(key)?).await.ok()?;
+        let result: EvaluationResult = serde_json::from_str(&json_str).ok()?;
+        self.entries
+            .lock()
+            .await
+            .put(key.to_string(), result.clone());
+        Some(result)
+    }
+
+    async fn put(&self, key: &str, result: &EvaluationResult) {
+        self.entries
+            .lock()
+            .await
+            .put(key.to_string(), result.clone());
+
+        if let (Some(dir), Some(path)) = (self.dir.as_ref(), self.path_for(key)) {
+            let written = match serde_json::to_string(result) {
+                Ok(json_str) => match fs::create_dir_all(dir).await {
+                    Ok(()) => fs::write(path, json_str).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = written {
+                eprintln!("Evaluation cache write error: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::domain::model::project::ProjectFile;
+use crate::domain::project;
+use crate::domain::repository::project::ProjectRepository;
+
+// プロジェクトを1件1ファイルのJSONとして保存するリポジトリ
+// 古い形式のまま置かれたファイルも、読み込む際に現行の形式へ移行する
+pub struct FileProjectRepository {
+    dir: PathBuf,
+}
+
+impl FileProjectRepository {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for FileProjectRepository {
+    async fn find(&self, id: Uuid) -> Result<Option<ProjectFile>, Box<dyn std::error::Error>> {
+        let content = match fs::read_to_string(self.path_for(id)).await {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(project::load(&content)?.project))
+    }
+
+    async fn create(&self, project: &ProjectFile) -> Result<bool, Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir).await?;
+        let json_str = serde_json::to_string_pretty(project)?;
+        // 一時ファイルに書き切ってからハードリンクで配置する
+        // 書き込みの途中で失敗しても途中までのファイルが残らず、
+        // リンクの作成は既存のファイルがあれば失敗するため、同時に取り込まれても上書きしない
+        let temp = self
+            .dir
+            .join(format!(".{}.{}.tmp", project.project_id, Uuid::new_v4()));
+        let linked = write_new(&temp, json_str.as_bytes())
+            .await
+            .and(fs::hard_link(&temp, self.path_for(project.project_id)).await);
+        let _ = fs::remove_file(&temp).await;
+        match linked {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+async fn write_new(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .await?;
+    file.write_all(content).await?;
+    file.sync_all().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn create_does_not_overwrite_an_existing_project() {
+        let dir = std::env::temp_dir().join(format!("projects-{}", Uuid::new_v4()));
+        let repo = FileProjectRepository::new(&dir);
+        let project = project::load(include_str!(
+            "../../domain/project/fixtures/v1_frontend.json"
+        ))
+        .unwrap()
+        .project;
+        assert!(repo.create(&project).await.unwrap());
+
+        let mut other = project.clone();
+        other.memo = "別のプロジェクト".to_string();
+        assert!(!repo.create(&other).await.unwrap());
+        let stored = repo.find(project.project_id).await.unwrap().unwrap();
+        assert_eq!(stored.memo, project.memo);
+
+        // 一時ファイルは残らない
+        let mut entries = fs::read_dir(&dir).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+        assert_eq!(names, vec![format!("{}.json", project.project_id)]);
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+}
@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use lru::LruCache;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use tokio::fs;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::domain::model::session::ChatSession;
+use crate::domain::repository::session::{SessionLock, SessionRepository};
+
+// セッションを1件1ファイルのJSONとして保存するリポジトリ
+// 最近使ったセッションはメモリにも保持し (LRU)、2回目以降はファイルを読まない
+pub struct FileSessionRepository {
+    dir: PathBuf,
+    cache: Mutex<LruCache<Uuid, ChatSession>>,
+    // 使用中のセッションのロック (誰も保持していないものは次の取得時に取り除く)
+    locks: std::sync::Mutex<HashMap<Uuid, Weak<Mutex<()>>>>,
+}
+
+impl FileSessionRepository {
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            dir: dir.into(),
+            cache: Mutex::new(LruCache::new(capacity)),
+            locks: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn path_for(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+#[async_trait]
+impl SessionRepository for FileSessionRepository {
+    async fn lock(&self, id: Uuid) -> SessionLock {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            locks.retain(|_, lock| lock.strong_count() > 0);
+            match locks.get(&id).and_then(Weak::upgrade) {
+                Some(lock) => lock,
+                None => {
+                    let lock = Arc::new(Mutex::new(()));
+                    locks.insert(id, Arc::downgrade(&lock));
+                    lock
+                }
+            }
+        };
+        SessionLock::new(lock.lock_owned().await)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<ChatSession>, Box<dyn std::error::Error>> {
+        if let Some(session) = self.cache.lock().await.get(&id) {
+            return Ok(Some(session.clone()));
+        }
+
+        let json_str = match fs::read_to_string(self.path_for(id)).await {
+            Ok(s) => s,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let session: ChatSession = serde_json::from_str(&json_str)?;
+        self.cache.lock().await.put(id, session.clone());
+        Ok(Some(session))
+    }
+
+    async fn save(&self, session: &ChatSession) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir).await?;
+        let json_str = serde_json::to_string_pretty(session)?;
+        fs::write(self.path_for(session.id), json_str).await?;
+        self.cache.lock().await.put(session.id, session.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_repo(capacity: usize) -> FileSessionRepository {
+        let dir = std::env::temp_dir().join(format!("sessions-{}", Uuid::new_v4()));
+        FileSessionRepository::new(dir, capacity)
+    }
+
+    #[tokio::test]
+    async fn evicted_sessions_are_reloaded_from_disk() {
+        let repo = temp_repo(1);
+        let first = ChatSession::new("internal_tool".into(), "ceo".into(), None, None);
+        let second = ChatSession::new("sns_app".into(), "cto".into(), None, None);
+        repo.save(&first).await.unwrap();
+        repo.save(&second).await.unwrap();
+
+        assert_eq!(repo.cache.lock().await.len(), 1);
+        let loaded = repo.find(first.id).await.unwrap().unwrap();
+        assert_eq!(loaded.scenario_id, "internal_tool");
+        let _ = fs::remove_dir_all(&repo.dir).await;
+    }
+
+    #[tokio::test]
+    async fn concurrent_updates_to_one_session_are_serialized() {
+        let repo = Arc::new(temp_repo(8));
+        let session = ChatSession::new("internal_tool".into(), "ceo".into(), None, None);
+        repo.save(&session).await.unwrap();
+
+        let tasks: Vec<_> = (0..2)
+            .map(|i| {
+                let repo = repo.clone();
+                let id = session.id;
+                tokio::spawn(async move {
+                    let _guard = repo.lock(id).await;
+                    let mut session = repo.find(id).await.unwrap().unwrap();
+                    // LLM の応答待ちに相当
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    session.push("user", format!("message {}", i));
+                    repo.save(&session).await.unwrap();
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let stored = repo.find(session.id).await.unwrap().unwrap();
+        assert_eq!(stored.messages.len(), 2);
+        assert!(
+            repo.locks
+                .lock()
+                .unwrap()
+                .values()
+                .all(|l| l.strong_count() == 0)
+        );
+        let _ = fs::remove_dir_all(&repo.dir).await;
+    }
+}
@@ -0,0 +1,3 @@
+pub mod evaluation_cache;
+pub mod file_project;
+pub mod file_session;
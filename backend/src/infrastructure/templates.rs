@@ -0,0 +1,65 @@
+use std::sync::OnceLock;
+
+use crate::domain::analysis::{edge_rules, nesting, properties};
+use crate::domain::model::catalog::ComponentCatalog;
+use crate::domain::model::template::TemplateLibrary;
+use crate::infrastructure::catalog::component_catalog;
+
+static TEMPLATES: OnceLock<TemplateLibrary> = OnceLock::new();
+
+// 組み込みのテンプレート集 (初回のみ読み込む)
+// 初学者の手本になるため、architecture_defs.json に照らして警告が1つでもあれば起動時に止める
+pub fn template_library() -> &'static TemplateLibrary {
+    TEMPLATES.get_or_init(|| {
+        let library = TemplateLibrary::from_json(include_str!("architecture_templates.json"))
+            .expect("Failed to parse architecture_templates.json");
+        let problems = validate(&library, component_catalog());
+        if !problems.is_empty() {
+            panic!("Invalid templates:\n{}", problems.join("\n"));
+        }
+        library
+    })
+}
+
+// テンプレートごとの未知の型・接続・プロパティ・入れ子の問題
+fn validate(library: &TemplateLibrary, catalog: &ComponentCatalog) -> Vec<String> {
+    let mut problems = Vec::new();
+    for template in library.templates() {
+        let diagram = &template.diagram;
+        let messages = catalog
+            .unknown_nodes(diagram)
+            .into_iter()
+            .map(|n| format!("unknown type {} ({})", n.type_label, n.id))
+            .chain(
+                edge_rules::validate_edges(diagram, catalog)
+                    .into_iter()
+                    .map(|w| w.message),
+            )
+            .chain(
+                properties::validate_properties(diagram, catalog)
+                    .into_iter()
+                    .map(|i| i.message),
+            )
+            .chain(
+                nesting::validate_nesting(diagram, catalog)
+                    .into_iter()
+                    .map(|i| i.message),
+            );
+        problems.extend(messages.map(|m| format!("[{}] {}", template.id, m)));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::catalog::test_catalog;
+
+    #[test]
+    fn bundled_templates_are_valid() {
+        let library =
+            TemplateLibrary::from_json(include_str!("architecture_templates.json")).unwrap();
+        assert!(library.templates().count() > 0);
+        assert_eq!(validate(&library, &test_catalog()), Vec::<String>::new());
+    }
+}
@@ -3,26 +3,89 @@ mod infrastructure;
 
 use axum::{
     Json, Router,
-    http::Method,
+    extract::{Path, Query, State},
+    http::{Method, StatusCode, header},
     response::IntoResponse,
     routing::{get, post},
 };
 use reqwest::Client;
 use reqwest::header::HeaderValue;
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
+use uuid::Uuid;
 
 use crate::domain::model::url_shorten::{ShortenRequest, ShortenResponse};
+use domain::analysis::{constraints, edge_rules, exposure, nesting, properties, threat_model};
+use domain::export;
+use domain::import;
+use domain::model::canonical::design_hash;
 use domain::model::chat::ChatRequest;
+use domain::model::diagram::{Diagram, Position};
+use domain::model::evaluation::{EnsembleRequest, EvaluationResult, ExplainRequest};
+use domain::model::interchange::ImportRequest;
+use domain::model::project::ProjectFile;
+use domain::model::scenario::ScenarioProfile;
+use domain::model::session::{ChatSession, CreateSessionRequest, SessionMessageRequest};
+use domain::model::template::{InstantiateRequest, TemplateQuery};
+use domain::project;
+use domain::repository::evaluation_cache::EvaluationCache;
+use domain::repository::project::ProjectRepository;
+use domain::repository::session::SessionRepository;
+use infrastructure::catalog::component_catalog;
 use infrastructure::gemini::client as gemini_client;
+use infrastructure::persistence::evaluation_cache::LruEvaluationCache;
+use infrastructure::persistence::file_project::FileProjectRepository;
+use infrastructure::persistence::file_session::FileSessionRepository;
+use infrastructure::templates::template_library;
+
+#[derive(Clone)]
+struct AppState {
+    sessions: Arc<dyn SessionRepository>,
+    evaluations: Arc<dyn EvaluationCache>,
+    projects: Arc<dyn ProjectRepository>,
+}
 
 #[tokio::main]
 async fn main() {
     println!("Starting server without Database...");
 
+    // コンポーネント定義は起動時に読み込み、不備があればここで停止する
+    let catalog = component_catalog();
+    println!(
+        "Loaded {} component definitions",
+        catalog.components().count()
+    );
+    println!(
+        "Loaded {} architecture templates",
+        template_library().templates().count()
+    );
+
     let frontend_origin =
         env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "http://localhost:5173".to_string());
 
+    // 1. セッション保存先の設定
+    let session_dir = env::var("SESSION_STORE_DIR").unwrap_or_else(|_| "data/sessions".to_string());
+    println!("Chat sessions are stored in: {}", session_dir);
+    let session_cache_size = env::var("SESSION_CACHE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(256);
+    let project_dir = env::var("PROJECT_STORE_DIR").unwrap_or_else(|_| "data/projects".to_string());
+    println!("Projects are stored in: {}", project_dir);
+    // 評価結果キャッシュの設定 (EVAL_CACHE_DIR 指定時のみファイルにも保存)
+    let eval_cache_size = env::var("EVAL_CACHE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(256);
+    let eval_cache_dir = env::var("EVAL_CACHE_DIR").ok().map(PathBuf::from);
+    let state = AppState {
+        sessions: Arc::new(FileSessionRepository::new(session_dir, session_cache_size)),
+        evaluations: Arc::new(LruEvaluationCache::new(eval_cache_size, eval_cache_dir)),
+        projects: Arc::new(FileProjectRepository::new(project_dir)),
+    };
+
     // 2. CORS設定
     let cors = CorsLayer::new()
         .allow_origin(
@@ -37,10 +100,39 @@ async fn main() {
     let app = Router::new()
         .route("/", get(|| async { "Hello, Architecture (Stateless)!" }))
         .route("/api/evaluate", post(evaluate_architecture))
+        .route("/api/evaluate/ensemble", post(evaluate_ensemble))
+        .route("/api/evaluate/explain", post(explain_evaluation))
+        .route("/api/validate", post(validate_diagram))
+        .route("/api/analysis/exposure", post(analyze_exposure))
+        .route("/api/analysis/threat-model", post(generate_threat_model))
+        .route("/api/analysis/capacity", post(plan_capacity))
+        .route("/api/export/mermaid", post(export_mermaid))
+        .route("/api/export/dot", post(export_dot))
+        .route("/api/export/svg", post(export_svg))
+        .route("/api/export/plantuml", post(export_plantuml))
+        .route("/api/export/structurizr", post(export_structurizr))
+        .route("/api/export/drawio", post(export_drawio))
+        .route("/api/export/terraform", post(export_terraform))
+        .route("/api/import/drawio", post(import_drawio))
+        .route("/api/import/terraform", post(import_terraform))
+        .route("/api/import/compose", post(import_compose))
+        .route("/api/import/kubernetes", post(import_kubernetes))
+        .route("/api/templates", get(list_templates))
+        .route(
+            "/api/templates/{id}/instantiate",
+            post(instantiate_template),
+        )
         .route("/api/chat", post(handle_chat))
+        .route("/api/sessions", post(create_session))
+        .route("/api/sessions/{id}", get(get_session))
+        .route("/api/sessions/{id}/messages", post(post_session_message))
         .route("/api/projects", post(mock_save_project))
+        .route("/api/projects/schema", get(project_schema))
+        .route("/api/projects/import", post(import_project))
+        .route("/api/projects/{id}/export", get(export_project))
         .route("/api/shorten", post(shorten_url_handler))
-        .layer(cors);
+        .layer(cors)
+        .with_state(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
     println!("Backend listening on 0.0.0.0:8080");
@@ -49,33 +141,368 @@ async fn main() {
 
 // --- ハンドラー関数 ---
 
-async fn evaluate_architecture(Json(payload): Json<serde_json::Value>) -> impl IntoResponse {
+async fn evaluate_architecture(
+    State(state): State<AppState>,
+    Json(payload): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    let diagram = match parse_design(&payload) {
+        Ok(diagram) => diagram,
+        Err(rejection) => return rejection,
+    };
+
+    // 同じ設計・同じプロンプト・同じモデルの評価はキャッシュから返す
+    let cache_key = design_hash(
+        &payload,
+        &gemini_client::prompt_version(),
+        &gemini_client::default_model_name(),
+    );
+    if let Some(cached) = state.evaluations.get(&cache_key).await {
+        println!("Evaluation cache hit: {}", cache_key);
+        return (
+            StatusCode::OK,
+            Json(evaluation_response(cached, &payload, &diagram, true)),
+        );
+    }
+
     println!("Evaluating with Gemini...");
-    match gemini_client::evaluate_with_gemini(&payload).await {
+    // Box<dyn Error> は Send ではないため、キャッシュ書き込みの await より前に文字列化しておく
+    let response = gemini_client::evaluate_with_gemini(&payload, &diagram)
+        .await
+        .map_err(|e| e.to_string());
+    let json = match response {
         Ok(ai_response_text) => {
-            let clean_text = ai_response_text
-                .replace("```json", "")
-                .replace("```", "")
-                .trim()
-                .to_string();
+            let clean_text = gemini_client::strip_code_fence(&ai_response_text);
+            if let Ok(result) = serde_json::from_str::<EvaluationResult>(&clean_text) {
+                // キャッシュにはLLMの素の評価を保存し、制約による上限は応答時に適用する
+                state.evaluations.put(&cache_key, &result).await;
+                return (
+                    StatusCode::OK,
+                    Json(evaluation_response(result, &payload, &diagram, false)),
+                );
+            }
             match serde_json::from_str::<serde_json::Value>(&clean_text) {
-                Ok(json) => Json(json),
-                Err(_) => Json(serde_json::json!({
+                Ok(json) => json,
+                Err(_) => serde_json::json!({
                     "score": 0, "feedback": clean_text, "status": "partial_success"
-                })),
+                }),
             }
         }
         Err(e) => {
             eprintln!("Gemini Error: {}", e);
-            Json(serde_json::json!({ "score": 0, "feedback": e.to_string(), "status": "error" }))
+            serde_json::json!({ "score": 0, "feedback": e.to_string(), "status": "error" })
+        }
+    };
+    (StatusCode::OK, Json(json))
+}
+
+// 評価・見積もりの前に設計図として解析する
+// 解析できない設計を通すと機械的な検査と上限がすべて外れてしまうため、400 で返す
+fn parse_design(
+    payload: &serde_json::Value,
+) -> Result<Diagram, (StatusCode, Json<serde_json::Value>)> {
+    serde_json::from_value::<Diagram>(payload.clone()).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "message": format!("設計図を解析できませんでした: {}", e),
+                "status": "error"
+            })),
+        )
+    })
+}
+
+// 機械的な制約でスコアを切り詰め、どの制約が効いたかを添えて返す
+fn evaluation_response(
+    mut result: EvaluationResult,
+    payload: &serde_json::Value,
+    diagram: &Diagram,
+    cached: bool,
+) -> serde_json::Value {
+    let findings = gemini_client::machine_findings(payload, diagram);
+    let bounded = constraints::apply(&mut result, &findings);
+    let mut json = serde_json::to_value(&result).unwrap_or_default();
+    // カタログに存在しない型のノード (フロントエンドと定義がずれている場合など)
+    json["unknownNodes"] = serde_json::json!(component_catalog().unknown_nodes(diagram));
+    json["constraints"] = serde_json::json!(findings);
+    json["boundedScores"] = serde_json::json!(bounded);
+    json["cached"] = serde_json::json!(cached);
+    json
+}
+
+async fn evaluate_ensemble(Json(payload): Json<EnsembleRequest>) -> impl IntoResponse {
+    let design = serde_json::Value::Object(payload.design);
+    let diagram = match parse_design(&design) {
+        Ok(diagram) => diagram,
+        Err(rejection) => return rejection,
+    };
+    println!(
+        "Evaluating with Gemini ({} samples)...",
+        payload.ensemble.samples
+    );
+    match gemini_client::evaluate_ensemble(&design, &diagram, &payload.ensemble).await {
+        Ok(mut result) => {
+            let findings = gemini_client::machine_findings(&design, &diagram);
+            let bounded = constraints::apply(&mut result.result, &findings);
+            let mut json = serde_json::to_value(result).unwrap_or_default();
+            json["unknownNodes"] = serde_json::json!(component_catalog().unknown_nodes(&diagram));
+            json["constraints"] = serde_json::json!(findings);
+            json["boundedScores"] = serde_json::json!(bounded);
+            (StatusCode::OK, Json(json))
+        }
+        Err(e) => {
+            eprintln!("Gemini Error: {}", e);
+            (
+                StatusCode::OK,
+                Json(
+                    serde_json::json!({ "score": 0, "feedback": e.to_string(), "status": "error" }),
+                ),
+            )
+        }
+    }
+}
+
+async fn explain_evaluation(Json(payload): Json<ExplainRequest>) -> impl IntoResponse {
+    println!(
+        "Explaining evaluation (total: {})",
+        payload.evaluation.total_score
+    );
+    match gemini_client::explain_evaluation(&payload).await {
+        Ok(reply) => Json(serde_json::json!({ "reply": reply, "status": "success" })),
+        Err(e) => {
+            eprintln!("Explain Error: {}", e);
+            Json(serde_json::json!({ "reply": e.to_string(), "status": "error" }))
         }
     }
 }
 
+async fn validate_diagram(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let mut report = validation_report(&payload);
+    report["status"] = serde_json::json!("success");
+    Json(report)
+}
+
+fn validation_report(diagram: &Diagram) -> serde_json::Value {
+    let catalog = component_catalog();
+    let warnings = edge_rules::validate_edges(diagram, catalog);
+    let property_issues = properties::validate_properties(diagram, catalog);
+    let nesting_issues = nesting::validate_nesting(diagram, catalog);
+    let unknown = catalog.unknown_nodes(diagram);
+    let valid = unknown.is_empty()
+        && property_issues.is_empty()
+        && !warnings
+            .iter()
+            .any(|w| matches!(w.severity, edge_rules::Severity::Error))
+        && !nesting_issues
+            .iter()
+            .any(|i| matches!(i.severity, edge_rules::Severity::Error));
+    serde_json::json!({
+        "valid": valid,
+        "warnings": warnings,
+        "propertyIssues": property_issues,
+        "nestingIssues": nesting_issues,
+        "unknownNodes": unknown
+    })
+}
+
+async fn analyze_exposure(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let report = exposure::analyze(&payload, component_catalog());
+    Json(serde_json::json!({
+        "exposure": report,
+        "status": "success"
+    }))
+}
+
+// 構造化データ (JSON) と、そのまま配布できる Markdown の両方を返す
+async fn generate_threat_model(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let model = threat_model::generate(&payload, component_catalog());
+    let markdown = model.to_markdown(&payload);
+    Json(serde_json::json!({
+        "threatModel": model,
+        "markdown": markdown,
+        "status": "success"
+    }))
+}
+
+// 負荷はシナリオの要件から読み取るため、評価と同じ形式のペイロードを受け取る
+async fn plan_capacity(Json(payload): Json<serde_json::Value>) -> impl IntoResponse {
+    let diagram = match parse_design(&payload) {
+        Ok(diagram) => diagram,
+        Err(rejection) => return rejection,
+    };
+    match gemini_client::capacity_plan(&payload, &diagram) {
+        Some(plan) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "capacityPlan": plan,
+                "status": "success"
+            })),
+        ),
+        None => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "シナリオの要件から利用者数やリクエスト数を読み取れませんでした",
+                "status": "error"
+            })),
+        ),
+    }
+}
+
+async fn export_mermaid(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let mermaid = export::mermaid::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "mermaid": mermaid,
+        "status": "success"
+    }))
+}
+
+async fn export_dot(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let dot = export::dot::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "dot": dot,
+        "status": "success"
+    }))
+}
+
+// 共有ページ等から画像として直接参照できるよう、SVG をそのまま返す
+async fn export_svg(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let svg = export::svg::render(&payload, component_catalog());
+    ([(header::CONTENT_TYPE, "image/svg+xml")], svg)
+}
+
+// 設計書用に、PlantUML の配置図として返す
+async fn export_plantuml(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let plantuml = export::plantuml::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "plantuml": plantuml,
+        "status": "success"
+    }))
+}
+
+// C4 モデル (コンテナ図・配置図) を Structurizr DSL で返す
+async fn export_structurizr(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let dsl = export::structurizr::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "structurizr": dsl,
+        "status": "success"
+    }))
+}
+
+async fn export_drawio(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let xml = export::drawio::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "drawio": xml,
+        "status": "success"
+    }))
+}
+
+// 設計図に対応する AWS の Terraform の雛形を返す
+async fn export_terraform(Json(payload): Json<Diagram>) -> impl IntoResponse {
+    let terraform = export::terraform::render(&payload, component_catalog());
+    Json(serde_json::json!({
+        "terraform": terraform,
+        "status": "success"
+    }))
+}
+
+// draw.io のファイル (mxGraph XML) を設計図として取り込む
+async fn import_drawio(Json(payload): Json<ImportRequest>) -> impl IntoResponse {
+    import_diagram(&payload.content, import::drawio::import, "draw.io ファイル")
+}
+
+async fn import_terraform(Json(payload): Json<ImportRequest>) -> impl IntoResponse {
+    import_diagram(
+        &payload.content,
+        import::terraform::import,
+        "Terraform の JSON",
+    )
+}
+
+async fn import_compose(Json(payload): Json<ImportRequest>) -> impl IntoResponse {
+    import_diagram(
+        &payload.content,
+        import::compose::import,
+        "compose ファイル",
+    )
+}
+
+async fn import_kubernetes(Json(payload): Json<ImportRequest>) -> impl IntoResponse {
+    import_diagram(
+        &payload.content,
+        import::kubernetes::import,
+        "Kubernetes のマニフェスト",
+    )
+}
+
+// 各形式の取り込みに共通する応答 (読み込めなければ 400 とし、label で形式を示す)
+fn import_diagram(
+    content: &str,
+    importer: import::Importer,
+    label: &str,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match importer(content, component_catalog()) {
+        Ok(result) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "diagram": result.diagram,
+                "skipped": result.skipped,
+                "status": "success"
+            })),
+        ),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "message": format!("{}を読み込めませんでした: {}", label, e),
+                "status": "error"
+            })),
+        ),
+    }
+}
+
+async fn list_templates(Query(query): Query<TemplateQuery>) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "templates": template_library().search(&query),
+        "status": "success"
+    }))
+}
+
+// テンプレートを新しいIDで複製し、指定された位置に置いた設計図を返す
+async fn instantiate_template(
+    Path(id): Path<String>,
+    payload: Option<Json<InstantiateRequest>>,
+) -> impl IntoResponse {
+    let Some(template) = template_library().get(&id) else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "message": format!("Template {} not found", id),
+                "status": "error"
+            })),
+        );
+    };
+    let origin = payload
+        .and_then(|Json(p)| p.position)
+        .unwrap_or(Position { x: 0.0, y: 0.0 });
+    let diagram = template.instantiate(&origin);
+    let validation = validation_report(&diagram);
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "templateId": template.id,
+            "diagram": diagram,
+            "validation": validation,
+            "status": "success"
+        })),
+    )
+}
+
 async fn handle_chat(Json(payload): Json<ChatRequest>) -> impl IntoResponse {
     println!("Chat request for scenario: {}", payload.scenario_id);
     match gemini_client::chat_with_customer(&payload).await {
-        Ok(reply) => Json(serde_json::json!({ "reply": reply, "status": "success" })),
+        Ok(res) => Json(serde_json::json!({
+            "reply": res.reply,
+            "disclosed": res.disclosed,
+            "status": "success"
+        })),
         Err(e) => {
             eprintln!("Chat Error: {}", e);
             Json(serde_json::json!({ "reply": e.to_string(), "status": "error" }))
@@ -83,6 +510,139 @@ async fn handle_chat(Json(payload): Json<ChatRequest>) -> impl IntoResponse {
     }
 }
 
+async fn create_session(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateSessionRequest>,
+) -> impl IntoResponse {
+    if let Err(message) = payload.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "message": message, "status": "error" })),
+        );
+    }
+    let role = payload.partner_role.unwrap_or_else(|| "ceo".to_string());
+    let session = ChatSession::new(
+        payload.scenario_id,
+        role,
+        payload.difficulty,
+        payload.system_prompt,
+    );
+
+    match state.sessions.save(&session).await {
+        Ok(()) => (
+            StatusCode::CREATED,
+            Json(serde_json::json!({ "session": session, "status": "success" })),
+        ),
+        Err(e) => {
+            eprintln!("Session Save Error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+            )
+        }
+    }
+}
+
+async fn get_session(State(state): State<AppState>, Path(id): Path<Uuid>) -> impl IntoResponse {
+    match state.sessions.find(id).await {
+        Ok(Some(session)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({ "session": session, "status": "success" })),
+        ),
+        Ok(None) => session_not_found(id),
+        Err(e) => {
+            eprintln!("Session Load Error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+            )
+        }
+    }
+}
+
+async fn post_session_message(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SessionMessageRequest>,
+) -> impl IntoResponse {
+    // 同じセッションへの同時投稿でターンが失われないよう、保存までを直列化する
+    let _guard = state.sessions.lock(id).await;
+    let mut session = match state.sessions.find(id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return session_not_found(id),
+        Err(e) => {
+            eprintln!("Session Load Error: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+            );
+        }
+    };
+
+    // 永続化された開示状態に今回の発言を反映する
+    let profile = ScenarioProfile::for_id(&session.scenario_id);
+    let disclosed = session.disclosure.observe(
+        &profile.hidden_facts,
+        &session.partner_role,
+        &payload.content,
+    );
+    session.push("user", payload.content);
+
+    let requirements = session
+        .difficulty
+        .as_deref()
+        .map(gemini_client::custom_requirements);
+    let reply = match gemini_client::reply_as_customer(
+        &session.scenario_id,
+        &session.partner_role,
+        &session.messages,
+        &session.disclosure,
+        payload.diagram.as_ref(),
+        requirements.as_ref(),
+    )
+    .await
+    {
+        Ok(reply) => reply,
+        Err(e) => {
+            // 失敗したターンは保存せず、同じ発言で再送できるようにする
+            eprintln!("Chat Error: {}", e);
+            return (
+                StatusCode::OK,
+                Json(serde_json::json!({ "reply": e.to_string(), "status": "error" })),
+            );
+        }
+    };
+    session.push("model", reply.clone());
+
+    // 保存できなかったターンは再起動で失われるため、成功として返さない
+    if let Err(e) = state.sessions.save(&session).await {
+        eprintln!("Session Save Error: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+        );
+    }
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "reply": reply,
+            "disclosed": disclosed,
+            "status": "success"
+        })),
+    )
+}
+
+fn session_not_found(id: Uuid) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "message": format!("Session {} not found", id),
+            "status": "error"
+        })),
+    )
+}
+
 async fn mock_save_project(Json(payload): Json<serde_json::Value>) -> impl IntoResponse {
     println!("Mock Save Project: {:?}", payload.get("title"));
     println!("(Database is disabled, so data is not persisted)");
@@ -93,6 +653,96 @@ async fn mock_save_project(Json(payload): Json<serde_json::Value>) -> impl IntoR
     )
 }
 
+// 現行の保存形式の JSON Schema
+async fn project_schema() -> impl IntoResponse {
+    Json(ProjectFile::json_schema())
+}
+
+// どの版の保存ファイルも現行の形式に移行してから保存する
+async fn import_project(
+    State(state): State<AppState>,
+    Json(payload): Json<ImportRequest>,
+) -> impl IntoResponse {
+    let loaded = match project::load(&payload.content) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "message": format!("プロジェクトファイルを読み込めませんでした: {}", e),
+                    "status": "error"
+                })),
+            );
+        }
+    };
+    // 既存のプロジェクトは上書きしない (別のプロジェクトのファイルで置き換わるのを防ぐ)
+    match state.projects.create(&loaded.project).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(serde_json::json!({
+                    "message": format!("Project {} already exists", loaded.project.project_id),
+                    "status": "error"
+                })),
+            );
+        }
+        Err(e) => {
+            eprintln!("Project Save Error: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+            );
+        }
+    }
+    println!(
+        "Imported project {} (format v{})",
+        loaded.project.project_id, loaded.migrated_from
+    );
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "project": loaded.project,
+            "migratedFrom": loaded.migrated_from,
+            "dropped": loaded.dropped,
+            "status": "success"
+        })),
+    )
+}
+
+// 保存ファイルとしてそのままダウンロードできるよう、プロジェクトの JSON を添付ファイルとして返す
+async fn export_project(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> axum::response::Response {
+    match state.projects.find(id).await {
+        Ok(Some(project)) => (
+            [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.json\"", id),
+            )],
+            Json(project),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "message": format!("Project {} not found", id),
+                "status": "error"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Project Load Error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "message": e.to_string(), "status": "error" })),
+            )
+                .into_response()
+        }
+    }
+}
+
 async fn shorten_url_handler(
     Json(payload): Json<ShortenRequest>,
 ) -> Result<Json<ShortenResponse>, String> {
@@ -35,7 +35,7 @@ import { MemoPad } from "./MemoPad";
 import { EvaluationPanel } from "./EvaluationPanel";
 import { v4 as uuidv4 } from "uuid";
 import { saveProjectToLocalFile } from "../utils/fileHandler";
-import { nodeTypes } from "../constants/nodeTypes";
+import { GROUP_TYPES, nodeTypes } from "../constants/nodeTypes";
 import { PropertiesPanel } from "./PropertiesPanel";
 import { HelpModal } from "./HelpModal";
 
@@ -56,15 +56,6 @@ const onDragOver = (event: React.DragEvent) => {
   event.dataTransfer.dropEffect = "move";
 };
 
-// グループとして扱うタイプ定義（新規作成時のラベル判定用）
-const GROUP_TYPES = [
-  "VPC",
-  "VPC (Network)",
-  "Availability Zone",
-  "Subnet",
-  "Security Group",
-];
-
 function ArchitectureFlow({
   selectedScenario,
   onBackToSelection,
@@ -126,21 +117,15 @@ function ArchitectureFlow({
         const difficultySpecs = {
           small: {
             scale: "小規模（個人開発・社内ツール）",
-            users: "50〜100人程度",
-            budget: "月額5,000円以内 (可能な限り安く)",
             constraint:
               "運用コストをかけられないため、メンテナンスフリーな構成を好む",
           },
           medium: {
             scale: "中規模（急成長スタートアップ）",
-            users: "10万DAU, ピーク時秒間100リクエスト",
-            budget: "月額50万円〜100万円",
             constraint: "急激なアクセス増に耐えられるスケーラビリティが必須",
           },
           large: {
             scale: "大規模（ミッションクリティカル）",
-            users: "1000万ユーザー, グローバル展開",
-            budget: "無制限（可用性とレイテンシが最優先）",
             constraint:
               "単一障害点(SPOF)の完全排除と、データロス発生時の法的リスク回避",
           },
@@ -155,8 +140,7 @@ Scenario:
   Description: "${currentScenario.description}"
   Scale: "${spec.scale}"
 Hidden_Context:
-  Users: "${spec.users}"
-  Budget: "${spec.budget}"
+  # 利用者数・予算などの数値はサーバーが難易度から型付きの要件として追加する
   Critical_Constraint: "${spec.constraint}"
   Domain_Specific_Constraint: "Please invent one technical constraint specific to '${currentScenario.title}' (e.g., real-time requirement, legacy system integration)."
 Behavior_Rules:
@@ -49,6 +49,7 @@ try {
           scenario_id: scenario.id,
           messages: newHistory,
           partner_role: scenario.partnerRole || 'ceo', 
+          difficulty: scenario.isCustom ? scenario.difficulty || 'medium' : undefined,
         }),
       });
 
@@ -17,6 +17,15 @@ export type NodeCategory = {
 
 export const NODE_CATEGORIES: NodeCategory[] = defs.categories;
 
+// グループとして扱うタイプ定義（新規作成時・読み込み時のラベル判定用）
+export const GROUP_TYPES = [
+  "VPC",
+  "VPC (Network)",
+  "Availability Zone",
+  "Subnet",
+  "Security Group",
+];
+
 export const nodeTypes = {
   custom: CustomNode,
   group: GroupNode,
@@ -66,6 +66,8 @@ export interface SimpleNodeData {
   position: { x: number; y: number };
   data: AppNodeData;
   style?: React.CSSProperties;
+  parentNode?: string;
+  extent?: "parent";
 }
 
 export interface SimpleEdgeData {
@@ -1,4 +1,79 @@
-import type { ProjectSaveData } from "../types";
+import type {
+  EvaluationResult,
+  ProjectSaveData,
+  SimpleNodeData,
+} from "../types";
+import { GROUP_TYPES } from "../constants/nodeTypes";
+
+// バックエンドが書き出す保存ファイル (formatVersion 2 以降) のノード
+// ノードは設計図の形 (type がコンポーネントの種類) で、色は appearance に分けて持つ
+interface ExportedNode {
+  id: string;
+  type: string;
+  label?: string;
+  description?: string;
+  position: { x: number; y: number };
+  width?: number;
+  height?: number;
+  parentNode?: string;
+}
+
+interface ExportedProject {
+  formatVersion: number;
+  version: string;
+  timestamp?: string;
+  projectId: string;
+  scenario: ProjectSaveData["scenario"];
+  memo?: string;
+  diagram: {
+    nodes: ExportedNode[];
+    edges: { id?: string; source: string; target: string }[];
+  };
+  appearance?: Record<string, { color?: string }>;
+  chatHistory?: ProjectSaveData["chatHistory"];
+  evaluation?: Omit<EvaluationResult, "score"> | null;
+}
+
+// 設計図の形のノードを、キャンバス (React Flow) のノードに戻す
+const toCanvasNode = (
+  node: ExportedNode,
+  appearance: ExportedProject["appearance"]
+): SimpleNodeData => {
+  const isGroup = GROUP_TYPES.includes(node.type);
+  return {
+    id: node.id,
+    type: isGroup ? "group" : "custom",
+    position: node.position,
+    data: {
+      label: node.label ?? node.type,
+      originalType: node.type,
+      description: node.description ?? "",
+      customColor: appearance?.[node.id]?.color,
+    },
+    style: isGroup
+      ? { width: node.width ?? 300, height: node.height ?? 200, zIndex: -1 }
+      : { zIndex: 10 },
+    parentNode: node.parentNode,
+    extent: node.parentNode ? "parent" : undefined,
+  };
+};
+
+const fromExportedProject = (loaded: ExportedProject): ProjectSaveData => ({
+  version: loaded.version,
+  timestamp: loaded.timestamp ?? "",
+  projectId: loaded.projectId,
+  scenario: loaded.scenario,
+  memo: loaded.memo ?? "",
+  diagram: {
+    nodes: loaded.diagram.nodes.map((n) => toCanvasNode(n, loaded.appearance)),
+    edges: loaded.diagram.edges,
+  },
+  chatHistory: loaded.chatHistory ?? [],
+  // 画面は score を表示するため、totalScore を写しておく
+  evaluation: loaded.evaluation
+    ? { ...loaded.evaluation, score: loaded.evaluation.totalScore }
+    : null,
+});
 
 /**
  * プロジェクトデータをローカルファイルとして保存（ダウンロード）する
@@ -41,14 +116,19 @@ export const loadProjectFromLocalFile = (
     const reader = new FileReader();
 
     reader.onload = (e) => {
-      const base64EncodedContent = e.target?.result as string;
+      const content = (e.target?.result as string).trim();
 
       try {
-        // Base64デコード (日本語対応)
-        const decodedJsonString = decodeURIComponent(
-          escape(atob(base64EncodedContent))
-        );
-        const loadedData = JSON.parse(decodedJsonString);
+        // サーバーから書き出したファイルは平文の JSON、このアプリで保存したファイルは Base64
+        const decodedJsonString = content.startsWith("{")
+          ? content
+          : // Base64デコード (日本語対応)
+            decodeURIComponent(escape(atob(content)));
+        const parsed = JSON.parse(decodedJsonString);
+        const loadedData =
+          typeof parsed.formatVersion === "number"
+            ? fromExportedProject(parsed as ExportedProject)
+            : parsed;
 
         // データ構造のバリデーション
         if (